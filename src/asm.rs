@@ -1,14 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use iced_x86::{Decoder, DecoderOptions, Formatter, Instruction, NasmFormatter};
 use iced_x86::code_asm::*;
+mod float_variables;
 mod lifetime;
+mod stack_objects;
 mod var_allocator;
 use crate::ir::{self, Data};
 use crate::parser;
 use crate::jit;
+use crate::predefined_functions::{self, ExternalFunction, InvalidString};
 
 use self::lifetime::LifetimeChecker;
-use self::var_allocator::VariableLocation;
+use self::var_allocator::{FloatLocation, VariableLocation};
 
 #[derive(Debug, PartialEq)]
 pub enum DataLocation {
@@ -22,8 +25,11 @@ fn get_data(data: &ir::Data, line: u64,  generator: &mut CodeGenerator) -> DataL
         ir::Data::Number(n) => {
             DataLocation::Number(n.to_owned())
         },
+        ir::Data::Float(f) => {
+            DataLocation::Number(f.to_bits() as i64)
+        },
         ir::Data::Variable(v) => {
-            match generator.variable_allocator.get(v, line, &mut generator.lifetime_checker, &mut generator.code_assembler) {
+            match generator.variable_allocator.get(v, line, &mut generator.lifetime_checker) {
                 var_allocator::VariableLocation::Register(r) => DataLocation::Register(r),
                 var_allocator::VariableLocation::Stack(s) => DataLocation::Stack(rbp + s)
             }
@@ -62,6 +68,10 @@ fn move_to(to: VariableLocation, from: DataLocation, generator: &mut CodeGenerat
 }
 
 fn generate_jump(label: &String, generator: &mut CodeGenerator) -> Result<(), IcedError>{
+    if generator.placed_labels.contains(label) {
+        //backwards jump -> loop back-edge
+        generate_tier_up_check(generator)?;
+    }
     match generator.labels.get_mut(label) {
         Some(l) => {
             generator.code_assembler.jmp(l.to_owned())?;
@@ -76,7 +86,7 @@ fn generate_jump(label: &String, generator: &mut CodeGenerator) -> Result<(), Ic
 }
 
 fn generate_jump_false(data: &ir::Data, label: &String, line: u64,  generator: &mut CodeGenerator) -> Result<(), IcedError> {
-    let d = get_data(data, line as u64, generator);
+    let d = get_data(data, line, generator);
     match d {
         DataLocation::Number(n) => {
             generator.code_assembler.mov(rax, n)?;
@@ -104,6 +114,7 @@ fn generate_jump_false(data: &ir::Data, label: &String, line: u64,  generator: &
 
 fn generate_label(label: &String, generator: &mut CodeGenerator) -> Result<(), IcedError> {
     generator.code_assembler.nop()?;
+    generator.placed_labels.insert(label.to_owned());
     match generator.labels.get_mut(label){
         None => {
             let mut l = generator.code_assembler.create_label();
@@ -117,16 +128,19 @@ fn generate_label(label: &String, generator: &mut CodeGenerator) -> Result<(), I
     Ok(())
 }
 
-fn generate_addition(res_var: &String, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator)-> Result<(), IcedError> {
-    let res_loc = generator.variable_allocator.get(&res_var, line, &mut generator.lifetime_checker, &mut generator.code_assembler);
+fn generate_addition(res_var: &str, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator)-> Result<(), IcedError> {
+    let res_loc = generator.variable_allocator.get(res_var, line, &mut generator.lifetime_checker);
     move_to(res_loc, get_data(data1, line, generator), generator)?;
 
     match get_data(data2, line, generator) {
         DataLocation::Number(n) => {
             match res_loc {
-                VariableLocation::Register(r) => generator.code_assembler.add( r, n as i32)?,
+                VariableLocation::Register(r) => {
+                    generator.code_assembler.mov(rax, n)?;
+                    generator.code_assembler.add(r, rax)?
+                },
                 VariableLocation::Stack(s) => {
-                    generator.code_assembler.mov(rbx, n)?;
+                    generator.code_assembler.mov(rax, n)?;
                     generator.code_assembler.add(rbp + s, rax)?
                 },
             }
@@ -152,16 +166,19 @@ fn generate_addition(res_var: &String, data1: &ir::Data, data2: &ir::Data, line:
 }
 
 
-fn generate_subtraction(res_var: &String, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator)-> Result<(), IcedError> {
-    let res_loc = generator.variable_allocator.get(&res_var, line, &mut generator.lifetime_checker, &mut generator.code_assembler);
+fn generate_subtraction(res_var: &str, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator)-> Result<(), IcedError> {
+    let res_loc = generator.variable_allocator.get(res_var, line, &mut generator.lifetime_checker);
     move_to(res_loc, get_data(data1, line, generator), generator)?;
 
     match get_data(data2, line, generator) {
         DataLocation::Number(n) => {
             match res_loc {
-                VariableLocation::Register(r) => generator.code_assembler.sub( r, n as i32)?,
+                VariableLocation::Register(r) => {
+                    generator.code_assembler.mov(rax, n)?;
+                    generator.code_assembler.sub(r, rax)?
+                },
                 VariableLocation::Stack(s) => {
-                    generator.code_assembler.mov(rbx, n)?;
+                    generator.code_assembler.mov(rax, n)?;
                     generator.code_assembler.sub(rbp + s, rax)?
                 },
             }
@@ -186,36 +203,59 @@ fn generate_subtraction(res_var: &String, data1: &ir::Data, data2: &ir::Data, li
     Ok(())
 }
 
-fn generate_division(res_var: &String, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator)-> Result<(), IcedError> {
-    let res_loc = generator.variable_allocator.get(&res_var, line, &mut generator.lifetime_checker, &mut generator.code_assembler);
+fn generate_division(res_var: &str, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator)-> Result<(), IcedError> {
+    let res_loc = generator.variable_allocator.get(res_var, line, &mut generator.lifetime_checker);
     generate_idiv(data1, data2, line, generator)?;
-    move_to(res_loc, DataLocation::Register(rax), generator)?;
     generator.code_assembler.pop(rdx)?;
+    move_to(res_loc, DataLocation::Register(rax), generator)?;
     Ok(())
 }
 
-fn generate_modulo(res_var: &String, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator)-> Result<(), IcedError> {
-    let res_loc = generator.variable_allocator.get(&res_var, line, &mut generator.lifetime_checker, &mut generator.code_assembler);
+fn generate_modulo(res_var: &str, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator)-> Result<(), IcedError> {
+    let res_loc = generator.variable_allocator.get(res_var, line, &mut generator.lifetime_checker);
     generate_idiv(data1, data2, line, generator)?;
-    move_to(res_loc, DataLocation::Register(rdx), generator)?;
+    generator.code_assembler.mov(rax, rdx)?;
     generator.code_assembler.pop(rdx)?;
+    move_to(res_loc, DataLocation::Register(rax), generator)?;
     Ok(())
 }
 
 
+//quotient in rax, remainder in rdx, the old rdx is pushed and has to be restored by the caller
+//idiv traps on a division by zero and on i64::MIN / -1, both are checked before and exit with a runtime error
 fn generate_idiv(data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator)-> Result<(), IcedError> {
-    move_to(VariableLocation::Register(rax), get_data(data1, line, generator), generator)?;
     move_to(VariableLocation::Register(rax), get_data(data1, line, generator), generator)?;
     move_to(VariableLocation::Register(rbx), get_data(data2, line, generator), generator)?;
     generator.code_assembler.push(rdx)?;
-    generator.code_assembler.mov(rdx, 0 as i64)?;
+    let mut end = generator.code_assembler.create_label();
+    if !matches!(data2, Data::Number(n) if *n != 0) {
+        generator.runtime_errors.used = true;
+        generator.code_assembler.test(rbx, rbx)?;
+        generator.code_assembler.jz(generator.runtime_errors.division_by_zero)?;
+    }
+    if !matches!(data2, Data::Number(n) if *n != -1) {
+        //dividing by -1 is a negation, it only overflows for i64::MIN
+        let mut divide = generator.code_assembler.create_label();
+        generator.runtime_errors.used = true;
+        generator.code_assembler.cmp(rbx, -1)?;
+        generator.code_assembler.jne(divide)?;
+        generator.code_assembler.neg(rax)?;
+        generator.code_assembler.jo(generator.runtime_errors.division_overflow)?;
+        generator.code_assembler.xor(edx, edx)?;
+        generator.code_assembler.jmp(end)?;
+        generator.code_assembler.set_label(&mut divide)?;
+    }
+    //sign extend rax into rdx
+    generator.code_assembler.cqo()?;
     generator.code_assembler.idiv(rbx)?;
+    generator.code_assembler.set_label(&mut end)?;
+    generator.code_assembler.nop()?;
     Ok(())
 }
 
 
-fn generate_multiplication(res_var: &String, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator)-> Result<(), IcedError> {
-    let res_loc = generator.variable_allocator.get(&res_var, line, &mut generator.lifetime_checker, &mut generator.code_assembler);
+fn generate_multiplication(res_var: &str, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator)-> Result<(), IcedError> {
+    let res_loc = generator.variable_allocator.get(res_var, line, &mut generator.lifetime_checker);
     move_to(res_loc, get_data(data1, line, generator), generator)?;
 
     match get_data(data2, line, generator) {
@@ -269,7 +309,8 @@ fn generate_compare(data1: &ir::Data, data2: &ir::Data, line: u64, generator: &m
             match data2_loc {
                 DataLocation::Number(n2) => {
                     generator.code_assembler.mov(rax, n1)?;
-                    generator.code_assembler.cmp(rax, n2 as i32)?;
+                    generator.code_assembler.mov(rbx, n2)?;
+                    generator.code_assembler.cmp(rax, rbx)?;
                 },
                 DataLocation::Register(r2) => {
                     generator.code_assembler.mov(rax, n1)?;
@@ -283,7 +324,10 @@ fn generate_compare(data1: &ir::Data, data2: &ir::Data, line: u64, generator: &m
         },
         DataLocation::Register(r1) => {
             match data2_loc {
-                DataLocation::Number(n2) => generator.code_assembler.cmp(r1, n2 as i32)?,
+                DataLocation::Number(n2) => {
+                    generator.code_assembler.mov(rbx, n2)?;
+                    generator.code_assembler.cmp(r1, rbx)?
+                },
                 DataLocation::Register(r2) => generator.code_assembler.cmp(r1, r2)?,
                 DataLocation::Stack(s2) => generator.code_assembler.cmp(r1, s2)?
             }
@@ -306,8 +350,8 @@ fn generate_compare(data1: &ir::Data, data2: &ir::Data, line: u64, generator: &m
     Ok(())
 }
 
-fn store_rax_in_var(var: &String, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
-    let res_loc: VariableLocation = generator.variable_allocator.get(&var, line, &mut generator.lifetime_checker, &mut generator.code_assembler);
+fn store_rax_in_var(var: &str, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    let res_loc: VariableLocation = generator.variable_allocator.get(var, line, &mut generator.lifetime_checker);
     match res_loc {
         VariableLocation::Register(r) => generator.code_assembler.mov(r, rax)?,
         VariableLocation::Stack(s) => generator.code_assembler.mov(rbp + s, rax)?
@@ -315,80 +359,339 @@ fn store_rax_in_var(var: &String, line: u64, generator: &mut CodeGenerator) -> R
     Ok(())
 }
 
-fn generate_greater(res_var: &String, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+fn generate_greater(res_var: &str, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
     generate_compare(data1, data2, line, generator)?;
-    generator.code_assembler.mov(rax, 0 as i64)?;
+    generator.code_assembler.mov(rax, 0_i64)?;
     generator.code_assembler.setg(al)?;
     store_rax_in_var(res_var, line, generator)?;
     Ok(())
 }
 
-fn generate_greater_equals(res_var: &String, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+fn generate_greater_equals(res_var: &str, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
     generate_compare(data1, data2, line, generator)?;
-    generator.code_assembler.mov(rax, 0 as i64)?;
+    generator.code_assembler.mov(rax, 0_i64)?;
     generator.code_assembler.setge(al)?;
     store_rax_in_var(res_var, line, generator)?;
     Ok(())
 }
 
-fn generate_less(res_var: &String, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+fn generate_less(res_var: &str, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
     generate_compare(data1, data2, line, generator)?;
-    generator.code_assembler.mov(rax, 0 as i64)?;
+    generator.code_assembler.mov(rax, 0_i64)?;
     generator.code_assembler.setl(al)?;
     store_rax_in_var(res_var, line, generator)?;
     Ok(())
 }
 
-fn generate_less_equals(res_var: &String, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+fn generate_less_equals(res_var: &str, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
     generate_compare(data1, data2, line, generator)?;
-    generator.code_assembler.mov(rax, 0 as i64)?;
+    generator.code_assembler.mov(rax, 0_i64)?;
     generator.code_assembler.setle(al)?;
     store_rax_in_var(res_var, line, generator)?;
     Ok(())
 }
 
-fn generate_equals(res_var: &String, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+fn generate_equals(res_var: &str, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
     generate_compare(data1, data2, line, generator)?;
-    generator.code_assembler.mov(rax, 0 as i64)?;
+    generator.code_assembler.mov(rax, 0_i64)?;
     generator.code_assembler.sete(al)?;
     store_rax_in_var(res_var, line, generator)?;
     Ok(())
 }
 
-fn generate_not_equals(res_var: &String, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+fn generate_not_equals(res_var: &str, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
     generate_compare(data1, data2, line, generator)?;
-    generator.code_assembler.mov(rax, 0 as i64)?;
+    generator.code_assembler.mov(rax, 0_i64)?;
     generator.code_assembler.setne(al)?;
     store_rax_in_var(res_var, line, generator)?;
     Ok(())
 }
 
-fn generate_and(res_var: &String, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
-    generate_compare(data1, &ir::Data::Number(0), line, generator)?;
-    generator.code_assembler.mov(rax, 0 as i64)?;
-    generator.code_assembler.setg(al)?;
-    generate_compare(data2, &ir::Data::Number(0), line, generator)?;
-    generator.code_assembler.mov(rbx, 0 as i64)?;
-    generator.code_assembler.setg(bl)?;
-    generator.code_assembler.and(rax, rbx)?;
+fn generate_bitwise_and(res_var: &str, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator)-> Result<(), IcedError> {
+    let res_loc = generator.variable_allocator.get(res_var, line, &mut generator.lifetime_checker);
+    move_to(res_loc, get_data(data1, line, generator), generator)?;
+
+    match get_data(data2, line, generator) {
+        DataLocation::Number(n) => {
+            generator.code_assembler.mov(rax, n)?;
+            match res_loc {
+                VariableLocation::Register(r) => generator.code_assembler.and(r, rax)?,
+                VariableLocation::Stack(s) => generator.code_assembler.and(rbp + s, rax)?
+            }
+        },
+        DataLocation::Register(rr) => {
+            match res_loc {
+                VariableLocation::Register(r) => generator.code_assembler.and(r, rr)?,
+                VariableLocation::Stack(s) => generator.code_assembler.and(rbp + s, rr)?,
+            }
+        },
+        DataLocation::Stack(st) => {
+            match res_loc {
+                VariableLocation::Register(r) => generator.code_assembler.and(r, st)?,
+                VariableLocation::Stack(s) => {
+                    generator.code_assembler.mov(rax, st)?;
+                    generator.code_assembler.and(rbp + s, rax)?;
+                },
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn generate_bitwise_or(res_var: &str, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator)-> Result<(), IcedError> {
+    let res_loc = generator.variable_allocator.get(res_var, line, &mut generator.lifetime_checker);
+    move_to(res_loc, get_data(data1, line, generator), generator)?;
+
+    match get_data(data2, line, generator) {
+        DataLocation::Number(n) => {
+            generator.code_assembler.mov(rax, n)?;
+            match res_loc {
+                VariableLocation::Register(r) => generator.code_assembler.or(r, rax)?,
+                VariableLocation::Stack(s) => generator.code_assembler.or(rbp + s, rax)?
+            }
+        },
+        DataLocation::Register(rr) => {
+            match res_loc {
+                VariableLocation::Register(r) => generator.code_assembler.or(r, rr)?,
+                VariableLocation::Stack(s) => generator.code_assembler.or(rbp + s, rr)?,
+            }
+        },
+        DataLocation::Stack(st) => {
+            match res_loc {
+                VariableLocation::Register(r) => generator.code_assembler.or(r, st)?,
+                VariableLocation::Stack(s) => {
+                    generator.code_assembler.mov(rax, st)?;
+                    generator.code_assembler.or(rbp + s, rax)?;
+                },
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn generate_bitwise_xor(res_var: &str, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator)-> Result<(), IcedError> {
+    let res_loc = generator.variable_allocator.get(res_var, line, &mut generator.lifetime_checker);
+    move_to(res_loc, get_data(data1, line, generator), generator)?;
+
+    match get_data(data2, line, generator) {
+        DataLocation::Number(n) => {
+            generator.code_assembler.mov(rax, n)?;
+            match res_loc {
+                VariableLocation::Register(r) => generator.code_assembler.xor(r, rax)?,
+                VariableLocation::Stack(s) => generator.code_assembler.xor(rbp + s, rax)?
+            }
+        },
+        DataLocation::Register(rr) => {
+            match res_loc {
+                VariableLocation::Register(r) => generator.code_assembler.xor(r, rr)?,
+                VariableLocation::Stack(s) => generator.code_assembler.xor(rbp + s, rr)?,
+            }
+        },
+        DataLocation::Stack(st) => {
+            match res_loc {
+                VariableLocation::Register(r) => generator.code_assembler.xor(r, st)?,
+                VariableLocation::Stack(s) => {
+                    generator.code_assembler.mov(rax, st)?;
+                    generator.code_assembler.xor(rbp + s, rax)?;
+                },
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum Shift {
+    Left,
+    Right,
+    RightLogical
+}
+
+fn generate_shift(res_var: &str, data1: &ir::Data, data2: &ir::Data, shift: Shift, line: u64, generator: &mut CodeGenerator)-> Result<(), IcedError> {
+    move_to(VariableLocation::Register(rax), get_data(data1, line, generator), generator)?;
+    match get_data(data2, line, generator) {
+        DataLocation::Number(n) => {
+            //the cpu only uses the lowest 6 bits of the shift count
+            let count = (n & 63) as u32;
+            match shift {
+                Shift::Left => generator.code_assembler.shl(rax, count)?,
+                Shift::Right => generator.code_assembler.sar(rax, count)?,
+                Shift::RightLogical => generator.code_assembler.shr(rax, count)?
+            }
+        },
+        data2_loc => {
+            //variable shift counts have to be in cl
+            move_to(VariableLocation::Register(rbx), data2_loc, generator)?;
+            generator.code_assembler.push(rcx)?;
+            generator.code_assembler.mov(rcx, rbx)?;
+            match shift {
+                Shift::Left => generator.code_assembler.shl(rax, cl)?,
+                Shift::Right => generator.code_assembler.sar(rax, cl)?,
+                Shift::RightLogical => generator.code_assembler.shr(rax, cl)?
+            }
+            generator.code_assembler.pop(rcx)?;
+        }
+    }
     store_rax_in_var(res_var, line, generator)?;
     Ok(())
 }
 
-fn generate_or(res_var: &String, data1: &ir::Data, data2: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
-    generate_compare(data1, &ir::Data::Number(0), line, generator)?;
-    generator.code_assembler.mov(rax, 0 as i64)?;
-    generator.code_assembler.setg(al)?;
-    generate_compare(data2, &ir::Data::Number(0), line, generator)?;
-    generator.code_assembler.mov(rbx, 0 as i64)?;
-    generator.code_assembler.setg(bl)?;
-    generator.code_assembler.or(rax, rbx)?;
+fn generate_negation(res_var: &str, data: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    let res_loc: VariableLocation = generator.variable_allocator.get(res_var, line, &mut generator.lifetime_checker);
+    move_to(res_loc, get_data(data, line, generator), generator)?;
+    match res_loc {
+        VariableLocation::Register(r) => generator.code_assembler.neg(r)?,
+        VariableLocation::Stack(s) => generator.code_assembler.neg(qword_ptr(rbp + s))?
+    };
+    Ok(())
+}
+
+fn generate_logic_not(res_var: &str, data: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    //every value except 0 is true
+    move_to(VariableLocation::Register(rbx), get_data(data, line, generator), generator)?;
+    generator.code_assembler.mov(rax, 0_i64)?;
+    generator.code_assembler.test(rbx, rbx)?;
+    generator.code_assembler.sete(al)?;
     store_rax_in_var(res_var, line, generator)?;
     Ok(())
 }
 
-fn generate_assignment(res_var: &String, data: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
-    let res_loc: VariableLocation = generator.variable_allocator.get(&res_var, line, &mut generator.lifetime_checker, &mut generator.code_assembler);
+fn generate_bitwise_not(res_var: &str, data: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    let res_loc: VariableLocation = generator.variable_allocator.get(res_var, line, &mut generator.lifetime_checker);
+    move_to(res_loc, get_data(data, line, generator), generator)?;
+    match res_loc {
+        VariableLocation::Register(r) => generator.code_assembler.not(r)?,
+        VariableLocation::Stack(s) => generator.code_assembler.not(qword_ptr(rbp + s))?
+    };
+    Ok(())
+}
+
+//float variables are kept in xmm registers, all other values are the bits of the float
+fn load_float(data: &ir::Data, to: AsmRegisterXmm, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    if let ir::Data::Variable(v) = data {
+        if generator.variable_allocator.is_float(v) {
+            match generator.variable_allocator.get_float(v, line, &mut generator.lifetime_checker) {
+                FloatLocation::Register(x) => generator.code_assembler.movapd(to, x)?,
+                FloatLocation::Stack(s) => generator.code_assembler.movsd_2(to, qword_ptr(rbp + s))?
+            }
+            return Ok(());
+        }
+    }
+    match get_data(data, line, generator) {
+        DataLocation::Number(n) => {
+            generator.code_assembler.mov(rax, n)?;
+            generator.code_assembler.movq(to, rax)?;
+        },
+        DataLocation::Register(r) => generator.code_assembler.movq(to, r)?,
+        DataLocation::Stack(s) => generator.code_assembler.movsd_2(to, qword_ptr(s))?
+    }
+    Ok(())
+}
+
+//the result is in xmm0
+fn store_float(res_var: &str, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    if generator.variable_allocator.is_float(res_var) {
+        match generator.variable_allocator.get_float(res_var, line, &mut generator.lifetime_checker) {
+            FloatLocation::Register(x) => generator.code_assembler.movapd(x, xmm0)?,
+            FloatLocation::Stack(s) => generator.code_assembler.movsd_2(qword_ptr(rbp + s), xmm0)?
+        }
+        return Ok(());
+    }
+    match generator.variable_allocator.get(res_var, line, &mut generator.lifetime_checker) {
+        VariableLocation::Register(r) => generator.code_assembler.movq(r, xmm0)?,
+        VariableLocation::Stack(s) => generator.code_assembler.movsd_2(qword_ptr(rbp + s), xmm0)?
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum FloatOperation {
+    Addition,
+    Subtraction,
+    Multiplication,
+    Division
+}
+
+fn generate_float_arithmetic(res_var: &str, data1: &ir::Data, data2: &ir::Data, operation: FloatOperation, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    load_float(data1, xmm0, line, generator)?;
+    load_float(data2, xmm1, line, generator)?;
+    match operation {
+        FloatOperation::Addition => generator.code_assembler.addsd(xmm0, xmm1)?,
+        FloatOperation::Subtraction => generator.code_assembler.subsd(xmm0, xmm1)?,
+        FloatOperation::Multiplication => generator.code_assembler.mulsd(xmm0, xmm1)?,
+        FloatOperation::Division => generator.code_assembler.divsd(xmm0, xmm1)?
+    }
+    store_float(res_var, line, generator)
+}
+
+#[derive(Clone, Copy)]
+enum FloatComparison {
+    Greater,
+    GreaterEquals,
+    Less,
+    LessEquals,
+    Equals,
+    NotEquals
+}
+
+//comparisons with NaN are false (except !=), ucomisd sets ZF, PF and CF if one of the operands is NaN
+fn generate_float_comparison(res_var: &str, data1: &ir::Data, data2: &ir::Data, comparison: FloatComparison, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    load_float(data1, xmm0, line, generator)?;
+    load_float(data2, xmm1, line, generator)?;
+    match comparison {
+        //a < b is b > a (seta and setae are false for unordered operands)
+        FloatComparison::Less | FloatComparison::LessEquals => generator.code_assembler.ucomisd(xmm1, xmm0)?,
+        _ => generator.code_assembler.ucomisd(xmm0, xmm1)?
+    }
+    generator.code_assembler.mov(rax, 0_i64)?;
+    match comparison {
+        FloatComparison::Greater | FloatComparison::Less => generator.code_assembler.seta(al)?,
+        FloatComparison::GreaterEquals | FloatComparison::LessEquals => generator.code_assembler.setae(al)?,
+        FloatComparison::Equals => {
+            generator.code_assembler.sete(al)?;
+            generator.code_assembler.setnp(bl)?;
+            generator.code_assembler.and(al, bl)?;
+        },
+        FloatComparison::NotEquals => {
+            generator.code_assembler.setne(al)?;
+            generator.code_assembler.setp(bl)?;
+            generator.code_assembler.or(al, bl)?;
+        }
+    }
+    store_rax_in_var(res_var, line, generator)
+}
+
+//flips the sign bit
+fn generate_float_negation(res_var: &str, data: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    load_float(data, xmm0, line, generator)?;
+    generator.code_assembler.mov(rax, i64::MIN)?;
+    generator.code_assembler.movq(xmm1, rax)?;
+    generator.code_assembler.xorpd(xmm0, xmm1)?;
+    store_float(res_var, line, generator)
+}
+
+fn generate_int_to_float(res_var: &str, data: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    move_to(VariableLocation::Register(rax), get_data(data, line, generator), generator)?;
+    generator.code_assembler.cvtsi2sd(xmm0, rax)?;
+    store_float(res_var, line, generator)
+}
+
+//truncates, NaN and values that are out of range become i64::MIN (see types::float_to_int)
+fn generate_float_to_int(res_var: &str, data: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    load_float(data, xmm0, line, generator)?;
+    generator.code_assembler.cvttsd2si(rax, xmm0)?;
+    store_rax_in_var(res_var, line, generator)
+}
+
+fn generate_assignment(res_var: &str, data: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    if generator.variable_allocator.is_float(res_var) {
+        load_float(data, xmm0, line, generator)?;
+        return store_float(res_var, line, generator);
+    }
+    let res_loc: VariableLocation = generator.variable_allocator.get(res_var, line, &mut generator.lifetime_checker);
     let data =  get_data(data, line, generator);
     move_to(res_loc, data, generator)?;
     Ok(())
@@ -396,6 +699,292 @@ fn generate_assignment(res_var: &String, data: &ir::Data, line: u64, generator:
 
 
 
+//globals are stored in the data section of the function tracker, they are accessed with absolute addresses
+fn generate_load_global(res_var: &str, name: &str, function_tracker: &mut jit::FunctionTracker, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    let address = function_tracker.get_global_address(name);
+    generator.code_assembler.mov(rax, address)?;
+    generator.code_assembler.mov(rax, qword_ptr(rax))?;
+    store_rax_in_var(res_var, line, generator)?;
+    Ok(())
+}
+
+fn generate_store_global(name: &str, data: &ir::Data, function_tracker: &mut jit::FunctionTracker, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    let address = function_tracker.get_global_address(name);
+    move_to(VariableLocation::Register(rbx), get_data(data, line, generator), generator)?;
+    generator.code_assembler.mov(rax, address)?;
+    generator.code_assembler.mov(qword_ptr(rax), rbx)?;
+    Ok(())
+}
+
+//string literals are stored in the read-only data section of the function tracker
+fn generate_load_string(res_var: &str, s: &str, function_tracker: &mut jit::FunctionTracker, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    let address = function_tracker.get_string_address(s);
+    generator.code_assembler.mov(rax, address)?;
+    store_rax_in_var(res_var, line, generator)?;
+    Ok(())
+}
+
+//looks up the handle in rax in the object table of the runtime, the address of the array is in rax afterwards
+//(the handle stays in rax if it is invalid)
+fn generate_array_address(generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    let invalid_array = generator.runtime_errors.invalid_array;
+    generate_object_address(rbx, invalid_array, generator)?;
+    //structs have a negative header
+    generator.code_assembler.cmp(qword_ptr(rbx), 0)?;
+    generator.code_assembler.jl(invalid_array)?;
+    generator.code_assembler.mov(rax, rbx)?;
+    Ok(())
+}
+
+//looks up the handle in rax in the object table of the runtime and writes the address of the object into res
+//(jumps to error if the handle is invalid, rax still contains the handle then unless it is res)
+fn generate_object_address(res: AsmRegister64, error: CodeLabel, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    let object_table = std::mem::offset_of!(jit::Runtime, object_table) as i32;
+    let num_handles = std::mem::offset_of!(jit::Runtime, num_handles) as i32;
+    generator.runtime_errors.used = true;
+    generator.code_assembler.mov(rbx, generator.runtime)?;
+    //unsigned comparison -> negative handles are invalid as well
+    generator.code_assembler.cmp(rax, qword_ptr(rbx + num_handles))?;
+    generator.code_assembler.jae(error)?;
+    generator.code_assembler.mov(rbx, qword_ptr(rbx + object_table))?;
+    generator.code_assembler.mov(res, qword_ptr(rbx + rax * 8))?;
+    generator.code_assembler.test(res, res)?;
+    generator.code_assembler.jz(error)?;
+    Ok(())
+}
+
+//address of the array in rax, index in rbx
+fn generate_bounds_check(array: &ir::Data, index: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    move_to(VariableLocation::Register(rax), get_data(array, line, generator), generator)?;
+    generate_array_address(generator)?;
+    move_to(VariableLocation::Register(rbx), get_data(index, line, generator), generator)?;
+    //unsigned comparison -> negative indices are out of bounds as well
+    generator.code_assembler.cmp(rbx, qword_ptr(rax))?;
+    generator.code_assembler.jae(generator.runtime_errors.index_out_of_bounds)?;
+    Ok(())
+}
+
+//the elements are stored after the length
+fn generate_array_load(res_var: &str, array: &ir::Data, index: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    generate_bounds_check(array, index, line, generator)?;
+    generator.code_assembler.mov(rax, qword_ptr(rax + rbx * 8 + 8))?;
+    store_rax_in_var(res_var, line, generator)?;
+    Ok(())
+}
+
+fn generate_array_store(array: &ir::Data, index: &ir::Data, value: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    generate_bounds_check(array, index, line, generator)?;
+    generator.code_assembler.lea(rax, qword_ptr(rax + rbx * 8 + 8))?;
+    move_to(VariableLocation::Register(rbx), get_data(value, line, generator), generator)?;
+    generator.code_assembler.mov(qword_ptr(rax), rbx)?;
+    Ok(())
+}
+
+fn generate_array_length(res_var: &str, array: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    move_to(VariableLocation::Register(rax), get_data(array, line, generator), generator)?;
+    generate_array_address(generator)?;
+    generator.code_assembler.mov(rax, qword_ptr(rax))?;
+    store_rax_in_var(res_var, line, generator)?;
+    Ok(())
+}
+
+//arrays are allocated by the runtime, it returns the handle of the array or 0 (and sets the error) if the size is invalid
+fn generate_new_array(res_var: &str, size: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    #[cfg(windows)]
+    let num_arg_regs = 4;
+
+    #[cfg(unix)]
+    let num_arg_regs = 6;
+
+    let args = vec![Data::Number(generator.runtime as i64), size.to_owned()];
+    let saved_regs = save_registers(args.len() as u64, generator)?;
+    if (std::cmp::max(args.len() as i64 - num_arg_regs, 0) as u64 + saved_regs.len() as u64).is_multiple_of(2){
+        //fix stack allignment
+        generator.code_assembler.push(rbx)?;
+    }
+    let pushed_args = set_arguments(&args, line, generator)?;
+    generator.code_assembler.call(jit::jit_new_array as *const () as u64)?;
+    if (pushed_args + saved_regs.len() as u64).is_multiple_of(2){
+        generator.code_assembler.pop(rbx)?;
+    }
+    unset_arguments(pushed_args, generator)?;
+    restore_registers(saved_regs, generator)?;
+
+    generator.runtime_errors.used = true;
+    generator.code_assembler.test(rax, rax)?;
+    generator.code_assembler.jz(generator.runtime_errors.exit)?;
+    store_rax_in_var(res_var, line, generator)?;
+    Ok(())
+}
+
+//structs are allocated in the stack frame if they don't escape (see stack_objects), otherwise by the runtime
+//(it returns a handle like for arrays). the header is !id, the fields follow in the declaration order
+fn generate_new_struct(res_var: &String, name: &String, fields: &Vec<(String, Data)>, function_tracker: &mut jit::FunctionTracker, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    #[cfg(windows)]
+    let num_arg_regs = 4;
+
+    #[cfg(unix)]
+    let num_arg_regs = 6;
+
+    let (id, declaration) = function_tracker.get_struct(name);
+    let num_fields = declaration.fields.len() as i64;
+    if generator.stack_objects.contains(res_var) {
+        let offset = generator.variable_allocator.allocate_stack_object(num_fields as u64 + 1);
+        generator.code_assembler.lea(rax, qword_ptr(rbp + offset))?;
+        generator.code_assembler.mov(qword_ptr(rax), !id as i32)?;
+    }else{
+        let args = vec![Data::Number(generator.runtime as i64), Data::Number(id), Data::Number(num_fields)];
+        let saved_regs = save_registers(args.len() as u64, generator)?;
+        if (std::cmp::max(args.len() as i64 - num_arg_regs, 0) as u64 + saved_regs.len() as u64).is_multiple_of(2){
+            //fix stack allignment
+            generator.code_assembler.push(rbx)?;
+        }
+        let pushed_args = set_arguments(&args, line, generator)?;
+        generator.code_assembler.call(jit::jit_new_struct as *const () as u64)?;
+        if (pushed_args + saved_regs.len() as u64).is_multiple_of(2){
+            generator.code_assembler.pop(rbx)?;
+        }
+        unset_arguments(pushed_args, generator)?;
+        restore_registers(saved_regs, generator)?;
+        //the heap is full
+        generator.runtime_errors.used = true;
+        generator.code_assembler.test(rax, rax)?;
+        generator.code_assembler.jz(generator.runtime_errors.exit)?;
+        generator.code_assembler.push(rax)?;
+        let object_table = std::mem::offset_of!(jit::Runtime, object_table) as i32;
+        generator.code_assembler.mov(rbx, generator.runtime)?;
+        generator.code_assembler.mov(rbx, qword_ptr(rbx + object_table))?;
+        generator.code_assembler.mov(rax, qword_ptr(rbx + rax * 8))?;
+    }
+    for (field, data) in fields {
+        let idx = declaration.fields.iter().position(|f| f == field).expect("fields are checked by the semantic analysis") as i32;
+        move_to(VariableLocation::Register(rbx), get_data(data, line, generator), generator)?;
+        generator.code_assembler.mov(qword_ptr(rax + (idx + 1) * 8), rbx)?;
+    }
+    if !generator.stack_objects.contains(res_var) {
+        //the handle
+        generator.code_assembler.pop(rax)?;
+    }
+    store_rax_in_var(res_var, line, generator)?;
+    Ok(())
+}
+
+//the type of the object is only known at runtime -> the offset of the field is looked up with the id in the header
+//(arrays have a positive header). variables that can contain anything else than a struct in the stack frame hold a
+//handle that is looked up in the object table first. the address of the field is in rax afterwards
+fn generate_field_address(object: &Data, field: &str, function_tracker: &mut jit::FunctionTracker, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    let (field_id, structs, offsets) = function_tracker.get_field(field);
+    let num_structs = function_tracker.get_num_structs();
+    let error = generator.code_assembler.create_label();
+    generator.runtime_errors.used = true;
+    generator.runtime_errors.invalid_fields.push((error, field_id));
+    move_to(VariableLocation::Register(rax), get_data(object, line, generator), generator)?;
+    if !matches!(object, Data::Variable(var) if generator.stack_objects.contains(var)) {
+        generate_object_address(rax, error, generator)?;
+    }
+    match structs.as_slice() {
+        //only one struct has the field
+        [(id, offset)] => {
+            generator.code_assembler.cmp(qword_ptr(rax), !*id as i32)?;
+            generator.code_assembler.jne(error)?;
+            generator.code_assembler.add(rax, *offset as i32)?;
+        },
+        _ => {
+            generator.code_assembler.mov(rbx, qword_ptr(rax))?;
+            generator.code_assembler.not(rbx)?;
+            generator.code_assembler.cmp(rbx, num_structs as i32)?;
+            generator.code_assembler.jae(error)?;
+            generator.code_assembler.push(rax)?;
+            generator.code_assembler.mov(rax, offsets)?;
+            generator.code_assembler.mov(rbx, qword_ptr(rax + rbx * 8))?;
+            generator.code_assembler.pop(rax)?;
+            generator.code_assembler.test(rbx, rbx)?;
+            generator.code_assembler.jz(error)?;
+            generator.code_assembler.add(rax, rbx)?;
+        }
+    }
+    Ok(())
+}
+
+fn generate_field_load(res_var: &str, object: &Data, field: &str, function_tracker: &mut jit::FunctionTracker, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    generate_field_address(object, field, function_tracker, line, generator)?;
+    generator.code_assembler.mov(rax, qword_ptr(rax))?;
+    store_rax_in_var(res_var, line, generator)?;
+    Ok(())
+}
+
+fn generate_field_store(object: &Data, field: &str, value: &Data, function_tracker: &mut jit::FunctionTracker, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    generate_field_address(object, field, function_tracker, line, generator)?;
+    move_to(VariableLocation::Register(rbx), get_data(value, line, generator), generator)?;
+    generator.code_assembler.mov(qword_ptr(rax), rbx)?;
+    Ok(())
+}
+
+//the error is written into the runtime, afterwards the stack of the entry function is restored and it returns
+fn generate_runtime_error_handlers(generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    let error = std::mem::offset_of!(jit::Runtime, error) as i32;
+    let value = std::mem::offset_of!(jit::Runtime, value) as i32;
+    let length = std::mem::offset_of!(jit::Runtime, length) as i32;
+    let stack_pointer = std::mem::offset_of!(jit::Runtime, stack_pointer) as i32;
+
+    //array in rax, index in rbx
+    generator.code_assembler.set_label(&mut generator.runtime_errors.index_out_of_bounds)?;
+    generator.code_assembler.mov(rax, qword_ptr(rax))?;
+    generator.code_assembler.mov(rcx, generator.runtime)?;
+    generator.code_assembler.mov(qword_ptr(rcx + length), rax)?;
+    generator.code_assembler.mov(qword_ptr(rcx + value), rbx)?;
+    generator.code_assembler.mov(qword_ptr(rcx + error), jit::RUNTIME_ERROR_INDEX_OUT_OF_BOUNDS)?;
+    generator.code_assembler.jmp(generator.runtime_errors.exit)?;
+
+    //every field access has its own label that loads the index of the field into rbx
+    if !generator.runtime_errors.invalid_fields.is_empty() {
+        let mut invalid_field = generator.code_assembler.create_label();
+        for (mut label, field_id) in std::mem::take(&mut generator.runtime_errors.invalid_fields) {
+            generator.code_assembler.set_label(&mut label)?;
+            generator.code_assembler.mov(rbx, field_id)?;
+            generator.code_assembler.jmp(invalid_field)?;
+        }
+        generator.code_assembler.set_label(&mut invalid_field)?;
+        generator.code_assembler.mov(rcx, generator.runtime)?;
+        generator.code_assembler.mov(qword_ptr(rcx + value), rbx)?;
+        generator.code_assembler.mov(qword_ptr(rcx + error), jit::RUNTIME_ERROR_INVALID_FIELD)?;
+        generator.code_assembler.jmp(generator.runtime_errors.exit)?;
+    }
+    //error of the string functions in rbx, it's reset for the next call
+    generator.code_assembler.set_label(&mut generator.runtime_errors.invalid_string)?;
+    generator.code_assembler.mov(rax, qword_ptr(rbx + std::mem::offset_of!(InvalidString, value) as i32))?;
+    generator.code_assembler.mov(qword_ptr(rbx + std::mem::offset_of!(InvalidString, occured) as i32), 0)?;
+    generator.code_assembler.mov(rcx, generator.runtime)?;
+    generator.code_assembler.mov(qword_ptr(rcx + value), rax)?;
+    generator.code_assembler.mov(qword_ptr(rcx + error), jit::RUNTIME_ERROR_INVALID_STRING)?;
+    generator.code_assembler.jmp(generator.runtime_errors.exit)?;
+
+    generator.code_assembler.set_label(&mut generator.runtime_errors.division_by_zero)?;
+    generator.code_assembler.mov(rcx, generator.runtime)?;
+    generator.code_assembler.mov(qword_ptr(rcx + error), jit::RUNTIME_ERROR_DIVISION_BY_ZERO)?;
+    generator.code_assembler.jmp(generator.runtime_errors.exit)?;
+
+    generator.code_assembler.set_label(&mut generator.runtime_errors.division_overflow)?;
+    generator.code_assembler.mov(rcx, generator.runtime)?;
+    generator.code_assembler.mov(qword_ptr(rcx + error), jit::RUNTIME_ERROR_DIVISION_OVERFLOW)?;
+    generator.code_assembler.jmp(generator.runtime_errors.exit)?;
+
+    //array in rax
+    generator.code_assembler.set_label(&mut generator.runtime_errors.invalid_array)?;
+    generator.code_assembler.mov(rcx, generator.runtime)?;
+    generator.code_assembler.mov(qword_ptr(rcx + value), rax)?;
+    generator.code_assembler.mov(qword_ptr(rcx + error), jit::RUNTIME_ERROR_INVALID_ARRAY)?;
+
+    generator.code_assembler.set_label(&mut generator.runtime_errors.exit)?;
+    generator.code_assembler.mov(rax, generator.runtime)?;
+    generator.code_assembler.mov(rsp, qword_ptr(rax + stack_pointer))?;
+    for reg in ENTRY_SAVED_REGISTERS.iter().rev() {
+        generator.code_assembler.pop(*reg)?;
+    }
+    generator.code_assembler.ret()?;
+    Ok(())
+}
+
 fn generate_return(data: &ir::Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
     let data_loc = get_data(data, line, generator);
     move_to(VariableLocation::Register(rax), data_loc, generator)?;
@@ -414,8 +1003,20 @@ fn generate_return(data: &ir::Data, line: u64, generator: &mut CodeGenerator) ->
 }
 
 
-fn set_arguments(args: &Vec<Data>, line: u64, generator: &mut CodeGenerator) -> Result<u64, IcedError> {
-    #[cfg(target_os = "windows")]
+fn push_data(data: &Data, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    match get_data(data, line, generator) {
+        DataLocation::Number(n) => {
+            generator.code_assembler.mov(rbx, n)?;
+            generator.code_assembler.push(rbx)?
+        },
+        DataLocation::Register(r) => generator.code_assembler.push(r)?,
+        DataLocation::Stack(s) => generator.code_assembler.push(qword_ptr(s))?
+    };
+    Ok(())
+}
+
+fn set_arguments(args: &[Data], line: u64, generator: &mut CodeGenerator) -> Result<u64, IcedError> {
+    #[cfg(windows)]
     let arg_regs = [
         rcx,
         rdx,
@@ -423,7 +1024,7 @@ fn set_arguments(args: &Vec<Data>, line: u64, generator: &mut CodeGenerator) ->
         r9
     ];
 
-    #[cfg(target_os = "linux")]
+    #[cfg(unix)]
     let arg_regs = [
         rdi,
         rsi,
@@ -435,35 +1036,20 @@ fn set_arguments(args: &Vec<Data>, line: u64, generator: &mut CodeGenerator) ->
 
     let mut pushed_args = 0;
 
-    for (i, arg) in args.iter().enumerate() {
-        if i < arg_regs.len() {
-            match arg {
-                Data::Number(n) => generator.code_assembler.mov(arg_regs[i], n.to_owned())?,
-                Data::Variable(v) => {
-                    match generator.variable_allocator.get(v, line, &mut generator.lifetime_checker, &mut generator.code_assembler){
-                        VariableLocation::Register(r) => generator.code_assembler.mov(arg_regs[i], r)?,
-                        VariableLocation::Stack(s) => generator.code_assembler.mov(arg_regs[i], rbp + s)?
-                    };
-                }
-            };
-        }else{
-            pushed_args += 1;
-            match arg {
-                Data::Number(n) =>{
-                    generator.code_assembler.mov(rbx, n.to_owned())?;
-                    generator.code_assembler.push(rbx)?
-                },
-                Data::Variable(v) => {
-                    match generator.variable_allocator.get(v, 0, &mut generator.lifetime_checker, &mut generator.code_assembler){
-                        VariableLocation::Register(r) => generator.code_assembler.push(r)?,
-                        VariableLocation::Stack(s) => {
-                            generator.code_assembler.mov(rbx, rbp + s)?;
-                            generator.code_assembler.push(rbx)?;
-                        }
-                    };
-                }
-            };
-        }
+    //stack arguments, the first one is pushed first
+    for arg in args.iter().skip(arg_regs.len()) {
+        pushed_args += 1;
+        push_data(arg, line, generator)?;
+    }
+
+    //the argument registers can hold variables that are arguments themselves
+    //-> push all values first and pop them into the argument registers afterwards
+    let num_reg_args = std::cmp::min(args.len(), arg_regs.len());
+    for arg in args.iter().take(num_reg_args) {
+        push_data(arg, line, generator)?;
+    }
+    for reg in arg_regs.iter().take(num_reg_args).rev() {
+        generator.code_assembler.pop(reg.to_owned())?;
     }
     Ok(pushed_args)
 }
@@ -478,19 +1064,31 @@ fn unset_arguments(pushed_args: u64, generator: &mut CodeGenerator) -> Result<()
     Ok(())
 }
 
-fn save_registers(mut number_of_args: u64, generator: &mut CodeGenerator) -> Result<Vec<AsmRegister64>, IcedError>{
+//xmm registers are saved in 8 byte stack slots after the general purpose registers
+struct SavedRegisters {
+    registers: Vec<AsmRegister64>,
+    float_registers: Vec<AsmRegisterXmm>
+}
+
+impl SavedRegisters {
+    fn len(&self) -> usize {
+        self.registers.len() + self.float_registers.len()
+    }
+}
+
+fn save_registers(mut number_of_args: u64, generator: &mut CodeGenerator) -> Result<SavedRegisters, IcedError>{
     let mut saved_vec = vec![];
 
-    #[cfg(target_os = "windows")]
+    #[cfg(windows)]
     let registers = [rcx, rdx, r8, r9, rsi, rdi, r10, r11];
 
-    #[cfg(target_os = "windows")]
+    #[cfg(windows)]
     let max_reg_args = 4;
 
-    #[cfg(target_os = "linux")]
+    #[cfg(unix)]
     let registers = [rdi, rsi, rdx, rcx, r8, r9, r10, r11];
 
-    #[cfg(target_os = "linux")]
+    #[cfg(unix)]
     let max_reg_args = 6;
 
     //cap number of args
@@ -507,61 +1105,170 @@ fn save_registers(mut number_of_args: u64, generator: &mut CodeGenerator) -> Res
         }
     }
 
-    Ok(saved_vec)
+    let float_registers = generator.variable_allocator.get_allocated_float_registers();
+    for reg in float_registers.iter().copied() {
+        generator.code_assembler.sub(rsp, 8)?;
+        generator.code_assembler.movsd_2(qword_ptr(rsp), reg)?;
+    }
+
+    Ok(SavedRegisters { registers: saved_vec, float_registers })
 }
 
-fn restore_registers(saved_regs: Vec<AsmRegister64>, generator: &mut CodeGenerator) -> Result<(), IcedError>{
-    for reg in saved_regs.iter().copied().rev() {
+fn restore_registers(saved_regs: SavedRegisters, generator: &mut CodeGenerator) -> Result<(), IcedError>{
+    for reg in saved_regs.float_registers.iter().copied().rev() {
+        generator.code_assembler.movsd_2(reg, qword_ptr(rsp))?;
+        generator.code_assembler.add(rsp, 8)?;
+    }
+    for reg in saved_regs.registers.iter().copied().rev() {
         generator.code_assembler.pop(reg)?;
     }
     Ok(())
 }
 
+enum ArgumentRegister {
+    Integer(AsmRegister64),
+    Float(AsmRegisterXmm)
+}
+
+//float arguments are passed in xmm0 - xmm7 and the result is returned in xmm0 (System V, windows uses the position of the argument).
+//the external functions are predefined, so all arguments fit into registers
+fn generate_float_external_call(res_var: &str, function: &ExternalFunction, args: &[Data], line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    #[cfg(windows)]
+    let arg_regs = [rcx, rdx, r8, r9];
+
+    #[cfg(unix)]
+    let arg_regs = [rdi, rsi, rdx, rcx, r8, r9];
 
-fn generate_function_call(res_var: &String, fun_name: &String, args: &Vec<Data>, function_tracker: &mut jit::FunctionTracker, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    let float_arg_regs = [xmm0, xmm1, xmm2, xmm3, xmm4, xmm5, xmm6, xmm7];
 
-    #[cfg(target_os = "windows")]
+    let is_float = |i: usize| function.parameter_types.get(i) == Some(&Some(parser::Type::Float));
+    let num_int_args = (0..args.len()).filter(|i| !is_float(*i)).count();
+    let saved_regs = save_registers(num_int_args as u64, generator)?;
+    if saved_regs.len() % 2 == 0 {
+        //fix stack allignment
+        generator.code_assembler.push(rbx)?;
+    }
+
+    //the register depends on the number of previous arguments of the same kind (on windows on the position)
+    let mut argument_registers = vec![];
+    let (mut int_index, mut float_index) = (0, 0);
+    for i in 0..args.len() {
+        #[cfg(windows)]
+        {
+            int_index = i;
+            float_index = i;
+        }
+        if is_float(i) {
+            argument_registers.push(ArgumentRegister::Float(float_arg_regs[float_index]));
+            float_index += 1;
+        }else{
+            argument_registers.push(ArgumentRegister::Integer(arg_regs[int_index]));
+            int_index += 1;
+        }
+    }
+
+    //push all values first and pop them into the argument registers afterwards (like set_arguments)
+    for arg in args {
+        push_data(arg, line, generator)?;
+    }
+    for register in argument_registers.iter().rev() {
+        match register {
+            ArgumentRegister::Integer(r) => generator.code_assembler.pop(*r)?,
+            ArgumentRegister::Float(x) => {
+                generator.code_assembler.movsd_2(*x, qword_ptr(rsp))?;
+                generator.code_assembler.add(rsp, 8)?;
+            }
+        }
+    }
+    generator.code_assembler.mov(rax, function.address)?;
+    generator.code_assembler.call(rax)?;
+    if function.return_type == Some(parser::Type::Float) {
+        generator.code_assembler.movq(rax, xmm0)?;
+    }
+
+    if saved_regs.len() % 2 == 0 {
+        generator.code_assembler.pop(rbx)?;
+    }
+    restore_registers(saved_regs, generator)?;
+    store_rax_in_var(res_var, line, generator)
+}
+
+
+fn generate_function_call(res_var: &str, fun_name: &str, args: &[Data], function_tracker: &mut jit::FunctionTracker, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+
+    #[cfg(windows)]
     let num_arg_regs = 4;
 
-    #[cfg(target_os = "linux")]
+    #[cfg(unix)]
     let num_arg_regs = 6;
 
 
+    if let Some(function) = function_tracker.get_float_external(fun_name) {
+        return generate_float_external_call(res_var, &function, args, line, generator);
+    }
+
     let mut jit_args = vec![];
     let fun_id = function_tracker.get_id(fun_name);
+    let slot = function_tracker.get_function_slot(fun_id);
     jit_args.push(Data::Number(function_tracker as *const _ as i64));
     jit_args.push(Data::Number(fun_id.to_owned()));
 
+    //the function table slot contains the address once the function is compiled
+    let mut resolved_label = generator.code_assembler.create_label();
+    generator.code_assembler.mov(rax, slot)?;
+    generator.code_assembler.mov(rax, qword_ptr(rax))?;
+    generator.code_assembler.test(rax, rax)?;
+    generator.code_assembler.jnz(resolved_label)?;
+
+    //not compiled yet -> call the jit (fills the slot)
     let saved_regs = save_registers(jit_args.len() as u64, generator)?;
-    if (std::cmp::max(jit_args.len() as i64 - num_arg_regs, 0) as u64 + saved_regs.len() as u64 + generator.variable_allocator.get_num_stackvars()) % 2 == 0{
+    if (std::cmp::max(jit_args.len() as i64 - num_arg_regs, 0) as u64 + saved_regs.len() as u64).is_multiple_of(2){
         //fix stack allignment
         generator.code_assembler.push(rbx)?;
     }
 
     let pushed_args = set_arguments(&jit_args, line, generator)?;
 
-    generator.code_assembler.call(jit::jit_callback as u64)?;
-    if (pushed_args + saved_regs.len() as u64 + generator.variable_allocator.get_num_stackvars()) % 2 == 0{
+    generator.code_assembler.call(jit::jit_callback as *const () as u64)?;
+    if (pushed_args + saved_regs.len() as u64).is_multiple_of(2){
         generator.code_assembler.pop(rbx)?;
     }
     unset_arguments(pushed_args, generator)?;
     restore_registers(saved_regs, generator)?;
+    generator.code_assembler.set_label(&mut resolved_label)?;
+
+    generate_call_rax(res_var, args, fun_id < 0, line, generator)
+}
+
+//calls the address in rax and stores the return value
+//external functions can report an invalid string (see predefined_functions), it's checked if the callee may be external
+fn generate_call_rax(res_var: &str, args: &[Data], check_invalid_string: bool, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    #[cfg(windows)]
+    let num_arg_regs = 4;
 
+    #[cfg(unix)]
+    let num_arg_regs = 6;
 
     let saved_regs = save_registers(args.len() as u64, generator)?;
-    if (std::cmp::max(args.len() as i64 - num_arg_regs, 0) as u64 + saved_regs.len() as u64 + generator.variable_allocator.get_num_stackvars()) % 2 == 0{
+    if (std::cmp::max(args.len() as i64 - num_arg_regs, 0) as u64 + saved_regs.len() as u64).is_multiple_of(2){
         //fix stack allignment
         generator.code_assembler.push(rbx)?;
     }
     let pushed_args = set_arguments(args, line, generator)?;
     generator.code_assembler.call(rax)?;
-    if (pushed_args + saved_regs.len() as u64 + generator.variable_allocator.get_num_stackvars()) % 2 == 0{
+    if (pushed_args + saved_regs.len() as u64).is_multiple_of(2){
         generator.code_assembler.pop(rbx)?;
     }
     unset_arguments(pushed_args,  generator)?;
     restore_registers(saved_regs, generator)?;
+    if check_invalid_string {
+        generator.runtime_errors.used = true;
+        generator.code_assembler.mov(rbx, predefined_functions::get_invalid_string_address())?;
+        generator.code_assembler.cmp(qword_ptr(rbx + std::mem::offset_of!(InvalidString, occured) as i32), 0)?;
+        generator.code_assembler.jne(generator.runtime_errors.invalid_string)?;
+    }
 
-    let res_loc: VariableLocation = generator.variable_allocator.get(&res_var, line, &mut generator.lifetime_checker, &mut generator.code_assembler);
+    let res_loc: VariableLocation = generator.variable_allocator.get(res_var, line, &mut generator.lifetime_checker);
     match res_loc {
         VariableLocation::Register(r) => generator.code_assembler.mov(r, rax)?,
         VariableLocation::Stack(s) => generator.code_assembler.mov(rbp + s, rax)?
@@ -570,24 +1277,195 @@ fn generate_function_call(res_var: &String, fun_name: &String, args: &Vec<Data>,
     Ok(())
 }
 
+//function references are the index of the function table slot + 1
+//compiled functions with the right number of parameters are called directly, everything else goes through the jit
+//(it compiles the function or sets the runtime error for invalid references and wrong numbers of arguments)
+fn generate_indirect_call(res_var: &str, function: &Data, args: &[Data], function_tracker: &mut jit::FunctionTracker, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+
+    #[cfg(windows)]
+    let num_arg_regs = 4;
+
+    #[cfg(unix)]
+    let num_arg_regs = 6;
+
+    let function_table = function_tracker.get_function_table_address();
+    let num_parameters = function_tracker.get_num_parameters_address();
+    let num_functions = function_tracker.get_function_table_len();
+    let jit_args = vec![Data::Number(function_tracker as *const _ as i64), function.to_owned(), Data::Number(args.len() as i64)];
+
+    let mut resolve_label = generator.code_assembler.create_label();
+    let mut resolved_label = generator.code_assembler.create_label();
+    move_to(VariableLocation::Register(rbx), get_data(function, line, generator), generator)?;
+    //unsigned comparison -> 0 and negative references are invalid as well
+    generator.code_assembler.sub(rbx, 1)?;
+    generator.code_assembler.cmp(rbx, num_functions as i32)?;
+    generator.code_assembler.jae(resolve_label)?;
+    generator.code_assembler.mov(rax, num_parameters)?;
+    generator.code_assembler.cmp(qword_ptr(rax + rbx * 8), args.len() as i32)?;
+    generator.code_assembler.jne(resolve_label)?;
+    generator.code_assembler.mov(rax, function_table)?;
+    generator.code_assembler.mov(rax, qword_ptr(rax + rbx * 8))?;
+    generator.code_assembler.test(rax, rax)?;
+    generator.code_assembler.jnz(resolved_label)?;
+
+    generator.code_assembler.set_label(&mut resolve_label)?;
+    let saved_regs = save_registers(jit_args.len() as u64, generator)?;
+    if (std::cmp::max(jit_args.len() as i64 - num_arg_regs, 0) as u64 + saved_regs.len() as u64).is_multiple_of(2){
+        //fix stack allignment
+        generator.code_assembler.push(rbx)?;
+    }
+    let pushed_args = set_arguments(&jit_args, line, generator)?;
+    generator.code_assembler.call(jit::jit_indirect_callback as *const () as u64)?;
+    if (pushed_args + saved_regs.len() as u64).is_multiple_of(2){
+        generator.code_assembler.pop(rbx)?;
+    }
+    unset_arguments(pushed_args, generator)?;
+    restore_registers(saved_regs, generator)?;
+    generator.runtime_errors.used = true;
+    generator.code_assembler.test(rax, rax)?;
+    generator.code_assembler.jz(generator.runtime_errors.exit)?;
+    generator.code_assembler.set_label(&mut resolved_label)?;
+
+    generate_call_rax(res_var, args, true, line, generator)
+}
+
+//function references are constants
+fn generate_load_function(res_var: &str, name: &str, function_tracker: &mut jit::FunctionTracker, line: u64, generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    let reference = function_tracker.get_function_reference(name);
+    generator.code_assembler.mov(rax, reference)?;
+    store_rax_in_var(res_var, line, generator)?;
+    Ok(())
+}
+
+
+fn generate_tier_up_check(generator: &mut CodeGenerator) -> Result<(), IcedError> {
+    let tier_up = match &generator.tier_up {
+        None => return Ok(()),
+        Some(t) => t.clone()
+    };
+
+    #[cfg(windows)]
+    let num_arg_regs = 4;
+
+    #[cfg(unix)]
+    let num_arg_regs = 6;
+
+    let jit_args = vec![Data::Number(tier_up.function_tracker), Data::Number(tier_up.function_id)];
+
+    //count and only call the jit once when the threshold is reached
+    let mut skip_label = generator.code_assembler.create_label();
+    generator.code_assembler.mov(rax, tier_up.counter)?;
+    generator.code_assembler.add(qword_ptr(rax), 1)?;
+    generator.code_assembler.cmp(qword_ptr(rax), tier_up.threshold)?;
+    generator.code_assembler.jne(skip_label)?;
+
+    let saved_regs = save_registers(jit_args.len() as u64, generator)?;
+    if (std::cmp::max(jit_args.len() as i64 - num_arg_regs, 0) as u64 + saved_regs.len() as u64).is_multiple_of(2){
+        //fix stack allignment
+        generator.code_assembler.push(rbx)?;
+    }
+    let pushed_args = set_arguments(&jit_args, 0, generator)?;
+    generator.code_assembler.call(jit::jit_tier_up_callback as *const () as u64)?;
+    if (pushed_args + saved_regs.len() as u64).is_multiple_of(2){
+        generator.code_assembler.pop(rbx)?;
+    }
+    unset_arguments(pushed_args, generator)?;
+    restore_registers(saved_regs, generator)?;
+    generator.code_assembler.set_label(&mut skip_label)?;
+    generator.code_assembler.nop()?;
+    Ok(())
+}
+
+struct RuntimeErrorLabels {
+    index_out_of_bounds: CodeLabel,
+    invalid_array: CodeLabel,
+    invalid_string: CodeLabel,
+    division_by_zero: CodeLabel,
+    division_overflow: CodeLabel,
+    invalid_fields: Vec<(CodeLabel, i64)>, //field accesses and the index of their field
+    exit: CodeLabel,
+    used: bool //the handlers are only generated if there is a check
+}
+
+#[derive(Clone)]
+struct TierUp {
+    function_tracker: i64,
+    function_id: jit::FunctionId,
+    counter: u64,
+    threshold: i32
+}
 
 pub struct CodeGenerator {
     lifetime_checker: LifetimeChecker,
     variable_allocator: var_allocator::VariableAllocator,
     code_assembler: CodeAssembler,
-    labels: HashMap<String, CodeLabel>
+    labels: HashMap<String, CodeLabel>,
+    placed_labels: HashSet<String>,
+    tier_up: Option<TierUp>,
+    runtime: u64,
+    runtime_errors: RuntimeErrorLabels,
+    stack_objects: HashSet<String> //variables that only reference structs in the stack frame
+}
+
+//saved by the entry function, the runtime error handlers restore them
+const ENTRY_SAVED_REGISTERS: [AsmRegister64; 8] = [rbp, rbx, rdi, rsi, r12, r13, r14, r15];
+
+//entry point for the jit: saves the stack pointer (restored by the runtime error handlers) and calls the function in the slot
+pub fn generate_entry(function_slot: u64, stack_pointer_address: u64) -> Result<Vec<Instruction>, IcedError> {
+    let mut code_assembler = CodeAssembler::new(64)?;
+    for reg in ENTRY_SAVED_REGISTERS {
+        code_assembler.push(reg)?;
+    }
+    code_assembler.mov(rax, stack_pointer_address)?;
+    code_assembler.mov(qword_ptr(rax), rsp)?;
+    //keep the stack 16 byte aligned (return address + 8 registers)
+    code_assembler.sub(rsp, 8)?;
+    code_assembler.mov(rax, function_slot)?;
+    code_assembler.mov(rax, qword_ptr(rax))?;
+    code_assembler.call(rax)?;
+    code_assembler.add(rsp, 8)?;
+    for reg in ENTRY_SAVED_REGISTERS.iter().rev() {
+        code_assembler.pop(*reg)?;
+    }
+    code_assembler.ret()?;
+    Ok(code_assembler.take_instructions())
 }
 
 
 
 
 #[allow(dead_code)]
-pub fn generate(instructions: &Vec<ir::IrInstruction>, parameters: &parser::Parameters, function_tracker: &mut jit::FunctionTracker) -> Result<Vec<Instruction>, IcedError> {
+pub fn generate(instructions: &Vec<ir::IrInstruction>, parameters: &parser::Parameters, function_id: jit::FunctionId, tier: jit::Tier, function_tracker: &mut jit::FunctionTracker) -> Result<Vec<Instruction>, IcedError> {
     let mut _lifetime = lifetime::get_checker(instructions, parameters);
+    let tier_up = match (tier, function_tracker.get_tier_up_threshold()) {
+        (jit::Tier::Baseline, Some(threshold)) => Some(TierUp {
+            function_tracker: function_tracker as *const _ as i64,
+            function_id,
+            counter: function_tracker.get_counter_address(function_id),
+            threshold: i32::try_from(threshold).unwrap_or(i32::MAX)
+        }),
+        _ => None
+    };
+    let mut code_assembler = CodeAssembler::new(64)?;
+    let runtime_errors = RuntimeErrorLabels {
+        index_out_of_bounds: code_assembler.create_label(),
+        invalid_array: code_assembler.create_label(),
+        invalid_string: code_assembler.create_label(),
+        division_by_zero: code_assembler.create_label(),
+        division_overflow: code_assembler.create_label(),
+        invalid_fields: vec![],
+        exit: code_assembler.create_label(),
+        used: false
+    };
     let mut generator = CodeGenerator {
-        code_assembler: CodeAssembler::new(64)?,
+        code_assembler,
         labels: HashMap::new(),
-        variable_allocator: var_allocator::VariableAllocator::new(parameters, &mut _lifetime),
+        placed_labels: HashSet::new(),
+        tier_up,
+        runtime: function_tracker.get_runtime_address(),
+        runtime_errors,
+        stack_objects: stack_objects::get_stack_objects(instructions, parameters),
+        variable_allocator: var_allocator::VariableAllocator::new(parameters, float_variables::get_float_variables(instructions, parameters), &mut _lifetime),
         lifetime_checker: _lifetime
     };
 
@@ -603,6 +1481,12 @@ pub fn generate(instructions: &Vec<ir::IrInstruction>, parameters: &parser::Para
     generator.code_assembler.push(r13)?;
     generator.code_assembler.push(r14)?;
     generator.code_assembler.push(r15)?;
+    //reserve the stack frame for spilled variables (the size is patched in after the body is generated)
+    let frame_instruction_idx = generator.code_assembler.instructions().len();
+    generator.code_assembler.sub(rsp, i32::MAX)?;
+
+    //invocation counter
+    generate_tier_up_check(&mut generator)?;
 
     for (line, inst) in instructions.iter().enumerate() {
         match inst {
@@ -618,6 +1502,12 @@ pub fn generate(instructions: &Vec<ir::IrInstruction>, parameters: &parser::Para
             ir::IrInstruction::FunctionCall(res_var, fun_name, args) => {
                 generate_function_call(res_var, fun_name, args, function_tracker, line as u64, &mut generator)?;
             }
+            ir::IrInstruction::IndirectCall(res_var, function, args) => {
+                generate_indirect_call(res_var, function, args, function_tracker, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::LoadFunction(res_var, name) => {
+                generate_load_function(res_var, name, function_tracker, line as u64, &mut generator)?;
+            }
             ir::IrInstruction::Addition(res_var, data1, data2) => {
                 generate_addition(res_var, data1, data2, line as u64, &mut generator)?;
             }
@@ -651,15 +1541,105 @@ pub fn generate(instructions: &Vec<ir::IrInstruction>, parameters: &parser::Para
             ir::IrInstruction::NotEquals(res_var, data1, data2) => {
                 generate_not_equals(res_var, data1, data2, line as u64, &mut generator)?;
             }
-            ir::IrInstruction::LogicAnd(res_var, data1, data2) => {
-                generate_and(res_var, data1, data2, line as u64, &mut generator)?;
+            ir::IrInstruction::BitwiseAnd(res_var, data1, data2) => {
+                generate_bitwise_and(res_var, data1, data2, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::BitwiseOr(res_var, data1, data2) => {
+                generate_bitwise_or(res_var, data1, data2, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::BitwiseXor(res_var, data1, data2) => {
+                generate_bitwise_xor(res_var, data1, data2, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::ShiftLeft(res_var, data1, data2) => {
+                generate_shift(res_var, data1, data2, Shift::Left, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::ShiftRight(res_var, data1, data2) => {
+                generate_shift(res_var, data1, data2, Shift::Right, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::ShiftRightLogical(res_var, data1, data2) => {
+                generate_shift(res_var, data1, data2, Shift::RightLogical, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::Negation(res_var, data) => {
+                generate_negation(res_var, data, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::LogicNot(res_var, data) => {
+                generate_logic_not(res_var, data, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::BitwiseNot(res_var, data) => {
+                generate_bitwise_not(res_var, data, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::FloatAddition(res_var, data1, data2) => {
+                generate_float_arithmetic(res_var, data1, data2, FloatOperation::Addition, line as u64, &mut generator)?;
             }
-            ir::IrInstruction::LogicOr(res_var, data1, data2) => {
-                generate_or(res_var, data1, data2, line as u64, &mut generator)?;
+            ir::IrInstruction::FloatSubtraction(res_var, data1, data2) => {
+                generate_float_arithmetic(res_var, data1, data2, FloatOperation::Subtraction, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::FloatMultiplication(res_var, data1, data2) => {
+                generate_float_arithmetic(res_var, data1, data2, FloatOperation::Multiplication, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::FloatDivision(res_var, data1, data2) => {
+                generate_float_arithmetic(res_var, data1, data2, FloatOperation::Division, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::FloatGreater(res_var, data1, data2) => {
+                generate_float_comparison(res_var, data1, data2, FloatComparison::Greater, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::FloatGreaterEquals(res_var, data1, data2) => {
+                generate_float_comparison(res_var, data1, data2, FloatComparison::GreaterEquals, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::FloatLess(res_var, data1, data2) => {
+                generate_float_comparison(res_var, data1, data2, FloatComparison::Less, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::FloatLessEquals(res_var, data1, data2) => {
+                generate_float_comparison(res_var, data1, data2, FloatComparison::LessEquals, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::FloatEquals(res_var, data1, data2) => {
+                generate_float_comparison(res_var, data1, data2, FloatComparison::Equals, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::FloatNotEquals(res_var, data1, data2) => {
+                generate_float_comparison(res_var, data1, data2, FloatComparison::NotEquals, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::FloatNegation(res_var, data) => {
+                generate_float_negation(res_var, data, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::IntToFloat(res_var, data) => {
+                generate_int_to_float(res_var, data, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::FloatToInt(res_var, data) => {
+                generate_float_to_int(res_var, data, line as u64, &mut generator)?;
             }
             ir::IrInstruction::Assignment(res_var, data) => {
                 generate_assignment(res_var, data, line as u64, &mut generator)?;
             }
+            ir::IrInstruction::LoadGlobal(res_var, name) => {
+                generate_load_global(res_var, name, function_tracker, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::StoreGlobal(name, data) => {
+                generate_store_global(name, data, function_tracker, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::LoadString(res_var, s) => {
+                generate_load_string(res_var, s, function_tracker, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::NewArray(res_var, size) => {
+                generate_new_array(res_var, size, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::ArrayLength(res_var, array) => {
+                generate_array_length(res_var, array, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::ArrayLoad(res_var, array, index) => {
+                generate_array_load(res_var, array, index, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::ArrayStore(array, index, value) => {
+                generate_array_store(array, index, value, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::NewStruct(res_var, name, fields) => {
+                generate_new_struct(res_var, name, fields, function_tracker, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::FieldLoad(res_var, object, field) => {
+                generate_field_load(res_var, object, field, function_tracker, line as u64, &mut generator)?;
+            }
+            ir::IrInstruction::FieldStore(object, field, value) => {
+                generate_field_store(object, field, value, function_tracker, line as u64, &mut generator)?;
+            }
             ir::IrInstruction::Return(data) => {
                 generate_return(data, line as u64, &mut generator)?;
             }
@@ -670,12 +1650,21 @@ pub fn generate(instructions: &Vec<ir::IrInstruction>, parameters: &parser::Para
     //always return
     generate_return(&Data::Number(0), 0, &mut generator)?;
 
-    Ok(generator.code_assembler.take_instructions())
+    if generator.runtime_errors.used {
+        generate_runtime_error_handlers(&mut generator)?;
+    }
+
+    //keep the stack 16 byte aligned: after the prologue rsp is always 8 mod 16
+    let num_slots = generator.variable_allocator.get_num_stack_slots();
+    let frame_size = (num_slots + num_slots % 2) * 8;
+    let mut instructions = generator.code_assembler.take_instructions();
+    instructions[frame_instruction_idx].set_immediate_i32(1, frame_size as i32);
+    Ok(instructions)
 }
 
-pub fn print_decoded_bytes(bytes: &Vec<u8>, rip: u64) {
+pub fn print_decoded_bytes(bytes: &[u8], rip: u64) {
     let mut decoder =
-        Decoder::with_ip(64, &bytes, rip, DecoderOptions::NONE);
+        Decoder::with_ip(64, bytes, rip, DecoderOptions::NONE);
 
     // Formatters: Masm*, Nasm*, Gas* (AT&T) and Intel* (XED).
     let mut formatter = NasmFormatter::new();
@@ -712,5 +1701,5 @@ pub fn assemble(instructions: &Vec<Instruction>, ip: u64) -> Result<Vec<u8>, Ice
     for inst in instructions {
         a.add_instruction(inst.to_owned())?;
     }
-    Ok(a.assemble(ip)?)
+    a.assemble(ip)
 }
@@ -0,0 +1,100 @@
+
+use std::collections::HashSet;
+use crate::ir::{self, Data, IrInstruction};
+use crate::parser;
+
+//float variables are kept in xmm registers, every other variable (including floats that are passed to functions,
+//returned, stored or used by integer instructions) is kept in the general purpose registers as its bits
+
+fn is_float_result(inst: &IrInstruction) -> bool {
+    matches!(inst,
+        IrInstruction::FloatAddition(_, _, _) | IrInstruction::FloatSubtraction(_, _, _) |
+        IrInstruction::FloatMultiplication(_, _, _) | IrInstruction::FloatDivision(_, _, _) |
+        IrInstruction::FloatNegation(_, _) | IrInstruction::IntToFloat(_, _))
+}
+
+fn has_float_operands(inst: &IrInstruction) -> bool {
+    matches!(inst,
+        IrInstruction::FloatAddition(_, _, _) | IrInstruction::FloatSubtraction(_, _, _) |
+        IrInstruction::FloatMultiplication(_, _, _) | IrInstruction::FloatDivision(_, _, _) |
+        IrInstruction::FloatGreater(_, _, _) | IrInstruction::FloatGreaterEquals(_, _, _) |
+        IrInstruction::FloatLess(_, _, _) | IrInstruction::FloatLessEquals(_, _, _) |
+        IrInstruction::FloatEquals(_, _, _) | IrInstruction::FloatNotEquals(_, _, _) |
+        IrInstruction::FloatNegation(_, _) | IrInstruction::FloatToInt(_, _))
+}
+
+fn is_float_definition(inst: &IrInstruction, float_variables: &HashSet<String>) -> bool {
+    match inst {
+        IrInstruction::Assignment(_, Data::Number(_)) | IrInstruction::Assignment(_, Data::Float(_)) => true,
+        IrInstruction::Assignment(_, Data::Variable(v)) => float_variables.contains(v),
+        _ => is_float_result(inst)
+    }
+}
+
+fn is_float_use(inst: &IrInstruction, float_variables: &HashSet<String>) -> bool {
+    match inst {
+        IrInstruction::Assignment(res, _) => float_variables.contains(res),
+        _ => has_float_operands(inst)
+    }
+}
+
+//starts with every variable and removes the ones that are defined or used by other instructions until nothing changes
+pub fn get_float_variables(instructions: &Vec<IrInstruction>, parameters: &parser::Parameters) -> HashSet<String> {
+    let mut float_variables: HashSet<String> = instructions.iter()
+        .filter_map(|inst| ir::get_result_variable(inst).cloned())
+        .filter(|v| !parameters.contains(v))
+        .collect();
+    loop {
+        let mut removed = HashSet::new();
+        for inst in instructions {
+            if let Some(res) = ir::get_result_variable(inst) {
+                if !is_float_definition(inst, &float_variables) {
+                    removed.insert(res.to_owned());
+                }
+            }
+            if !is_float_use(inst, &float_variables) {
+                for operand in ir::get_operands(inst) {
+                    if let Data::Variable(v) = operand {
+                        removed.insert(v.to_owned());
+                    }
+                }
+            }
+        }
+        let len = float_variables.len();
+        float_variables.retain(|v| !removed.contains(v));
+        if float_variables.len() == len {
+            return float_variables;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn float_variables_assignments() {
+        let instructions = vec![
+            IrInstruction::Assignment("a".to_owned(), Data::Float(1.5)),
+            IrInstruction::FloatAddition("b".to_owned(), Data::Variable("a".to_owned()), Data::Variable("p".to_owned())),
+            IrInstruction::Assignment("c".to_owned(), Data::Variable("b".to_owned())),
+            IrInstruction::FloatLess("d".to_owned(), Data::Variable("c".to_owned()), Data::Float(2.0)),
+            IrInstruction::Return(Data::Variable("d".to_owned()))
+        ];
+        let float_variables = get_float_variables(&instructions, &vec!["p".to_owned()]);
+        assert_eq!(float_variables, HashSet::from(["a".to_owned(), "b".to_owned(), "c".to_owned()]));
+    }
+
+    #[test]
+    fn float_variables_integer_uses() {
+        //returned floats and values that are passed to functions are kept as their bits
+        let instructions = vec![
+            IrInstruction::FloatMultiplication("a".to_owned(), Data::Float(1.5), Data::Float(2.0)),
+            IrInstruction::Assignment("b".to_owned(), Data::Variable("a".to_owned())),
+            IrInstruction::FunctionCall("c".to_owned(), "f".to_owned(), vec![Data::Variable("b".to_owned())]),
+            IrInstruction::IntToFloat("d".to_owned(), Data::Variable("c".to_owned())),
+            IrInstruction::Return(Data::Variable("d".to_owned()))
+        ];
+        assert_eq!(get_float_variables(&instructions, &vec![]), HashSet::new());
+    }
+}
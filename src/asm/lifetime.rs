@@ -5,6 +5,7 @@ use log::debug;
 
 pub struct Lifetime {
     name: String,
+    #[allow(dead_code)]
     start: i64,
     end: i64
 }
@@ -19,6 +20,7 @@ impl LifetimeChecker {
         }
     }
 
+    #[allow(dead_code)]
     pub fn print_all(&mut self) {
         debug!("\nLifetime-Checker:");
         for lifetime in &self.lifetimes {
@@ -26,6 +28,7 @@ impl LifetimeChecker {
         }
     }
 
+    #[allow(dead_code)]
     pub fn get_start_lifetime(&mut self, name: &str) -> i64 {
         let lifetime_option = self.lifetimes.iter().find(|l| l.name == name);
         if lifetime_option.is_none() {
@@ -44,7 +47,7 @@ impl LifetimeChecker {
     }
 
     fn set_start_lifetime(&mut self, name: String, line: i64) {
-        self.lifetimes.push(Lifetime { name: name, start: line, end: line})
+        self.lifetimes.push(Lifetime { name, start: line, end: line})
     }
 
     fn set_end_lifetime(&mut self, name: String, line: i64) {
@@ -58,12 +61,12 @@ impl LifetimeChecker {
 
 fn check_end_lifetime(data: &ir::Data, line: i64,  lifetime_checker: &mut LifetimeChecker) {
     match data {
-        ir::Data::Number(_) => (),
+        ir::Data::Number(_) | ir::Data::Float(_) => (),
         ir::Data::Variable(v) => lifetime_checker.set_end_lifetime(v.to_owned(), line)
     }
 }
 
-pub fn get_checker(instructions: &Vec<ir::IrInstruction>, parameters: &parser::Parameters) -> LifetimeChecker {
+pub fn get_checker(instructions: &[ir::IrInstruction], parameters: &parser::Parameters) -> LifetimeChecker {
     let mut checker = LifetimeChecker::new();
 
     for parameter in parameters {
@@ -82,6 +85,13 @@ pub fn get_checker(instructions: &Vec<ir::IrInstruction>, parameters: &parser::P
                 }
                 checker.set_start_lifetime(res_var.to_owned(), line as i64);
             },
+            ir::IrInstruction::IndirectCall(res_var, function, args) => {
+                check_end_lifetime(function, line as i64, &mut checker);
+                for d in args {
+                    check_end_lifetime(d, line as i64, &mut checker);
+                }
+                checker.set_start_lifetime(res_var.to_owned(), line as i64);
+            },
             ir::IrInstruction::Addition(res_var, d1, d2) => {
                 check_end_lifetime(d1, line as i64, &mut checker);
                 check_end_lifetime(d2, line as i64, &mut checker);
@@ -137,20 +147,52 @@ pub fn get_checker(instructions: &Vec<ir::IrInstruction>, parameters: &parser::P
                 check_end_lifetime(d2, line as i64, &mut checker);
                 checker.set_start_lifetime(res_var.to_owned(), line as i64);
             },
-            ir::IrInstruction::LogicAnd(res_var, d1, d2) => {
+            ir::IrInstruction::BitwiseAnd(res_var, d1, d2) | ir::IrInstruction::BitwiseOr(res_var, d1, d2) | ir::IrInstruction::BitwiseXor(res_var, d1, d2) | ir::IrInstruction::ShiftLeft(res_var, d1, d2) | ir::IrInstruction::ShiftRight(res_var, d1, d2) | ir::IrInstruction::ShiftRightLogical(res_var, d1, d2) => {
+                check_end_lifetime(d1, line as i64, &mut checker);
+                check_end_lifetime(d2, line as i64, &mut checker);
+                checker.set_start_lifetime(res_var.to_owned(), line as i64);
+            },
+            ir::IrInstruction::FloatAddition(res_var, d1, d2) | ir::IrInstruction::FloatSubtraction(res_var, d1, d2) |
+            ir::IrInstruction::FloatMultiplication(res_var, d1, d2) | ir::IrInstruction::FloatDivision(res_var, d1, d2) |
+            ir::IrInstruction::FloatGreater(res_var, d1, d2) | ir::IrInstruction::FloatGreaterEquals(res_var, d1, d2) |
+            ir::IrInstruction::FloatLess(res_var, d1, d2) | ir::IrInstruction::FloatLessEquals(res_var, d1, d2) |
+            ir::IrInstruction::FloatEquals(res_var, d1, d2) | ir::IrInstruction::FloatNotEquals(res_var, d1, d2) => {
+                check_end_lifetime(d1, line as i64, &mut checker);
+                check_end_lifetime(d2, line as i64, &mut checker);
+                checker.set_start_lifetime(res_var.to_owned(), line as i64);
+            },
+            ir::IrInstruction::ArrayLoad(res_var, d1, d2) => {
                 check_end_lifetime(d1, line as i64, &mut checker);
                 check_end_lifetime(d2, line as i64, &mut checker);
                 checker.set_start_lifetime(res_var.to_owned(), line as i64);
             },
-            ir::IrInstruction::LogicOr(res_var, d1, d2) => {
+            ir::IrInstruction::ArrayStore(d1, d2, d3) => {
                 check_end_lifetime(d1, line as i64, &mut checker);
                 check_end_lifetime(d2, line as i64, &mut checker);
+                check_end_lifetime(d3, line as i64, &mut checker);
+            },
+            ir::IrInstruction::NewStruct(res_var, _, fields) => {
+                for (_, d) in fields {
+                    check_end_lifetime(d, line as i64, &mut checker);
+                }
+                checker.set_start_lifetime(res_var.to_owned(), line as i64);
+            },
+            ir::IrInstruction::FieldStore(d1, _, d2) => {
+                check_end_lifetime(d1, line as i64, &mut checker);
+                check_end_lifetime(d2, line as i64, &mut checker);
+            },
+            ir::IrInstruction::Negation(res_var, d) | ir::IrInstruction::LogicNot(res_var, d) | ir::IrInstruction::BitwiseNot(res_var, d) |
+            ir::IrInstruction::NewArray(res_var, d) | ir::IrInstruction::ArrayLength(res_var, d) | ir::IrInstruction::FieldLoad(res_var, d, _) |
+            ir::IrInstruction::FloatNegation(res_var, d) | ir::IrInstruction::IntToFloat(res_var, d) | ir::IrInstruction::FloatToInt(res_var, d) => {
+                check_end_lifetime(d, line as i64, &mut checker);
                 checker.set_start_lifetime(res_var.to_owned(), line as i64);
             },
             ir::IrInstruction::Assignment(res_var, d) => {
                 check_end_lifetime(d, line as i64, &mut checker);
                 checker.set_start_lifetime(res_var.to_owned(), line as i64);
             }
+            ir::IrInstruction::LoadGlobal(res_var, _) | ir::IrInstruction::LoadString(res_var, _) | ir::IrInstruction::LoadFunction(res_var, _) => checker.set_start_lifetime(res_var.to_owned(), line as i64),
+            ir::IrInstruction::StoreGlobal(_, d) => check_end_lifetime(d, line as i64, &mut checker),
             ir::IrInstruction::Return(d) => check_end_lifetime(d, line as i64, &mut checker),
             ir::IrInstruction::KeepAlive(var) => {
                 checker.set_end_lifetime(var.to_owned(), line as i64)
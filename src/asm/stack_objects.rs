@@ -0,0 +1,123 @@
+
+use std::collections::{HashMap, HashSet};
+use crate::ir::{self, Data, IrInstruction};
+use crate::parser;
+
+//structs are allocated in the stack frame if they can't outlive the function call and if every execution of the
+//allocation gets its own memory (the allocation isn't in a loop). every other struct is allocated by the runtime.
+//the variables that reference a struct in the stack frame never contain anything else, so their field accesses
+//don't have to look up the object in the object table of the runtime.
+
+//instructions between a label and a jump back to it are executed repeatedly
+fn get_loops(instructions: &[IrInstruction]) -> Vec<(usize, usize)> {
+    let labels: HashMap<&String, usize> = instructions.iter().enumerate().filter_map(|(idx, inst)| match inst {
+        IrInstruction::Label(l) => Some((l, idx)),
+        _ => None
+    }).collect();
+    instructions.iter().enumerate().filter_map(|(idx, inst)| match inst {
+        IrInstruction::Jump(l) | IrInstruction::JumpFalse(_, l) if labels[l] < idx => Some((labels[l], idx)),
+        _ => None
+    }).collect()
+}
+
+fn is_variable(data: &Data, variables: &HashSet<String>) -> bool {
+    matches!(data, Data::Variable(v) if variables.contains(v))
+}
+
+//the struct escapes if a copy of the reference is used by anything else than field accesses.
+//it escapes as well if a variable with a copy can contain another value (a parameter or a variable that is assigned
+//something else than a copy), the references are returned otherwise
+fn escapes(object: &String, instructions: &Vec<IrInstruction>, parameters: &parser::Parameters) -> Option<HashSet<String>> {
+    let mut references = HashSet::from([object.to_owned()]);
+    loop {
+        let len = references.len();
+        for inst in instructions {
+            match inst {
+                IrInstruction::Assignment(res, d) if is_variable(d, &references) => { references.insert(res.to_owned()); },
+                IrInstruction::Assignment(_, _) => (),
+                IrInstruction::FieldLoad(_, _, _) => (),
+                IrInstruction::FieldStore(_, _, value) if is_variable(value, &references) => return None,
+                IrInstruction::FieldStore(_, _, _) => (),
+                _ if ir::get_operands(inst).into_iter().any(|d| is_variable(d, &references)) => return None,
+                _ => ()
+            }
+        }
+        if references.len() == len {
+            break;
+        }
+    }
+    if parameters.iter().any(|p| references.contains(p)) {
+        return None;
+    }
+    for inst in instructions {
+        match inst {
+            IrInstruction::NewStruct(res, _, _) if res == object => (),
+            IrInstruction::Assignment(_, d) if is_variable(d, &references) => (),
+            _ if ir::get_result_variable(inst).is_some_and(|res| references.contains(res)) => return None,
+            _ => ()
+        }
+    }
+    Some(references)
+}
+
+//variables that reference a struct in the stack frame (the results of the allocations and their copies)
+pub fn get_stack_objects(instructions: &Vec<IrInstruction>, parameters: &parser::Parameters) -> HashSet<String> {
+    let loops = get_loops(instructions);
+    instructions.iter().enumerate().filter_map(|(idx, inst)| match inst {
+        IrInstruction::NewStruct(res, _, _) if !loops.iter().any(|(start, end)| (*start..=*end).contains(&idx)) => Some(res),
+        _ => None
+    }).filter_map(|res| escapes(res, instructions, parameters)).flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_struct(res: &str) -> IrInstruction {
+        IrInstruction::NewStruct(res.to_owned(), "P".to_owned(), vec![("x".to_owned(), Data::Number(1))])
+    }
+
+    #[test]
+    fn stack_objects_field_accesses() {
+        let instructions = vec![
+            new_struct("a"),
+            IrInstruction::Assignment("b".to_owned(), Data::Variable("a".to_owned())),
+            IrInstruction::FieldStore(Data::Variable("b".to_owned()), "x".to_owned(), Data::Number(2)),
+            IrInstruction::FieldLoad("c".to_owned(), Data::Variable("a".to_owned()), "x".to_owned()),
+            new_struct("d"),
+            IrInstruction::FieldStore(Data::Variable("a".to_owned()), "x".to_owned(), Data::Variable("d".to_owned())),
+            IrInstruction::Return(Data::Variable("c".to_owned()))
+        ];
+        assert_eq!(get_stack_objects(&instructions, &vec![]), HashSet::from(["a".to_owned(), "b".to_owned()]));
+    }
+
+    #[test]
+    fn stack_objects_escapes_and_loops() {
+        let instructions = vec![
+            new_struct("a"),
+            IrInstruction::Assignment("b".to_owned(), Data::Variable("a".to_owned())),
+            IrInstruction::FunctionCall("c".to_owned(), "f".to_owned(), vec![Data::Variable("b".to_owned())]),
+            IrInstruction::Label("loop".to_owned()),
+            new_struct("d"),
+            IrInstruction::FieldLoad("e".to_owned(), Data::Variable("d".to_owned()), "x".to_owned()),
+            IrInstruction::Jump("loop".to_owned()),
+        ];
+        assert_eq!(get_stack_objects(&instructions, &vec![]), HashSet::new());
+    }
+
+    #[test]
+    fn stack_objects_references_with_other_values() {
+        let instructions = vec![
+            new_struct("a"),
+            IrInstruction::Assignment("b".to_owned(), Data::Variable("a".to_owned())),
+            IrInstruction::Assignment("b".to_owned(), Data::Number(8000)),
+            IrInstruction::FieldLoad("c".to_owned(), Data::Variable("b".to_owned()), "x".to_owned()),
+            new_struct("d"),
+            IrInstruction::Assignment("p".to_owned(), Data::Variable("d".to_owned())),
+            IrInstruction::FieldLoad("e".to_owned(), Data::Variable("p".to_owned()), "x".to_owned()),
+            new_struct("f"),
+            IrInstruction::FieldLoad("g".to_owned(), Data::Variable("f".to_owned()), "x".to_owned()),
+        ];
+        assert_eq!(get_stack_objects(&instructions, &vec!["p".to_owned()]), HashSet::from(["f".to_owned()]));
+    }
+}
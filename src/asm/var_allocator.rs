@@ -1,18 +1,28 @@
 
 
 use core::fmt;
+use std::collections::HashSet;
 use iced_x86::code_asm::*;
-use log::debug;
 use crate::parser;
 
 use crate::asm::lifetime;
 
+//rbp points to the return address, below it are the saved rbp, rbx, r12, r13, r14 and r15
+const FIRST_STACK_VARIABLE_OFFSET: i64 = -56;
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum VariableLocation {
     Stack(i64), //rbp offset
     Register(AsmRegister64)
 }
 
+//float variables (see float_variables), xmm0 and xmm1 are scratch registers
+#[derive(Clone, Copy, PartialEq)]
+pub enum FloatLocation {
+    Stack(i64), //rbp offset
+    Register(AsmRegisterXmm)
+}
+
 #[allow(non_upper_case_globals)]
 fn register_to_string(reg: AsmRegister64) -> String {
     match reg {
@@ -44,21 +54,41 @@ impl fmt::Display for VariableLocation {
     }
 }
 
+impl fmt::Display for FloatLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Register(register) =>
+            write!(f, "REGISTER ({:?})", register),
+            Self::Stack(offset) =>
+            write!(f, "STACK ({})", offset),
+        }
+    }
+}
+
 pub struct AllocatedVariable {
     location: VariableLocation,
     lifetime_end: u64,
     name: String
 }
 
+pub struct AllocatedFloatVariable {
+    location: FloatLocation,
+    lifetime_end: u64,
+    name: String
+}
+
 pub struct VariableAllocator {
     free_registers: Vec<AsmRegister64>,
     variables: Vec<AllocatedVariable>,
+    free_float_registers: Vec<AsmRegisterXmm>,
+    float_variables: Vec<AllocatedFloatVariable>,
+    float_variable_names: HashSet<String>,
     next_stack_variable_offset: i64,
 }
 
 impl VariableAllocator {
-    pub fn new(parameters: &parser::Parameters, lifetime_checker: &mut lifetime::LifetimeChecker) -> Self {
-        #[cfg(target_os = "linux")]
+    pub fn new(parameters: &parser::Parameters, float_variable_names: HashSet<String>, lifetime_checker: &mut lifetime::LifetimeChecker) -> Self {
+        #[cfg(unix)]
         let mut free_registers = [
             r15,
             r14,
@@ -66,7 +96,6 @@ impl VariableAllocator {
             r12,
             r11,
             r10,
-            rbx,
             r9,
             r8,
             rcx,
@@ -75,7 +104,7 @@ impl VariableAllocator {
             rdi
         ].to_vec();
 
-        #[cfg(target_os = "windows")]
+        #[cfg(windows)]
         let mut free_registers = [
             r15,
             r14,
@@ -85,18 +114,44 @@ impl VariableAllocator {
             r10,
             rsi,
             rdi,
-            rbx,
             r9,
             r8,
             rdx,
             rcx,
         ].to_vec();
 
+        //xmm6 - xmm15 are callee saved on windows
+        #[cfg(unix)]
+        let free_float_registers = [
+            xmm15,
+            xmm14,
+            xmm13,
+            xmm12,
+            xmm11,
+            xmm10,
+            xmm9,
+            xmm8,
+            xmm7,
+            xmm6,
+            xmm5,
+            xmm4,
+            xmm3,
+            xmm2
+        ].to_vec();
+
+        #[cfg(windows)]
+        let free_float_registers = [
+            xmm5,
+            xmm4,
+            xmm3,
+            xmm2
+        ].to_vec();
+
         let mut variables = vec![];
 
-        #[cfg(target_os = "linux")]
+        #[cfg(unix)]
         let number_of_register_arguments = 6;
-        #[cfg(target_os = "windows")]
+        #[cfg(windows)]
         let number_of_register_arguments = 4;
 
         let mut offset = 8 + (parameters.len() as i64 - number_of_register_arguments as i64) * 8;
@@ -105,29 +160,36 @@ impl VariableAllocator {
             let lifetime_end = lifetime_checker.get_end_lifetime(parameter) as u64;
             if i < number_of_register_arguments {
                 //allocate parameter register
-                variables.push(AllocatedVariable { location: VariableLocation::Register(free_registers.pop().unwrap()), lifetime_end: lifetime_end, name: parameter.to_owned()});
+                variables.push(AllocatedVariable { location: VariableLocation::Register(free_registers.pop().unwrap()), lifetime_end, name: parameter.to_owned()});
             }else{
-                offset = offset - 8; //base offset
-                variables.push(AllocatedVariable { location: VariableLocation::Stack(offset), lifetime_end: lifetime_end, name: parameter.to_owned()});
+                offset -= 8; //base offset
+                variables.push(AllocatedVariable { location: VariableLocation::Stack(offset), lifetime_end, name: parameter.to_owned()});
             }
         }
 
         VariableAllocator {
             free_registers,
             variables,
-            next_stack_variable_offset: -56,
+            free_float_registers,
+            float_variables: vec![],
+            float_variable_names,
+            next_stack_variable_offset: FIRST_STACK_VARIABLE_OFFSET,
         }
     }
 
+    #[allow(dead_code)]
     pub fn print_allocated(&mut self) {
         println!("\nVariable-Allocator:");
         for var in &self.variables {
             println!("Name: {} - Location: {} - Lifetime end at line {}", var.name, var.location, var.lifetime_end);
         }
+        for var in &self.float_variables {
+            println!("Name: {} - Location: {} - Lifetime end at line {}", var.name, var.location, var.lifetime_end);
+        }
     }
 
     pub fn is_allocated(&mut self, reg: AsmRegister64) -> bool{
-        self.free_registers.contains(&reg) == false
+        !self.free_registers.contains(&reg)
     }
 
     fn check(&mut self, line: u64) {
@@ -141,34 +203,85 @@ impl VariableAllocator {
             }
         }
         self.variables.retain(|v| v.lifetime_end >= line || match v.location {VariableLocation::Register(_) => false, VariableLocation::Stack(_) => true});
+        for var in &self.float_variables {
+            if var.lifetime_end >= line {
+                continue;
+            }
+            match var.location {
+                FloatLocation::Register(r) => self.free_float_registers.push(r),
+                FloatLocation::Stack(_) => ()
+            }
+        }
+        self.float_variables.retain(|v| v.lifetime_end >= line || match v.location {FloatLocation::Register(_) => false, FloatLocation::Stack(_) => true});
     }
 
 
-    fn allcoate(&mut self, name: &str, line: u64, lifetime_checker: &mut lifetime::LifetimeChecker, code_assembler: &mut CodeAssembler) -> VariableLocation {
+    fn allcoate(&mut self, name: &str, line: u64, lifetime_checker: &mut lifetime::LifetimeChecker) -> VariableLocation {
         self.check(line);
         let lifetime_end = lifetime_checker.get_end_lifetime(name) as u64;
-        if self.free_registers.len() > 0 {
+        if !self.free_registers.is_empty() {
             //register allocation
             let location = VariableLocation::Register(self.free_registers.pop().unwrap());
-            self.variables.push(AllocatedVariable { location: location, lifetime_end: lifetime_end, name: name.to_owned() });
+            self.variables.push(AllocatedVariable { location, lifetime_end, name: name.to_owned() });
             location
         }else{
             let location = VariableLocation::Stack(self.next_stack_variable_offset);
-            self.next_stack_variable_offset = self.next_stack_variable_offset - 8;
-            self.variables.push(AllocatedVariable { location: location, lifetime_end: lifetime_end, name: name.to_owned() });
-            code_assembler.push(rbx).unwrap();
+            self.next_stack_variable_offset -= 8;
+            self.variables.push(AllocatedVariable { location, lifetime_end, name: name.to_owned() });
             location
         }
     }
 
-    pub fn get(&mut self, name: &str, line: u64, lifetime_checker: &mut lifetime::LifetimeChecker, code_assembler: &mut CodeAssembler) -> VariableLocation {
+    fn allocate_float(&mut self, name: &str, line: u64, lifetime_checker: &mut lifetime::LifetimeChecker) -> FloatLocation {
+        self.check(line);
+        let lifetime_end = lifetime_checker.get_end_lifetime(name) as u64;
+        let location = match self.free_float_registers.pop() {
+            Some(r) => FloatLocation::Register(r),
+            None => {
+                //float and integer variables share the stack slots
+                let location = FloatLocation::Stack(self.next_stack_variable_offset);
+                self.next_stack_variable_offset -= 8;
+                location
+            }
+        };
+        self.float_variables.push(AllocatedFloatVariable { location, lifetime_end, name: name.to_owned() });
+        location
+    }
+
+    pub fn is_float(&self, name: &str) -> bool {
+        self.float_variable_names.contains(name)
+    }
+
+    pub fn get_float(&mut self, name: &str, line: u64, lifetime_checker: &mut lifetime::LifetimeChecker) -> FloatLocation {
+        match self.float_variables.iter().find(|v| v.name == name) {
+            None => self.allocate_float(name, line, lifetime_checker),
+            Some(v) => v.location
+        }
+    }
+
+    //xmm registers are caller saved -> they are saved around calls as well
+    pub fn get_allocated_float_registers(&self) -> Vec<AsmRegisterXmm> {
+        self.float_variables.iter().filter_map(|v| match v.location {
+            FloatLocation::Register(r) => Some(r),
+            FloatLocation::Stack(_) => None
+        }).collect()
+    }
+
+    pub fn get(&mut self, name: &str, line: u64, lifetime_checker: &mut lifetime::LifetimeChecker) -> VariableLocation {
         match self.variables.iter().find(|v| v.name == name) {
-            None => self.allcoate(name, line, lifetime_checker, code_assembler),
+            None => self.allcoate(name, line, lifetime_checker),
             Some(v) => v.location
         }
     }
 
-    pub fn get_num_stackvars(&mut self) -> u64 {
-        self.variables.iter().filter(|v| match v.location {VariableLocation::Register(_) => false, VariableLocation::Stack(v) => v < 0}).count() as u64
+    //reserves consecutive slots for an object in the stack frame, returns the rbp offset of the first (lowest) slot
+    pub fn allocate_stack_object(&mut self, num_slots: u64) -> i64 {
+        self.next_stack_variable_offset -= num_slots as i64 * 8;
+        self.next_stack_variable_offset + 8
+    }
+
+    //number of 8 byte slots the stack frame needs for spilled variables and stack objects
+    pub fn get_num_stack_slots(&self) -> u64 {
+        ((FIRST_STACK_VARIABLE_OFFSET - self.next_stack_variable_offset) / 8) as u64
     }
 }
@@ -0,0 +1,138 @@
+//differential testing: every program is executed by the jit and by the reference evaluator,
+//the return values and the printed output have to match
+
+use std::fs;
+use crate::{evaluator, execute_code, lexer, parser, predefined_functions, semantic, types, ExecuteOptions};
+use crate::generator::{self, GeneratorConfig};
+
+const PROGRAM_DIRECTORIES: [&str; 2] = ["test", "examples"];
+//input for programs that call read_num
+const INPUT: &str = "5\n3\n0\n";
+const NUM_RANDOM_PROGRAMS: u64 = 200;
+
+#[derive(Debug, PartialEq)]
+struct Outcome {
+    result: Result<i64, ()>,
+    output: String
+}
+
+fn get_num_main_parameters(code: &str) -> usize {
+    let program = parser::parse(&mut lexer::lex(code)).unwrap();
+    program.functions.iter().find_map(|f| match f {
+        parser::Function::Internal(f) if f.name == "main" => Some(f.parameters.len()),
+        _ => None
+    }).unwrap_or(0)
+}
+
+fn run_jit(code: &str, args: Vec<i64>, options: &ExecuteOptions) -> Outcome {
+    let (result, output) = predefined_functions::capture_io(INPUT, || execute_code(code, args, options));
+    Outcome { result, output }
+}
+
+fn run_reference(code: &str, args: Vec<i64>) -> Outcome {
+    let mut program = parser::parse(&mut lexer::lex(code)).unwrap();
+    predefined_functions::add(&mut program);
+    //programs that are rejected by the jit aren't evaluated, both sides rejecting a program is no difference
+    if semantic::check(&program).is_err() {
+        return Outcome { result: Err(()), output: String::new() };
+    }
+    //the evaluator needs the float operations that are inserted by the type check
+    if types::check(&mut program).is_err() {
+        return Outcome { result: Err(()), output: String::new() };
+    }
+    let (result, output) = predefined_functions::capture_io(INPUT, || evaluator::Evaluator::new(&program).execute_main(args).map_err(|_| ()));
+    Outcome { result, output }
+}
+
+//returns a description of the difference if the jit doesn't behave like the reference evaluator
+fn compare(name: &str, code: &str, options: &ExecuteOptions) -> Option<String> {
+    let args: Vec<i64> = (1..=get_num_main_parameters(code) as i64).collect();
+    let reference = run_reference(code, args.clone());
+    let jit = run_jit(code, args, options);
+    if reference == jit {
+        return None;
+    }
+    Some(format!("{}:\n  reference: {:?}\n  jit:       {:?}\n{}", name, reference, jit, code))
+}
+
+fn assert_no_differences(differences: Vec<String>) {
+    assert!(differences.is_empty(), "{} program(s) behave differently:\n{}", differences.len(), differences.join("\n"))
+}
+
+fn get_program_files() -> Vec<String> {
+    let mut files = vec![];
+    for dir in PROGRAM_DIRECTORIES {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|e| e == "ji") {
+                files.push(path.to_string_lossy().into_owned());
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+#[test]
+fn differential_program_files() {
+    let differences = get_program_files().iter().filter_map(|file| {
+        let code = fs::read_to_string(file).unwrap();
+        compare(file, &code, &ExecuteOptions::default())
+    }).collect();
+    assert_no_differences(differences);
+}
+
+#[test]
+fn differential_rejected_programs() {
+    //the invalid code is never executed, only the semantic check finds it
+    let codes = [
+        "fun main() { if(0) { return g(1); } return 1; }",
+        "fun main() { if(0) { return a; } return 1; }",
+        "fun f(a) { return a; } fun main() { if(0) { return f(); } return 1; }"
+    ];
+    for code in codes {
+        assert_eq!(compare("rejected program", code, &ExecuteOptions::default()), None);
+    }
+}
+
+#[test]
+fn differential_random_programs() {
+    let differences = (0..NUM_RANDOM_PROGRAMS).filter_map(|seed| {
+        compare(&format!("random program {}", seed), &generator::generate(seed, &GeneratorConfig::default()), &ExecuteOptions::default())
+    }).collect();
+    assert_no_differences(differences);
+}
+
+#[test]
+fn differential_random_programs_tiered() {
+    let differences = (0..NUM_RANDOM_PROGRAMS).filter_map(|seed| {
+        compare(&format!("random program {}", seed), &generator::generate(seed, &GeneratorConfig::default()), &ExecuteOptions { tier_up_threshold: Some(1), ..Default::default() })
+    }).collect();
+    assert_no_differences(differences);
+}
+
+
+#[test]
+fn differential_program_files_optimized() {
+    let differences = get_program_files().iter().filter_map(|file| {
+        let code = fs::read_to_string(file).unwrap();
+        compare(file, &code, &ExecuteOptions { optimize: true, ..Default::default() })
+    }).collect();
+    assert_no_differences(differences);
+}
+
+#[test]
+fn differential_random_programs_optimized() {
+    let differences = (0..NUM_RANDOM_PROGRAMS).filter_map(|seed| {
+        compare(&format!("random program {}", seed), &generator::generate(seed, &GeneratorConfig::default()), &ExecuteOptions { optimize: true, ..Default::default() })
+    }).collect();
+    assert_no_differences(differences);
+}
+
+#[test]
+fn differential_random_programs_interpreted_optimized() {
+    let differences = (0..NUM_RANDOM_PROGRAMS).filter_map(|seed| {
+        compare(&format!("random program {}", seed), &generator::generate(seed, &GeneratorConfig::default()), &ExecuteOptions { interpret: true, optimize: true, ..Default::default() })
+    }).collect();
+    assert_no_differences(differences);
+}
@@ -0,0 +1,524 @@
+//reference evaluator that walks the parse tree (no ssa, ir or asm involved)
+
+use std::collections::HashMap;
+use std::mem;
+use crate::jit::{MAX_ARRAY_SIZE, MAX_HEAP_SIZE};
+use crate::memory::StringTable;
+use crate::parser::{self, Expression, Statement};
+use crate::predefined_functions::{self, ExternalFunction};
+use crate::types::float_to_int;
+
+#[derive(Debug, PartialEq)]
+pub enum EvaluationError {
+    FunctionDoesNotExist(String),
+    VariableNotDefined(String),
+    DivisionByZero,
+    DivisionOverflow,
+    TooManyArguments(String),
+    IndexOutOfBounds(i64, i64),
+    InvalidArraySize(i64),
+    InvalidArray(i64),
+    InvalidFunction(i64),
+    InvalidField(String),
+    InvalidNumberOfArguments(String, u64, u64),
+    OutOfMemory(i64),
+    InvalidString(i64)
+}
+
+impl std::fmt::Display for EvaluationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FunctionDoesNotExist(fun) =>
+                write!(f, "function does not exist: {}", fun),
+            Self::VariableNotDefined(var) =>
+                write!(f, "variable used before it was defined: {}", var),
+            Self::DivisionByZero =>
+                write!(f, "division by zero"),
+            Self::DivisionOverflow =>
+                write!(f, "division overflow: {} / -1", i64::MIN),
+            Self::TooManyArguments(fun) =>
+                write!(f, "The evaluator can't call the external function {} with so many arguments", fun),
+            Self::IndexOutOfBounds(index, length) =>
+                write!(f, "index {} out of bounds for array of length {}", index, length),
+            Self::InvalidArraySize(size) =>
+                write!(f, "invalid array size: {}", size),
+            Self::InvalidArray(value) =>
+                write!(f, "not an array: {}", value),
+            Self::InvalidFunction(value) =>
+                write!(f, "not a function: {}", value),
+            Self::InvalidField(field) =>
+                write!(f, "not a struct with the field: {}", field),
+            Self::InvalidNumberOfArguments(fun, expected, found) =>
+                write!(f, "Invalid number of arguments for function {}! Expected {:?}. Found {:?}.", fun, expected, found),
+            Self::OutOfMemory(size) =>
+                write!(f, "out of memory, can't allocate an object with {} elements", size),
+            Self::InvalidString(value) =>
+                write!(f, "not a string: {}", value),
+        }
+    }
+}
+
+type EvaluationResult<T> = Result<T, EvaluationError>;
+
+type Variables = HashMap<String, i64>;
+
+//how a block was left
+enum Flow {
+    Normal,
+    Return(i64),
+    Break,
+    Continue
+}
+
+pub struct Evaluator<'a> {
+    program: &'a parser::Program,
+    globals: Variables, //constants and globals
+    arrays: Vec<Vec<i64>>, //arrays are referenced by their index + 1
+    structs: Vec<(usize, Vec<i64>)>, //id and fields, structs are referenced by -(index + 1)
+    heap_size: i64, //same limit as the jit (one word for the header of every object)
+    strings: HashMap<String, StringTable> //string literals, created when they are evaluated the first time
+}
+
+fn call_external(function: &ExternalFunction, args: &[i64]) -> EvaluationResult<i64> {
+    if function.uses_floats() {
+        return function.call_with_floats(args).ok_or_else(|| EvaluationError::TooManyArguments(function.name.to_owned()));
+    }
+    unsafe {
+        let address = function.address as usize;
+        let res = match args.len() {
+            0 => mem::transmute::<usize, extern "C" fn() -> i64>(address)(),
+            1 => mem::transmute::<usize, extern "C" fn(i64) -> i64>(address)(args[0]),
+            2 => mem::transmute::<usize, extern "C" fn(i64, i64) -> i64>(address)(args[0], args[1]),
+            3 => mem::transmute::<usize, extern "C" fn(i64, i64, i64) -> i64>(address)(args[0], args[1], args[2]),
+            _ => return Err(EvaluationError::TooManyArguments(function.name.to_owned()))
+        };
+        match predefined_functions::take_invalid_string() {
+            Some(value) => Err(EvaluationError::InvalidString(value)),
+            None => Ok(res)
+        }
+    }
+}
+
+fn to_bool(value: i64) -> bool {
+    value != 0
+}
+
+impl<'a> Evaluator<'a> {
+    pub fn new(program: &'a parser::Program) -> Self {
+        Evaluator { program, globals: Variables::new(), arrays: vec![], structs: vec![], heap_size: 0, strings: HashMap::new() }
+    }
+
+    pub fn execute_main(&mut self, args: Vec<i64>) -> EvaluationResult<i64> {
+        //globals are 0 until their initializer was evaluated
+        self.globals = self.program.globals.iter().map(|g| (g.variable_name.to_owned(), 0)).collect();
+        let mut no_variables = Variables::new();
+        for constant in &self.program.constants {
+            let value = self.evaluate_expression(&constant.expression, &mut no_variables)?;
+            self.globals.insert(constant.variable_name.to_owned(), value);
+        }
+        for global in &self.program.globals {
+            self.assign(global, &mut no_variables)?;
+        }
+        self.call(&"main".to_owned(), args)
+    }
+
+    fn assign(&mut self, assignment: &parser::Assignment, variables: &mut Variables) -> EvaluationResult<()> {
+        let value = self.evaluate_expression(&assignment.expression, variables)?;
+        //local variables can't shadow globals
+        if self.globals.contains_key(&assignment.variable_name) {
+            self.globals.insert(assignment.variable_name.to_owned(), value);
+        }else{
+            variables.insert(assignment.variable_name.to_owned(), value);
+        }
+        Ok(())
+    }
+
+    fn allocate(&mut self, num_elements: i64) -> EvaluationResult<()> {
+        if self.heap_size + num_elements + 1 > MAX_HEAP_SIZE {
+            return Err(EvaluationError::OutOfMemory(num_elements));
+        }
+        self.heap_size += num_elements + 1;
+        Ok(())
+    }
+
+    fn get_element(&mut self, array: i64, index: i64) -> EvaluationResult<&mut i64> {
+        let array = match usize::try_from(array.wrapping_sub(1)).ok().and_then(|idx| self.arrays.get_mut(idx)) {
+            Some(a) => a,
+            None => return Err(EvaluationError::InvalidArray(array))
+        };
+        let length = array.len() as i64;
+        match usize::try_from(index).ok().and_then(|idx| array.get_mut(idx)) {
+            Some(element) => Ok(element),
+            None => Err(EvaluationError::IndexOutOfBounds(index, length))
+        }
+    }
+
+    fn get_field(&mut self, object: i64, field: &String) -> EvaluationResult<&mut i64> {
+        let element = usize::try_from(-(object as i128) - 1).ok().and_then(|idx| self.structs.get_mut(idx));
+        if let Some((id, fields)) = element {
+            if let Some(idx) = self.program.structs[*id].fields.iter().position(|f| f == field) {
+                return Ok(&mut fields[idx]);
+            }
+        }
+        Err(EvaluationError::InvalidField(field.to_owned()))
+    }
+
+    fn call(&mut self, name: &String, args: Vec<i64>) -> EvaluationResult<i64> {
+        let function = self.program.functions.iter().find(|f| match f {
+            parser::Function::Internal(f) => &f.name == name,
+            parser::Function::External(f) => &f.name == name
+        });
+        match function {
+            None => Err(EvaluationError::FunctionDoesNotExist(name.to_owned())),
+            Some(parser::Function::External(f)) => call_external(f, &args),
+            Some(parser::Function::Internal(f)) => {
+                let mut variables: Variables = f.parameters.iter().cloned().zip(args).collect();
+                match self.evaluate_block(&f.block, &mut variables)? {
+                    Flow::Return(value) => Ok(value),
+                    //functions without return statement return 0
+                    _ => Ok(0)
+                }
+            }
+        }
+    }
+
+    //the variables that are declared in a block aren't visible after it (calls of their names are direct calls again)
+    fn evaluate_scope(&mut self, block: &parser::Block, variables: &mut Variables) -> EvaluationResult<Flow> {
+        let flow = self.evaluate_block(block, variables)?;
+        for statement in block {
+            if let Statement::Assignment(a) = statement {
+                if a.declaration {
+                    variables.remove(&a.variable_name);
+                }
+            }
+        }
+        Ok(flow)
+    }
+
+    fn evaluate_block(&mut self, block: &parser::Block, variables: &mut Variables) -> EvaluationResult<Flow> {
+        for statement in block {
+            match statement {
+                Statement::Assignment(a) => self.assign(a, variables)?,
+                Statement::ArrayAssignment(a) => {
+                    let array = self.evaluate_expression(&a.array, variables)?;
+                    let index = self.evaluate_expression(&a.index, variables)?;
+                    let value = self.evaluate_expression(&a.expression, variables)?;
+                    *self.get_element(array, index)? = value;
+                },
+                Statement::FieldAssignment(a) => {
+                    let object = self.evaluate_expression(&a.object, variables)?;
+                    let value = self.evaluate_expression(&a.expression, variables)?;
+                    *self.get_field(object, &a.field)? = value;
+                },
+                Statement::FunctionCall(fc) => {
+                    self.evaluate_function_call(fc, variables)?;
+                },
+                Statement::IfStatement(s) => {
+                    let block = if to_bool(self.evaluate_expression(&s.condition, variables)?) {
+                        Some(&s.block)
+                    }else{
+                        s.else_block.as_ref()
+                    };
+                    if let Some(block) = block {
+                        match self.evaluate_scope(block, variables)? {
+                            Flow::Normal => (),
+                            flow => return Ok(flow)
+                        }
+                    }
+                },
+                Statement::WhileLoop(l) => {
+                    while to_bool(self.evaluate_expression(&l.condition, variables)?) {
+                        match self.evaluate_scope(&l.block, variables)? {
+                            Flow::Normal | Flow::Continue => (),
+                            Flow::Break => break,
+                            Flow::Return(value) => return Ok(Flow::Return(value))
+                        }
+                    }
+                },
+                Statement::ForLoop(l) => {
+                    if let Some(init) = &l.init {
+                        self.assign(init, variables)?;
+                    }
+                    while to_bool(self.evaluate_expression(&l.condition, variables)?) {
+                        match self.evaluate_scope(&l.block, variables)? {
+                            Flow::Normal | Flow::Continue => (),
+                            Flow::Break => break,
+                            Flow::Return(value) => return Ok(Flow::Return(value))
+                        }
+                        if let Some(step) = &l.step {
+                            self.assign(step, variables)?;
+                        }
+                    }
+                    if let Some(init) = l.init.as_ref().filter(|init| init.declaration) {
+                        variables.remove(&init.variable_name);
+                    }
+                },
+                Statement::Return(e) => {
+                    return Ok(Flow::Return(self.evaluate_expression(e, variables)?));
+                },
+                Statement::Break => return Ok(Flow::Break),
+                Statement::Continue => return Ok(Flow::Continue)
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    //calls of variables (locals, parameters and globals, but not constants) are indirect calls
+    fn evaluate_function_call(&mut self, function_call: &parser::FunctionCall, variables: &mut Variables) -> EvaluationResult<i64> {
+        let is_global = self.program.globals.iter().any(|g| g.variable_name == function_call.name);
+        let function = match variables.get(&function_call.name) {
+            Some(function) => Some(function.to_owned()),
+            None if is_global => Some(self.globals[&function_call.name]),
+            None => None
+        };
+        let mut args = vec![];
+        for arg in &function_call.arguments {
+            args.push(self.evaluate_expression(arg, variables)?);
+        }
+        match function {
+            Some(function) => self.call_reference(function, args),
+            None => self.call(&function_call.name, args)
+        }
+    }
+
+    //functions are referenced by their index in the program + 1
+    fn get_function_reference(&self, name: &String) -> EvaluationResult<i64> {
+        let idx = self.program.functions.iter().position(|f| match f {
+            parser::Function::Internal(f) => &f.name == name,
+            parser::Function::External(f) => &f.name == name
+        });
+        match idx {
+            Some(idx) => Ok(idx as i64 + 1),
+            None => Err(EvaluationError::FunctionDoesNotExist(name.to_owned()))
+        }
+    }
+
+    fn call_reference(&mut self, function: i64, args: Vec<i64>) -> EvaluationResult<i64> {
+        let (name, num_parameters) = match usize::try_from(function.wrapping_sub(1)).ok().and_then(|idx| self.program.functions.get(idx)) {
+            Some(parser::Function::Internal(f)) => (f.name.to_owned(), f.parameters.len()),
+            Some(parser::Function::External(f)) => (f.name.to_owned(), f.parameters.len()),
+            None => return Err(EvaluationError::InvalidFunction(function))
+        };
+        if num_parameters != args.len() {
+            return Err(EvaluationError::InvalidNumberOfArguments(name, num_parameters as u64, args.len() as u64));
+        }
+        self.call(&name, args)
+    }
+
+    fn evaluate_binary(&mut self, b: &(Expression, Expression), variables: &mut Variables) -> EvaluationResult<(i64, i64)> {
+        let left = self.evaluate_expression(&b.0, variables)?;
+        let right = self.evaluate_expression(&b.1, variables)?;
+        Ok((left, right))
+    }
+
+    //floats are stored as their bits
+    fn evaluate_float_binary(&mut self, b: &(Expression, Expression), variables: &mut Variables) -> EvaluationResult<(f64, f64)> {
+        let (left, right) = self.evaluate_binary(b, variables)?;
+        Ok((f64::from_bits(left as u64), f64::from_bits(right as u64)))
+    }
+
+    fn evaluate_expression(&mut self, expression: &Expression, variables: &mut Variables) -> EvaluationResult<i64> {
+        match expression {
+            Expression::Number(n) => Ok(n.to_owned()),
+            Expression::Float(f) => Ok(f.to_bits() as i64),
+            Expression::Variable(v) | Expression::Global(v) => match variables.get(v).or(self.globals.get(v)) {
+                Some(value) => Ok(value.to_owned()),
+                None => Err(EvaluationError::VariableNotDefined(v.to_owned()))
+            },
+            Expression::FunctionCall(fc) => self.evaluate_function_call(fc, variables),
+            Expression::FunctionReference(f) => self.get_function_reference(f),
+            Expression::IndirectCall(c) => {
+                let function = self.evaluate_expression(&c.function, variables)?;
+                let mut args = vec![];
+                for arg in &c.arguments {
+                    args.push(self.evaluate_expression(arg, variables)?);
+                }
+                self.call_reference(function, args)
+            },
+            Expression::Addition(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                Ok(l.wrapping_add(r))
+            },
+            Expression::Subtraction(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                Ok(l.wrapping_sub(r))
+            },
+            Expression::Multiplication(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                Ok(l.wrapping_mul(r))
+            },
+            Expression::Division(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                if r == 0 {
+                    return Err(EvaluationError::DivisionByZero);
+                }
+                if l == i64::MIN && r == -1 {
+                    return Err(EvaluationError::DivisionOverflow);
+                }
+                Ok(l / r)
+            },
+            Expression::Modulo(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                if r == 0 {
+                    return Err(EvaluationError::DivisionByZero);
+                }
+                if l == i64::MIN && r == -1 {
+                    return Err(EvaluationError::DivisionOverflow);
+                }
+                Ok(l % r)
+            },
+            Expression::Greater(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                Ok((l > r) as i64)
+            },
+            Expression::GreaterEquals(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                Ok((l >= r) as i64)
+            },
+            Expression::Less(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                Ok((l < r) as i64)
+            },
+            Expression::LessEquals(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                Ok((l <= r) as i64)
+            },
+            Expression::Equals(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                Ok((l == r) as i64)
+            },
+            Expression::NotEquals(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                Ok((l != r) as i64)
+            },
+            //short circuit
+            Expression::LogicAnd(b) => {
+                if !to_bool(self.evaluate_expression(&b.0, variables)?) {
+                    return Ok(0);
+                }
+                Ok(to_bool(self.evaluate_expression(&b.1, variables)?) as i64)
+            },
+            Expression::LogicOr(b) => {
+                if to_bool(self.evaluate_expression(&b.0, variables)?) {
+                    return Ok(1);
+                }
+                Ok(to_bool(self.evaluate_expression(&b.1, variables)?) as i64)
+            },
+            Expression::BitwiseAnd(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                Ok(l & r)
+            },
+            Expression::BitwiseOr(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                Ok(l | r)
+            },
+            Expression::BitwiseXor(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                Ok(l ^ r)
+            },
+            Expression::ShiftLeft(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                //the shift count is taken modulo 64 (like x86)
+                Ok(l.wrapping_shl(r as u32))
+            },
+            Expression::ShiftRight(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                Ok(l.wrapping_shr(r as u32))
+            },
+            Expression::ShiftRightLogical(b) => {
+                let (l, r) = self.evaluate_binary(b, variables)?;
+                Ok((l as u64).wrapping_shr(r as u32) as i64)
+            },
+            Expression::Negation(e) => Ok(self.evaluate_expression(e, variables)?.wrapping_neg()),
+            Expression::LogicNot(e) => Ok(!to_bool(self.evaluate_expression(e, variables)?) as i64),
+            Expression::BitwiseNot(e) => Ok(!self.evaluate_expression(e, variables)?),
+            Expression::Cast(e, _) => self.evaluate_expression(e, variables),
+            Expression::FloatAddition(b) => {
+                let (l, r) = self.evaluate_float_binary(b, variables)?;
+                Ok((l + r).to_bits() as i64)
+            },
+            Expression::FloatSubtraction(b) => {
+                let (l, r) = self.evaluate_float_binary(b, variables)?;
+                Ok((l - r).to_bits() as i64)
+            },
+            Expression::FloatMultiplication(b) => {
+                let (l, r) = self.evaluate_float_binary(b, variables)?;
+                Ok((l * r).to_bits() as i64)
+            },
+            Expression::FloatDivision(b) => {
+                let (l, r) = self.evaluate_float_binary(b, variables)?;
+                Ok((l / r).to_bits() as i64)
+            },
+            Expression::FloatGreater(b) => {
+                let (l, r) = self.evaluate_float_binary(b, variables)?;
+                Ok((l > r) as i64)
+            },
+            Expression::FloatGreaterEquals(b) => {
+                let (l, r) = self.evaluate_float_binary(b, variables)?;
+                Ok((l >= r) as i64)
+            },
+            Expression::FloatLess(b) => {
+                let (l, r) = self.evaluate_float_binary(b, variables)?;
+                Ok((l < r) as i64)
+            },
+            Expression::FloatLessEquals(b) => {
+                let (l, r) = self.evaluate_float_binary(b, variables)?;
+                Ok((l <= r) as i64)
+            },
+            Expression::FloatEquals(b) => {
+                let (l, r) = self.evaluate_float_binary(b, variables)?;
+                Ok((l == r) as i64)
+            },
+            Expression::FloatNotEquals(b) => {
+                let (l, r) = self.evaluate_float_binary(b, variables)?;
+                Ok((l != r) as i64)
+            },
+            Expression::FloatNegation(e) => {
+                let value = f64::from_bits(self.evaluate_expression(e, variables)? as u64);
+                Ok((-value).to_bits() as i64)
+            },
+            Expression::IntToFloat(e) => Ok((self.evaluate_expression(e, variables)? as f64).to_bits() as i64),
+            Expression::FloatToInt(e) => Ok(float_to_int(f64::from_bits(self.evaluate_expression(e, variables)? as u64))),
+            Expression::NewArray(e) => {
+                let size = self.evaluate_expression(e, variables)?;
+                if !(0..=MAX_ARRAY_SIZE).contains(&size) {
+                    return Err(EvaluationError::InvalidArraySize(size));
+                }
+                self.allocate(size)?;
+                self.arrays.push(vec![0; size as usize]);
+                Ok(self.arrays.len() as i64)
+            },
+            Expression::ArrayLength(e) => {
+                let array = self.evaluate_expression(e, variables)?;
+                match usize::try_from(array.wrapping_sub(1)).ok().and_then(|idx| self.arrays.get(idx)) {
+                    Some(a) => Ok(a.len() as i64),
+                    None => Err(EvaluationError::InvalidArray(array))
+                }
+            },
+            Expression::ArrayAccess(b) => {
+                let (array, index) = self.evaluate_binary(b, variables)?;
+                Ok(*self.get_element(array, index)?)
+            },
+            Expression::NewStruct(s) => {
+                let id = self.program.structs.iter().position(|d| d.name == s.name).expect("structs are checked by the semantic analysis");
+                let mut fields = vec![0; self.program.structs[id].fields.len()];
+                for (field, e) in &s.fields {
+                    let value = self.evaluate_expression(e, variables)?;
+                    let idx = self.program.structs[id].fields.iter().position(|f| f == field).expect("fields are checked by the semantic analysis");
+                    fields[idx] = value;
+                }
+                self.allocate(fields.len() as i64)?;
+                self.structs.push((id, fields));
+                Ok(-(self.structs.len() as i64))
+            },
+            Expression::FieldAccess(e, field) => {
+                let object = self.evaluate_expression(e, variables)?;
+                Ok(*self.get_field(object, field)?)
+            },
+            Expression::String(s) => {
+                let table = self.strings.entry(s.to_owned()).or_insert_with(|| StringTable::new(&[s.to_owned()]));
+                Ok(table.get_address(s).unwrap() as i64)
+            },
+        }
+    }
+}
@@ -0,0 +1,711 @@
+//generates random programs that pass the semantic and type check and always terminate:
+//- variables are declared with let and only used after their declaration in a visible block
+//- functions only call functions that were generated before them (no recursion)
+//- loops have their own counter that is only changed at the start of a while loop or in the step of a for loop
+//  (continue can't skip it)
+//- constants are only initialized with constants, globals can be read and changed by every function
+//- divisors are always in [2, 14] or guarded by a short circuit check
+//- array variables are only used for indexing and len (the value of an array differs between the execution engines),
+//  most indices are wrapped into the bounds of the array
+//- string literals are only passed directly to the string builtins (for the same reason)
+//- function references are only called (the engines number the functions differently)
+//- float variables are typed and only used in float expressions, the results only reach the untyped integers
+//  through print_num and if conditions (typed ints and bools can't be mixed with untyped values everywhere)
+//- declarations are never initialized with a number literal (the variable would be an int)
+//- struct variables always reference a struct of the same type and are only used for field accesses of its fields
+//  (the handles differ between the execution engines), fields only contain untyped integers. structs are never
+//  passed to functions, stored in fields or arrays or used in globals, they are only moved to the heap by allocations
+//  in loops and by reassigning the variable
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[derive(Debug, Clone)]
+pub struct GeneratorConfig {
+    pub max_depth: u64, //nesting of blocks and expressions
+    pub max_statements: u64, //per block
+    pub max_functions: u64, //without main
+    pub max_parameters: u64,
+    pub loops: bool,
+    pub calls: bool,
+    pub globals: bool, //constants and globals
+    pub arrays: bool,
+    pub strings: bool,
+    pub references: bool, //function references and indirect calls
+    pub floats: bool,
+    pub structs: bool //struct declarations, struct literals and field accesses
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig {
+            max_depth: 3,
+            max_statements: 6,
+            max_functions: 3,
+            max_parameters: 8,
+            loops: true,
+            calls: true,
+            globals: true,
+            arrays: true,
+            strings: true,
+            references: true,
+            floats: true,
+            structs: true
+        }
+    }
+}
+
+//`(n + 0)` is an untyped initializer
+fn initializer(expression: String) -> String {
+    match expression.trim_start_matches("-(").trim_end_matches(')').parse::<i64>() {
+        Ok(_) => format!("({} + 0)", expression),
+        Err(_) => expression
+    }
+}
+
+struct Variable {
+    name: String,
+    assignable: bool, //loop counters are read only
+    array: bool,
+    float: bool,
+    reference: Option<u64>, //number of parameters of the referenced function
+    structure: Option<usize> //index of the struct
+}
+
+impl Variable {
+    //untyped integer that can be used in every expression
+    fn is_number(&self) -> bool {
+        !self.array && !self.float && self.reference.is_none() && self.structure.is_none()
+    }
+}
+
+struct Generator<'a> {
+    rng: StdRng,
+    config: &'a GeneratorConfig,
+    functions: Vec<(String, u64)>, //callable functions and their number of parameters
+    structs: Vec<(String, Vec<String>)>, //names and fields
+    scopes: Vec<Vec<Variable>>,
+    num_variables: u64,
+    loop_depth: u64,
+    in_constant: bool //constants can't call the string builtins
+}
+
+impl<'a> Generator<'a> {
+    fn new(seed: u64, config: &'a GeneratorConfig) -> Self {
+        Generator {
+            rng: StdRng::seed_from_u64(seed),
+            config,
+            functions: vec![],
+            structs: vec![],
+            scopes: vec![],
+            num_variables: 0,
+            loop_depth: 0,
+            in_constant: false
+        }
+    }
+
+    fn new_variable_name(&mut self, prefix: &str) -> String {
+        self.num_variables += 1;
+        format!("{}{}", prefix, self.num_variables)
+    }
+
+    fn declare(&mut self, name: &str, assignable: bool) {
+        self.scopes.last_mut().unwrap().push(Variable { name: name.to_owned(), assignable, array: false, float: false, reference: None, structure: None });
+    }
+
+    fn declare_array(&mut self, name: &str) {
+        self.scopes.last_mut().unwrap().push(Variable { name: name.to_owned(), assignable: true, array: true, float: false, reference: None, structure: None });
+    }
+
+    fn declare_reference(&mut self, name: &str, num_parameters: u64) {
+        self.scopes.last_mut().unwrap().push(Variable { name: name.to_owned(), assignable: true, array: false, float: false, reference: Some(num_parameters), structure: None });
+    }
+
+    fn declare_float(&mut self, name: &str) {
+        self.scopes.last_mut().unwrap().push(Variable { name: name.to_owned(), assignable: true, array: false, float: true, reference: None, structure: None });
+    }
+
+    fn declare_struct(&mut self, name: &str, structure: usize) {
+        self.scopes.last_mut().unwrap().push(Variable { name: name.to_owned(), assignable: true, array: false, float: false, reference: None, structure: Some(structure) });
+    }
+
+    fn visible_variables(&self, only_assignable: bool) -> Vec<String> {
+        self.scopes.iter().flatten().filter(|v| v.is_number() && (v.assignable || !only_assignable)).map(|v| v.name.to_owned()).collect()
+    }
+
+    fn visible_structs(&self) -> Vec<(String, usize)> {
+        self.scopes.iter().flatten().filter_map(|v| Some((v.name.to_owned(), v.structure?))).collect()
+    }
+
+    //struct variable and one of its fields
+    fn random_field(&mut self) -> Option<(String, String)> {
+        let structs = self.visible_structs();
+        if !self.config.structs || structs.is_empty() {
+            return None;
+        }
+        let (name, structure) = structs[self.rng.gen_range(0..structs.len())].clone();
+        let fields = &self.structs[structure].1;
+        Some((name, fields[self.rng.gen_range(0..fields.len())].to_owned()))
+    }
+
+    //every field is listed, in random order
+    fn struct_literal(&mut self, structure: usize) -> String {
+        let (name, mut fields) = self.structs[structure].clone();
+        let rotation = self.rng.gen_range(0..fields.len());
+        fields.rotate_left(rotation);
+        let values: Vec<String> = fields.iter().map(|f| format!("{}: {}", f, self.expression(2))).collect();
+        format!("{} {{ {} }}", name, values.join(", "))
+    }
+
+    fn visible_floats(&self) -> Vec<String> {
+        self.scopes.iter().flatten().filter(|v| v.float).map(|v| v.name.to_owned()).collect()
+    }
+
+    fn visible_references(&self) -> Vec<(String, u64)> {
+        self.scopes.iter().flatten().filter_map(|v| Some((v.name.to_owned(), v.reference?))).collect()
+    }
+
+    fn visible_arrays(&self) -> Vec<String> {
+        self.scopes.iter().flatten().filter(|v| v.array).map(|v| v.name.to_owned()).collect()
+    }
+
+    fn random_array(&mut self) -> Option<String> {
+        let arrays = self.visible_arrays();
+        if !self.config.arrays || arrays.is_empty() {
+            return None;
+        }
+        Some(arrays[self.rng.gen_range(0..arrays.len())].to_owned())
+    }
+
+    //sizes are in [1, 15]
+    fn array_size(&mut self) -> String {
+        format!("({} % 8 + 8)", self.expression(1))
+    }
+
+    //mostly wrapped into the bounds, small numbers can be out of bounds (runtime error)
+    fn array_index(&mut self, array: &str, depth: u64) -> String {
+        if self.rng.gen_bool(0.1) {
+            return self.rng.gen_range(0..10).to_string();
+        }
+        format!("(({e} % len({a}) + len({a})) % len({a}))", e = self.expression(depth), a = array)
+    }
+
+    fn number(&mut self) -> String {
+        //mostly small numbers, sometimes ones that don't fit into 32 bit
+        if self.rng.gen_bool(0.05) {
+            return self.rng.gen_range(i32::MAX as i64..i64::MAX / 4).to_string();
+        }
+        self.rng.gen_range(0..20).to_string()
+    }
+
+    fn string_literal(&mut self) -> String {
+        let length = self.rng.gen_range(0..12);
+        let s: String = (0..length).map(|_| match self.rng.gen_range(0..20) {
+            0 => "\\n".to_owned(),
+            1 => "\\t".to_owned(),
+            2 => "\\\\".to_owned(),
+            3 => "\\\"".to_owned(),
+            _ => (self.rng.gen_range(b' '..=b'~') as char).to_string().replace('\\', "\\\\").replace('"', "\\\"")
+        }).collect();
+        format!("\"{}\"", s)
+    }
+
+    //local variable or number, the value can't be changed by function calls
+    fn local_operand(&mut self) -> String {
+        let locals: Vec<String> = self.scopes.iter().skip(1).flatten().filter(|v| v.is_number()).map(|v| v.name.to_owned()).collect();
+        if !locals.is_empty() && self.rng.gen_bool(0.6) {
+            return locals[self.rng.gen_range(0..locals.len())].to_owned();
+        }
+        self.number()
+    }
+
+    fn function_call(&mut self, depth: u64) -> Option<String> {
+        if !self.config.calls || self.functions.is_empty() {
+            return None;
+        }
+        let references = self.visible_references();
+        let (name, num_parameters) = if !references.is_empty() && self.rng.gen_bool(0.4) {
+            references[self.rng.gen_range(0..references.len())].clone()
+        }else{
+            self.functions[self.rng.gen_range(0..self.functions.len())].clone()
+        };
+        let args: Vec<String> = (0..num_parameters).map(|_| self.expression(depth.saturating_sub(1))).collect();
+        Some(format!("{}({})", name, args.join(", ")))
+    }
+
+    fn float_literal(&mut self) -> String {
+        format!("{}.{}", self.rng.gen_range(0..20), self.rng.gen_range(0..100))
+    }
+
+    //division by zero is fine (infinity or NaN)
+    fn float_expression(&mut self, depth: u64) -> String {
+        if depth == 0 || self.rng.gen_bool(0.3) {
+            let floats = self.visible_floats();
+            if !floats.is_empty() && self.rng.gen_bool(0.6) {
+                return floats[self.rng.gen_range(0..floats.len())].to_owned();
+            }
+            if self.rng.gen_bool(0.2) {
+                return format!("({} as float)", self.expression(depth.saturating_sub(1)));
+            }
+            return self.float_literal();
+        }
+        let left = self.float_expression(depth - 1);
+        match self.rng.gen_range(0..7) {
+            0 => format!("(-{})", left),
+            1 => format!("sqrt({})", left),
+            2 => format!("({} + {})", left, self.float_expression(depth - 1)),
+            3 => format!("({} - {})", left, self.float_expression(depth - 1)),
+            4 => format!("({} * {})", left, self.float_expression(depth - 1)),
+            _ => format!("({} / {})", left, self.float_expression(depth - 1))
+        }
+    }
+
+    fn expression(&mut self, depth: u64) -> String {
+        if self.rng.gen_bool(0.08) {
+            if let Some(array) = self.random_array() {
+                if self.rng.gen_bool(0.2) {
+                    return format!("len({})", array);
+                }
+                let index = self.array_index(&array, depth.saturating_sub(1));
+                return format!("{}[{}]", array, index);
+            }
+        }
+        if self.rng.gen_bool(0.08) {
+            if let Some((name, field)) = self.random_field() {
+                return format!("{}.{}", name, field);
+            }
+        }
+        if self.config.strings && !self.in_constant && self.rng.gen_bool(0.03) {
+            let s = self.string_literal();
+            if self.rng.gen_bool(0.3) {
+                return format!("str_len({})", s);
+            }
+            return format!("str_char_at({}, ({} % 12))", s, self.expression(depth.saturating_sub(1)));
+        }
+        if depth == 0 || self.rng.gen_bool(0.3) {
+            let vars = self.visible_variables(false);
+            if !vars.is_empty() && self.rng.gen_bool(0.6) {
+                return vars[self.rng.gen_range(0..vars.len())].to_owned();
+            }
+            return self.number();
+        }
+        if self.rng.gen_bool(0.1) {
+            if let Some(call) = self.function_call(depth) {
+                return call;
+            }
+        }
+        if self.rng.gen_bool(0.15) {
+            //the operand is wrapped, `--x` would be a decrement
+            let operator = ["-", "!", "~"][self.rng.gen_range(0..3)];
+            return format!("{}({})", operator, self.expression(depth - 1));
+        }
+        let left = self.expression(depth - 1);
+        let right = self.expression(depth - 1);
+        match self.rng.gen_range(0..21) {
+            0 => format!("({} + {})", left, right),
+            1 => format!("({} - {})", left, right),
+            2 => format!("({} * {})", left, right),
+            3 => format!("({} / ({} % 7 + 8))", left, right),
+            4 => format!("({} % ({} % 7 + 8))", left, right),
+            5 => format!("({} > {})", left, right),
+            6 => format!("({} >= {})", left, right),
+            7 => format!("({} < {})", left, right),
+            8 => format!("({} <= {})", left, right),
+            9 => format!("({} == {})", left, right),
+            10 => format!("({} != {})", left, right),
+            11 => format!("({} && {})", left, right),
+            12 => format!("({} & {})", left, right),
+            13 => format!("({} | {})", left, right),
+            14 => format!("({} ^ {})", left, right),
+            15 => format!("({} << {})", left, right),
+            16 => format!("({} >> {})", left, right),
+            17 => format!("({} >>> {})", left, right),
+            //the divisor is evaluated twice, calls in the left side could change a global in between
+            18 => format!("({r} > 0 && {l} / {r})", l = left, r = self.local_operand()),
+            19 => format!("({r} <= 0 || {l} % {r})", l = left, r = self.local_operand()),
+            _ => format!("({} || {})", left, right),
+        }
+    }
+
+    fn statements(&mut self, depth: u64, indent: usize) -> String {
+        let mut code = String::new();
+        for _ in 0..self.rng.gen_range(0..=self.config.max_statements) {
+            code.push_str(&self.statement(depth, indent));
+        }
+        code
+    }
+
+    fn block(&mut self, depth: u64, indent: usize) -> String {
+        self.scopes.push(vec![]);
+        let code = self.statements(depth, indent);
+        self.scopes.pop();
+        code
+    }
+
+    fn float_comparison(&mut self) -> String {
+        let operator = ["<", "<=", ">", ">=", "==", "!="][self.rng.gen_range(0..6)];
+        format!("({} {} {})", self.float_expression(1), operator, self.float_expression(1))
+    }
+
+    fn condition(&mut self) -> String {
+        if self.config.floats && self.rng.gen_bool(0.1) {
+            return self.float_comparison();
+        }
+        self.expression(2)
+    }
+
+    fn statement(&mut self, depth: u64, indent: usize) -> String {
+        let spaces = " ".repeat(indent * 4);
+        match self.rng.gen_range(0..10) {
+            0 | 1 if depth > 0 => {
+                let condition = self.condition();
+                let block = self.block(depth - 1, indent + 1);
+                let mut code = format!("{}if({}) {{\n{}{}}}", spaces, condition, block, spaces);
+                //else if chains and else blocks
+                while self.rng.gen_bool(0.3) {
+                    let condition = self.condition();
+                    let block = self.block(depth - 1, indent + 1);
+                    code.push_str(&format!(" else if({}) {{\n{}{}}}", condition, block, spaces));
+                }
+                if self.rng.gen_bool(0.4) {
+                    let block = self.block(depth - 1, indent + 1);
+                    code.push_str(&format!(" else {{\n{}{}}}", block, spaces));
+                }
+                code.push('\n');
+                code
+            },
+            2 if depth > 0 && self.config.loops => {
+                let counter = self.new_variable_name("c");
+                let iterations = initializer(self.rng.gen_range(0..5).to_string());
+                //the counter of a for loop is only visible in the loop
+                self.scopes.push(vec![]);
+                self.declare(&counter, false);
+                self.loop_depth += 1;
+                let block = self.block(depth - 1, indent + 1);
+                self.loop_depth -= 1;
+                self.scopes.pop();
+                let step = [format!("{} = {} - 1", counter, counter), format!("{} -= 1", counter), format!("{}--", counter)][self.rng.gen_range(0..3)].to_owned();
+                if self.rng.gen_bool(0.5) {
+                    return format!("{s}for(let {c} = {n}; {c} > 0; {t}) {{\n{b}{s}}}\n", s = spaces, c = counter, n = iterations, t = step, b = block);
+                }
+                format!("{s}let {c} = {n};\n{s}while({c} > 0) {{\n{s}    {t};\n{b}{s}}}\n", s = spaces, c = counter, n = iterations, t = step, b = block)
+            },
+            6 if self.loop_depth > 0 && self.rng.gen_bool(0.3) => {
+                let condition = self.expression(2);
+                let statement = ["break", "continue"][self.rng.gen_range(0..2)];
+                format!("{s}if({}) {{\n{s}    {};\n{s}}}\n", condition, statement, s = spaces)
+            },
+            3 if self.config.floats && self.rng.gen_bool(0.2) => {
+                let function = ["print_float", "println_float", "print_num", "println_num"][self.rng.gen_range(0..4)];
+                let expression = self.float_expression(2);
+                if function.ends_with("num") {
+                    return format!("{}{}({} as int);\n", spaces, function, expression);
+                }
+                format!("{}{}({});\n", spaces, function, expression)
+            },
+            8 if self.config.floats && self.rng.gen_bool(0.4) => {
+                let floats = self.visible_floats();
+                let expression = self.float_expression(2);
+                if !floats.is_empty() && self.rng.gen_bool(0.5) {
+                    let name = floats[self.rng.gen_range(0..floats.len())].to_owned();
+                    let operator = ["=", "+=", "-=", "*=", "/="][self.rng.gen_range(0..5)];
+                    return format!("{}{} {} {};\n", spaces, name, operator, expression);
+                }
+                let name = self.new_variable_name("f");
+                self.declare_float(&name);
+                format!("{}let {}: float = {};\n", spaces, name, expression)
+            },
+            3 if self.config.strings && self.rng.gen_bool(0.3) => {
+                let function = ["print_str", "println_str"][self.rng.gen_range(0..2)];
+                let s = self.string_literal();
+                format!("{}{}({});\n", spaces, function, s)
+            },
+            3 => {
+                let function = ["print_num", "println_num", "print_char", "println_char"][self.rng.gen_range(0..4)];
+                let expression = self.expression(2);
+                //number literals that are used as char have to be valid characters
+                let expression = match expression.parse::<i64>() {
+                    Ok(n) if function.ends_with("char") => (n % 128).to_string(),
+                    _ => expression
+                };
+                format!("{}{}({});\n", spaces, function, expression)
+            },
+            //references keep the number of parameters of the first function they were assigned
+            4 if self.config.calls && self.config.references && !self.functions.is_empty() && self.rng.gen_bool(0.3) => {
+                let (function, num_parameters) = self.functions[self.rng.gen_range(0..self.functions.len())].clone();
+                let references: Vec<String> = self.visible_references().into_iter().filter(|(_, n)| *n == num_parameters).map(|(r, _)| r).collect();
+                if !references.is_empty() && self.rng.gen_bool(0.5) {
+                    let name = references[self.rng.gen_range(0..references.len())].to_owned();
+                    return format!("{}{} = &{};\n", spaces, name, function);
+                }
+                let name = self.new_variable_name("r");
+                self.declare_reference(&name, num_parameters);
+                format!("{}let {} = &{};\n", spaces, name, function)
+            },
+            4 => match self.function_call(2) {
+                Some(call) => format!("{}{};\n", spaces, call),
+                None => self.statement(depth, indent)
+            },
+            7 if self.config.arrays => {
+                let arrays = self.visible_arrays();
+                if !arrays.is_empty() && self.rng.gen_bool(0.7) {
+                    let array = arrays[self.rng.gen_range(0..arrays.len())].to_owned();
+                    let index = self.array_index(&array, 2);
+                    let expression = self.expression(2);
+                    return format!("{}{}[{}] = {};\n", spaces, array, index, expression);
+                }
+                let name = if !arrays.is_empty() && self.rng.gen_bool(0.3) {
+                    arrays[self.rng.gen_range(0..arrays.len())].to_owned()
+                }else{
+                    self.new_variable_name("a")
+                };
+                let size = self.array_size();
+                if arrays.contains(&name) {
+                    return format!("{}{} = new_array({});\n", spaces, name, size);
+                }
+                self.declare_array(&name);
+                format!("{}let {} = new_array({});\n", spaces, name, size)
+            },
+            //allocations in loops and reassigned variables are heap structs, the others are in the stack frame
+            9 if self.config.structs && !self.structs.is_empty() && self.rng.gen_bool(0.5) => {
+                let structs = self.visible_structs();
+                if !structs.is_empty() && self.rng.gen_bool(0.7) {
+                    let (name, structure) = structs[self.rng.gen_range(0..structs.len())].clone();
+                    match self.rng.gen_range(0..10) {
+                        0 | 1 => {
+                            let literal = self.struct_literal(structure);
+                            return format!("{}{} = {};\n", spaces, name, literal);
+                        },
+                        2 | 3 => {
+                            let copy = self.new_variable_name("s");
+                            self.declare_struct(&copy, structure);
+                            return format!("{}let {} = {};\n", spaces, copy, name);
+                        },
+                        _ => {
+                            let fields = &self.structs[structure].1;
+                            let field = fields[self.rng.gen_range(0..fields.len())].to_owned();
+                            let expression = self.expression(2);
+                            return format!("{}{}.{} = {};\n", spaces, name, field, expression);
+                        }
+                    }
+                }
+                let structure = self.rng.gen_range(0..self.structs.len());
+                let name = self.new_variable_name("s");
+                let literal = self.struct_literal(structure);
+                self.declare_struct(&name, structure);
+                format!("{}let {} = {};\n", spaces, name, literal)
+            },
+            5 if indent > 1 && self.rng.gen_bool(0.2) => {
+                let expression = self.expression(2);
+                format!("{}return {};\n", spaces, expression)
+            },
+            _ => {
+                let vars = self.visible_variables(true);
+                let name = if !vars.is_empty() && self.rng.gen_bool(0.5) {
+                    vars[self.rng.gen_range(0..vars.len())].to_owned()
+                }else{
+                    self.new_variable_name("v")
+                };
+                let expression = self.expression(self.config.max_depth);
+                if vars.contains(&name) {
+                    //compound assignments and increments (divisors are in [2, 14])
+                    return match self.rng.gen_range(0..20) {
+                        0 => format!("{}{}++;\n", spaces, name),
+                        1 => format!("{}{}--;\n", spaces, name),
+                        2 => format!("{}{} /= ({} % 7 + 8);\n", spaces, name, expression),
+                        3 => format!("{}{} %= ({} % 7 + 8);\n", spaces, name, expression),
+                        4..=12 => {
+                            let operator = ["+=", "-=", "*=", "&=", "|=", "^=", "<<=", ">>=", ">>>="][self.rng.gen_range(0..9)];
+                            format!("{}{} {} {};\n", spaces, name, operator, expression)
+                        },
+                        _ => format!("{}{} = {};\n", spaces, name, expression)
+                    };
+                }
+                self.declare(&name, true);
+                format!("{}let {} = {};\n", spaces, name, initializer(expression))
+            }
+        }
+    }
+
+    fn function(&mut self, name: &str, num_parameters: u64) -> String {
+        let parameters: Vec<String> = (0..num_parameters).map(|_| self.new_variable_name("p")).collect();
+        self.scopes.push(vec![]);
+        for p in &parameters {
+            self.declare(p, true);
+        }
+        let block = self.statements(self.config.max_depth, 1);
+        let ret = self.expression(self.config.max_depth);
+        self.scopes.pop();
+        format!("fun {}({}) {{\n{}    return {};\n}}\n", name, parameters.join(", "), block, ret)
+    }
+
+    //constants only use constants, globals can be changed in every function
+    fn globals(&mut self) -> String {
+        let mut code = String::new();
+        self.in_constant = true;
+        for _ in 0..self.rng.gen_range(0..=2) {
+            let name = self.new_variable_name("k");
+            let expression = initializer(self.expression(2));
+            self.declare(&name, false);
+            code.push_str(&format!("const {} = {};\n", name, expression));
+        }
+        self.in_constant = false;
+        for _ in 0..self.rng.gen_range(0..=2) {
+            let name = self.new_variable_name("g");
+            let expression = initializer(self.expression(2));
+            self.declare(&name, true);
+            code.push_str(&format!("global {} = {};\n", name, expression));
+        }
+        if self.config.arrays && self.rng.gen_bool(0.5) {
+            let name = self.new_variable_name("a");
+            let size = self.array_size();
+            self.declare_array(&name);
+            code.push_str(&format!("global {} = new_array({});\n", name, size));
+        }
+        code
+    }
+
+    //the structs share the field names, so the fields have different offsets in different structs
+    fn struct_declarations(&mut self) -> String {
+        let mut code = String::new();
+        for i in 0..self.rng.gen_range(1..=3) {
+            let name = format!("S{}", i);
+            let fields: Vec<String> = ["x", "y", "z", "w"].iter().filter(|_| self.rng.gen_bool(0.6)).map(|f| f.to_string()).collect();
+            let fields = if fields.is_empty() { vec!["x".to_owned()] } else { fields };
+            code.push_str(&format!("struct {} {{ {} }}\n", name, fields.join(", ")));
+            self.structs.push((name, fields));
+        }
+        code
+    }
+
+    fn program(&mut self) -> String {
+        let mut code = String::new();
+        self.scopes.push(vec![]);
+        if self.config.structs {
+            code.push_str(&self.struct_declarations());
+            code.push('\n');
+        }
+        if self.config.globals {
+            code.push_str(&self.globals());
+            code.push('\n');
+        }
+        for i in 0..self.rng.gen_range(0..=self.config.max_functions) {
+            let name = format!("f{}", i);
+            let num_parameters = self.rng.gen_range(0..=self.config.max_parameters);
+            code.push_str(&self.function(&name, num_parameters));
+            code.push('\n');
+            //only functions generated later can call this one
+            self.functions.push((name, num_parameters));
+        }
+        code.push_str(&self.function("main", 0));
+        code
+    }
+}
+
+pub fn generate(seed: u64, config: &GeneratorConfig) -> String {
+    Generator::new(seed, config).program()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic;
+    use proptest::prelude::*;
+    use crate::{asm, ir, jit, lexer, optimizer, parser, predefined_functions, semantic, ssa, types};
+
+    const NUM_PROGRAMS: u64 = 300;
+
+    fn configs() -> Vec<GeneratorConfig> {
+        vec![
+            GeneratorConfig::default(),
+            GeneratorConfig { loops: false, calls: false, globals: false, arrays: false, strings: false, references: false, floats: false, structs: false, ..Default::default() },
+            GeneratorConfig { max_depth: 5, max_statements: 3, max_functions: 6, max_parameters: 10, ..Default::default() },
+        ]
+    }
+
+    //lexer -> parser -> semantic check -> type check -> ssa -> ir (unoptimized and optimized) -> asm for every function
+    //(without executing the code)
+    fn compile(code: &str) -> Result<(), String> {
+        let mut program = parser::parse(&mut lexer::lex(code)).map_err(|e| e.to_string())?;
+        predefined_functions::add(&mut program);
+        semantic::check(&program).map_err(|e| e.to_string())?;
+        types::check(&mut program).map_err(|e| e.to_string())?;
+        let program_ssa = ssa::convert(&program);
+        let functions = program_ssa.functions.clone();
+        let mut function_tracker = jit::FunctionTracker::new(program_ssa, false, false, None, false);
+        for function in &functions {
+            let instructions = ir::transform(function);
+            let optimized = optimizer::optimize(instructions.clone(), &function.parameters);
+            let id = function_tracker.get_id(&function.name);
+            for instructions in [instructions, optimized] {
+                let asm_instructions = asm::generate(&instructions, &function.parameters, id, jit::Tier::Optimized, &mut function_tracker).map_err(|e| e.to_string())?;
+                asm::assemble(&asm_instructions, 0).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    //runs every generated program through the pipeline and returns the failures (panics included)
+    fn fuzz_pipeline(seeds: std::ops::Range<u64>) -> Vec<String> {
+        let mut failures = vec![];
+        for config in configs() {
+            for seed in seeds.clone() {
+                let code = generate(seed, &config);
+                let res = panic::catch_unwind(|| compile(&code));
+                let error = match res {
+                    Ok(Ok(())) => continue,
+                    Ok(Err(e)) => e,
+                    Err(p) => match p.downcast_ref::<String>() {
+                        Some(s) => format!("panic: {}", s),
+                        None => format!("panic: {:?}", p.downcast_ref::<&str>())
+                    }
+                };
+                failures.push(format!("seed {} ({:?}): {}\n{}", seed, config, error, code));
+            }
+        }
+        failures
+    }
+
+    prop_compose! {
+        fn any_config()(max_depth in 0..6_u64, max_statements in 0..8_u64, max_functions in 0..6_u64, max_parameters in 0..10_u64,
+                        features in proptest::collection::vec(any::<bool>(), 8)) -> GeneratorConfig {
+            GeneratorConfig {
+                max_depth, max_statements, max_functions, max_parameters,
+                loops: features[0], calls: features[1], globals: features[2], arrays: features[3],
+                strings: features[4], references: features[5], floats: features[6], structs: features[7]
+            }
+        }
+    }
+
+    proptest! {
+        //random configurations, the failing seed and configuration are shrunk by proptest
+        #[test]
+        fn generated_programs_compile_with_any_config(seed in any::<u64>(), config in any_config()) {
+            let code = generate(seed, &config);
+            prop_assert_eq!(compile(&code), Ok(()), "{}", code);
+        }
+    }
+
+    #[test]
+    fn generate_is_deterministic() {
+        let config = GeneratorConfig::default();
+        assert_eq!(generate(42, &config), generate(42, &config));
+        assert_ne!(generate(1, &config), generate(2, &config));
+    }
+
+    #[test]
+    fn generated_programs_compile() {
+        let failures = fuzz_pipeline(0..NUM_PROGRAMS);
+        assert!(failures.is_empty(), "{} program(s) failed:\n{}", failures.len(), failures.join("\n"));
+    }
+
+    //long running fuzzing: JITTER_FUZZ_ITERATIONS=100000 cargo test fuzz_pipeline_long -- --ignored
+    #[test]
+    #[ignore]
+    fn fuzz_pipeline_long() {
+        let iterations = std::env::var("JITTER_FUZZ_ITERATIONS").ok().and_then(|i| i.parse().ok()).unwrap_or(10000);
+        let failures = fuzz_pipeline(NUM_PROGRAMS..NUM_PROGRAMS + iterations);
+        assert!(failures.is_empty(), "{} program(s) failed:\n{}", failures.len(), failures.join("\n"));
+    }
+}
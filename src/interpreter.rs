@@ -0,0 +1,709 @@
+//reference execution engine that runs the ir without generating machine code
+
+use std::collections::HashMap;
+use std::mem;
+use log::debug;
+use crate::ir::{self, Data, IrInstruction};
+use crate::ssa;
+use crate::jit::{MAX_ARRAY_SIZE, MAX_HEAP_SIZE};
+use crate::memory::StringTable;
+use crate::optimizer;
+use crate::predefined_functions::{self, ExternalFunction};
+use crate::types::float_to_int;
+
+#[derive(Debug, PartialEq)]
+pub enum InterpreterError {
+    InvalidNumberOfArguments(String, u64, u64),
+    FunctionDoesNotExist(String),
+    LabelDoesNotExist(String),
+    VariableNotDefined(String),
+    DivisionByZero,
+    DivisionOverflow,
+    TooManyArguments(String),
+    IndexOutOfBounds(i64, i64),
+    InvalidArraySize(i64),
+    InvalidArray(i64),
+    InvalidFunction(i64),
+    InvalidField(String),
+    OutOfMemory(i64),
+    InvalidString(i64)
+}
+
+impl std::fmt::Display for InterpreterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidNumberOfArguments(fun, expected, found) =>
+                write!(f, "Invalid number of arguments for function {}! Expected {:?}. Found {:?}.", fun, expected, found),
+            Self::FunctionDoesNotExist(fun) =>
+                write!(f, "function does not exist: {}", fun),
+            Self::LabelDoesNotExist(label) =>
+                write!(f, "label does not exist: {}", label),
+            Self::VariableNotDefined(var) =>
+                write!(f, "variable used before it was defined: {}", var),
+            Self::DivisionByZero =>
+                write!(f, "division by zero"),
+            Self::DivisionOverflow =>
+                write!(f, "division overflow: {} / -1", i64::MIN),
+            Self::TooManyArguments(fun) =>
+                write!(f, "The interpreter can't call the external function {} with so many arguments", fun),
+            Self::IndexOutOfBounds(index, length) =>
+                write!(f, "index {} out of bounds for array of length {}", index, length),
+            Self::InvalidArraySize(size) =>
+                write!(f, "invalid array size: {}", size),
+            Self::InvalidArray(value) =>
+                write!(f, "not an array: {}", value),
+            Self::InvalidFunction(value) =>
+                write!(f, "not a function: {}", value),
+            Self::InvalidField(field) =>
+                write!(f, "not a struct with the field: {}", field),
+            Self::OutOfMemory(size) =>
+                write!(f, "out of memory, can't allocate an object with {} elements", size),
+            Self::InvalidString(value) =>
+                write!(f, "not a string: {}", value),
+        }
+    }
+}
+
+type InterpreterResult<T> = Result<T, InterpreterError>;
+
+struct CompiledFunction {
+    parameters: Vec<String>,
+    instructions: Vec<IrInstruction>,
+    labels: HashMap<String, usize>
+}
+
+pub struct Interpreter {
+    program: ssa::SsaProgram,
+    functions: HashMap<String, CompiledFunction>,
+    globals: HashMap<String, i64>,
+    arrays: Vec<Vec<i64>>, //arrays are referenced by their index + 1 (0 is never a valid array)
+    structs: Vec<(usize, Vec<i64>)>, //struct and fields, structs are referenced by -(index + 1)
+    heap_size: i64, //same limit as the jit (one word for the header of every object)
+    strings: StringTable,
+    print_ir: bool,
+    optimize: bool
+}
+
+fn get_value(data: &Data, variables: &HashMap<String, i64>) -> InterpreterResult<i64> {
+    match data {
+        Data::Number(n) => Ok(n.to_owned()),
+        Data::Float(f) => Ok(f.to_bits() as i64),
+        Data::Variable(v) => match variables.get(v) {
+            Some(value) => Ok(value.to_owned()),
+            None => Err(InterpreterError::VariableNotDefined(v.to_owned()))
+        }
+    }
+}
+
+fn get_float(data: &Data, variables: &HashMap<String, i64>) -> InterpreterResult<f64> {
+    get_value(data, variables).map(|bits| f64::from_bits(bits as u64))
+}
+
+fn call_external(function: &ExternalFunction, args: &[i64]) -> InterpreterResult<i64> {
+    if function.uses_floats() {
+        return function.call_with_floats(args).ok_or_else(|| InterpreterError::TooManyArguments(function.name.to_owned()));
+    }
+    unsafe {
+        let address = function.address as usize;
+        let res = match args.len() {
+            0 => mem::transmute::<usize, extern "C" fn() -> i64>(address)(),
+            1 => mem::transmute::<usize, extern "C" fn(i64) -> i64>(address)(args[0]),
+            2 => mem::transmute::<usize, extern "C" fn(i64, i64) -> i64>(address)(args[0], args[1]),
+            3 => mem::transmute::<usize, extern "C" fn(i64, i64, i64) -> i64>(address)(args[0], args[1], args[2]),
+            4 => mem::transmute::<usize, extern "C" fn(i64, i64, i64, i64) -> i64>(address)(args[0], args[1], args[2], args[3]),
+            5 => mem::transmute::<usize, extern "C" fn(i64, i64, i64, i64, i64) -> i64>(address)(args[0], args[1], args[2], args[3], args[4]),
+            6 => mem::transmute::<usize, extern "C" fn(i64, i64, i64, i64, i64, i64) -> i64>(address)(args[0], args[1], args[2], args[3], args[4], args[5]),
+            _ => return Err(InterpreterError::TooManyArguments(function.name.to_owned()))
+        };
+        match predefined_functions::take_invalid_string() {
+            Some(value) => Err(InterpreterError::InvalidString(value)),
+            None => Ok(res)
+        }
+    }
+}
+
+fn to_bool(value: i64) -> bool {
+    value != 0
+}
+
+impl Interpreter {
+    pub fn new(program: ssa::SsaProgram, print_ir: bool, optimize: bool) -> Self {
+        let globals = program.globals.iter().map(|g| (g.to_owned(), 0)).collect();
+        let strings = StringTable::new(&program.strings);
+        Interpreter {
+            program,
+            functions: HashMap::new(),
+            globals,
+            arrays: vec![],
+            structs: vec![],
+            heap_size: 0,
+            strings,
+            print_ir,
+            optimize
+        }
+    }
+
+    fn load_function(&mut self, name: &String) -> InterpreterResult<()> {
+        if self.functions.contains_key(name) {
+            return Ok(());
+        }
+        let fun = match self.program.functions.iter().find(|f| &f.name == name) {
+            Some(f) => f,
+            None => return Err(InterpreterError::FunctionDoesNotExist(name.to_owned()))
+        };
+        debug!("Transforming function: {}", name);
+        let mut instructions = ir::transform(fun);
+        if self.optimize {
+            instructions = optimizer::optimize(instructions, &fun.parameters);
+        }
+
+        if self.print_ir {
+            println!("\n\n##### IR Output Start [Function: {}] #####", name);
+            println!("{:#?}", instructions);
+            println!("##### IR Output End [Function: {}] #####", name);
+        }
+
+        let mut labels = HashMap::new();
+        for (line, inst) in instructions.iter().enumerate() {
+            if let IrInstruction::Label(l) = inst {
+                labels.insert(l.to_owned(), line);
+            }
+        }
+        self.functions.insert(name.to_owned(), CompiledFunction { parameters: fun.parameters.clone(), instructions, labels });
+        Ok(())
+    }
+
+    pub fn execute_main(&mut self, args: Vec<i64>) -> InterpreterResult<i64> {
+        if self.program.functions.iter().any(|f| f.name == ssa::GLOBALS_INIT_FUNCTION) {
+            self.call(&ssa::GLOBALS_INIT_FUNCTION.to_owned(), vec![])?;
+        }
+        self.call(&"main".to_owned(), args)
+    }
+
+    pub fn call(&mut self, name: &String, args: Vec<i64>) -> InterpreterResult<i64> {
+        if let Some(ext) = self.program.external_functions.iter().find(|f| &f.name == name) {
+            if ext.parameters.len() != args.len() {
+                return Err(InterpreterError::InvalidNumberOfArguments(name.to_owned(), ext.parameters.len() as u64, args.len() as u64));
+            }
+            return call_external(ext, &args);
+        }
+
+        self.load_function(name)?;
+        let function = self.functions.get(name).unwrap();
+        if function.parameters.len() != args.len() {
+            return Err(InterpreterError::InvalidNumberOfArguments(name.to_owned(), function.parameters.len() as u64, args.len() as u64));
+        }
+
+        let mut variables: HashMap<String, i64> = HashMap::new();
+        for (parameter, value) in function.parameters.iter().zip(args) {
+            variables.insert(parameter.to_owned(), value);
+        }
+
+        let mut pc = 0;
+        loop {
+            let function = self.functions.get(name).unwrap();
+            let inst = match function.instructions.get(pc) {
+                //functions without return statement return 0
+                None => return Ok(0),
+                Some(i) => i.clone()
+            };
+            pc += 1;
+
+            match inst {
+                IrInstruction::Jump(label) => {
+                    pc = self.get_label(name, &label)?;
+                },
+                IrInstruction::JumpFalse(data, label) => {
+                    if !to_bool(get_value(&data, &variables)?) {
+                        pc = self.get_label(name, &label)?;
+                    }
+                },
+                IrInstruction::Label(_) => (),
+                IrInstruction::FunctionCall(res_var, fun_name, args) => {
+                    let mut arg_values = vec![];
+                    for arg in &args {
+                        arg_values.push(get_value(arg, &variables)?);
+                    }
+                    let res = self.call(&fun_name, arg_values)?;
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::IndirectCall(res_var, function, args) => {
+                    let fun_name = self.get_function_name(get_value(&function, &variables)?)?;
+                    let mut arg_values = vec![];
+                    for arg in &args {
+                        arg_values.push(get_value(arg, &variables)?);
+                    }
+                    let res = self.call(&fun_name, arg_values)?;
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::LoadFunction(res_var, fun_name) => {
+                    let res = self.get_function_reference(&fun_name);
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::Addition(res_var, d1, d2) => {
+                    let res = get_value(&d1, &variables)?.wrapping_add(get_value(&d2, &variables)?);
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::Subtraction(res_var, d1, d2) => {
+                    let res = get_value(&d1, &variables)?.wrapping_sub(get_value(&d2, &variables)?);
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::Multiplication(res_var, d1, d2) => {
+                    let res = get_value(&d1, &variables)?.wrapping_mul(get_value(&d2, &variables)?);
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::Division(res_var, d1, d2) => {
+                    let dividend = get_value(&d1, &variables)?;
+                    let divisor = get_value(&d2, &variables)?;
+                    if divisor == 0 {
+                        return Err(InterpreterError::DivisionByZero);
+                    }
+                    //idiv traps on this overflow, the jit reports it as an error
+                    if dividend == i64::MIN && divisor == -1 {
+                        return Err(InterpreterError::DivisionOverflow);
+                    }
+                    let res = dividend / divisor;
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::Modulo(res_var, d1, d2) => {
+                    let dividend = get_value(&d1, &variables)?;
+                    let divisor = get_value(&d2, &variables)?;
+                    if divisor == 0 {
+                        return Err(InterpreterError::DivisionByZero);
+                    }
+                    //idiv traps on this overflow, the jit reports it as an error
+                    if dividend == i64::MIN && divisor == -1 {
+                        return Err(InterpreterError::DivisionOverflow);
+                    }
+                    let res = dividend % divisor;
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::Greater(res_var, d1, d2) => {
+                    let res = get_value(&d1, &variables)? > get_value(&d2, &variables)?;
+                    variables.insert(res_var, res as i64);
+                },
+                IrInstruction::GreaterEquals(res_var, d1, d2) => {
+                    let res = get_value(&d1, &variables)? >= get_value(&d2, &variables)?;
+                    variables.insert(res_var, res as i64);
+                },
+                IrInstruction::Less(res_var, d1, d2) => {
+                    let res = get_value(&d1, &variables)? < get_value(&d2, &variables)?;
+                    variables.insert(res_var, res as i64);
+                },
+                IrInstruction::LessEquals(res_var, d1, d2) => {
+                    let res = get_value(&d1, &variables)? <= get_value(&d2, &variables)?;
+                    variables.insert(res_var, res as i64);
+                },
+                IrInstruction::Equals(res_var, d1, d2) => {
+                    let res = get_value(&d1, &variables)? == get_value(&d2, &variables)?;
+                    variables.insert(res_var, res as i64);
+                },
+                IrInstruction::NotEquals(res_var, d1, d2) => {
+                    let res = get_value(&d1, &variables)? != get_value(&d2, &variables)?;
+                    variables.insert(res_var, res as i64);
+                },
+                IrInstruction::BitwiseAnd(res_var, d1, d2) => {
+                    let (a, b) = (get_value(&d1, &variables)?, get_value(&d2, &variables)?);
+                    variables.insert(res_var, a & b);
+                },
+                IrInstruction::BitwiseOr(res_var, d1, d2) => {
+                    let (a, b) = (get_value(&d1, &variables)?, get_value(&d2, &variables)?);
+                    variables.insert(res_var, a | b);
+                },
+                IrInstruction::BitwiseXor(res_var, d1, d2) => {
+                    let (a, b) = (get_value(&d1, &variables)?, get_value(&d2, &variables)?);
+                    variables.insert(res_var, a ^ b);
+                },
+                IrInstruction::ShiftLeft(res_var, d1, d2) => {
+                    let (a, b) = (get_value(&d1, &variables)?, get_value(&d2, &variables)?);
+                    variables.insert(res_var, a.wrapping_shl(b as u32));
+                },
+                IrInstruction::ShiftRight(res_var, d1, d2) => {
+                    let (a, b) = (get_value(&d1, &variables)?, get_value(&d2, &variables)?);
+                    variables.insert(res_var, a.wrapping_shr(b as u32));
+                },
+                IrInstruction::ShiftRightLogical(res_var, d1, d2) => {
+                    let (a, b) = (get_value(&d1, &variables)?, get_value(&d2, &variables)?);
+                    variables.insert(res_var, (a as u64).wrapping_shr(b as u32) as i64);
+                },
+                IrInstruction::Negation(res_var, data) => {
+                    let res = get_value(&data, &variables)?.wrapping_neg();
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::LogicNot(res_var, data) => {
+                    let res = !to_bool(get_value(&data, &variables)?);
+                    variables.insert(res_var, res as i64);
+                },
+                IrInstruction::BitwiseNot(res_var, data) => {
+                    let res = !get_value(&data, &variables)?;
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::FloatAddition(res_var, d1, d2) => {
+                    let (a, b) = (get_float(&d1, &variables)?, get_float(&d2, &variables)?);
+                    variables.insert(res_var, (a + b).to_bits() as i64);
+                },
+                IrInstruction::FloatSubtraction(res_var, d1, d2) => {
+                    let (a, b) = (get_float(&d1, &variables)?, get_float(&d2, &variables)?);
+                    variables.insert(res_var, (a - b).to_bits() as i64);
+                },
+                IrInstruction::FloatMultiplication(res_var, d1, d2) => {
+                    let (a, b) = (get_float(&d1, &variables)?, get_float(&d2, &variables)?);
+                    variables.insert(res_var, (a * b).to_bits() as i64);
+                },
+                IrInstruction::FloatDivision(res_var, d1, d2) => {
+                    let (a, b) = (get_float(&d1, &variables)?, get_float(&d2, &variables)?);
+                    variables.insert(res_var, (a / b).to_bits() as i64);
+                },
+                IrInstruction::FloatGreater(res_var, d1, d2) => {
+                    let (a, b) = (get_float(&d1, &variables)?, get_float(&d2, &variables)?);
+                    variables.insert(res_var, (a > b) as i64);
+                },
+                IrInstruction::FloatGreaterEquals(res_var, d1, d2) => {
+                    let (a, b) = (get_float(&d1, &variables)?, get_float(&d2, &variables)?);
+                    variables.insert(res_var, (a >= b) as i64);
+                },
+                IrInstruction::FloatLess(res_var, d1, d2) => {
+                    let (a, b) = (get_float(&d1, &variables)?, get_float(&d2, &variables)?);
+                    variables.insert(res_var, (a < b) as i64);
+                },
+                IrInstruction::FloatLessEquals(res_var, d1, d2) => {
+                    let (a, b) = (get_float(&d1, &variables)?, get_float(&d2, &variables)?);
+                    variables.insert(res_var, (a <= b) as i64);
+                },
+                IrInstruction::FloatEquals(res_var, d1, d2) => {
+                    let (a, b) = (get_float(&d1, &variables)?, get_float(&d2, &variables)?);
+                    variables.insert(res_var, (a == b) as i64);
+                },
+                IrInstruction::FloatNotEquals(res_var, d1, d2) => {
+                    let (a, b) = (get_float(&d1, &variables)?, get_float(&d2, &variables)?);
+                    variables.insert(res_var, (a != b) as i64);
+                },
+                IrInstruction::FloatNegation(res_var, data) => {
+                    let res = -get_float(&data, &variables)?;
+                    variables.insert(res_var, res.to_bits() as i64);
+                },
+                IrInstruction::IntToFloat(res_var, data) => {
+                    let res = get_value(&data, &variables)? as f64;
+                    variables.insert(res_var, res.to_bits() as i64);
+                },
+                IrInstruction::FloatToInt(res_var, data) => {
+                    let res = float_to_int(get_float(&data, &variables)?);
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::Assignment(res_var, data) => {
+                    let res = get_value(&data, &variables)?;
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::LoadGlobal(res_var, name) => {
+                    let res = self.globals[&name];
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::StoreGlobal(name, data) => {
+                    let res = get_value(&data, &variables)?;
+                    self.globals.insert(name, res);
+                },
+                IrInstruction::LoadString(res_var, s) => {
+                    let res = self.strings.get_address(&s).expect("strings are collected by the ssa conversion") as i64;
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::NewArray(res_var, data) => {
+                    let res = self.new_array(get_value(&data, &variables)?)?;
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::ArrayLength(res_var, data) => {
+                    let res = self.get_array(get_value(&data, &variables)?)?.len() as i64;
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::ArrayLoad(res_var, array, index) => {
+                    let (array, index) = (get_value(&array, &variables)?, get_value(&index, &variables)?);
+                    let res = *self.get_element(array, index)?;
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::ArrayStore(array, index, data) => {
+                    let (array, index, value) = (get_value(&array, &variables)?, get_value(&index, &variables)?, get_value(&data, &variables)?);
+                    *self.get_element(array, index)? = value;
+                },
+                IrInstruction::NewStruct(res_var, name, fields) => {
+                    let mut values = vec![];
+                    for (field, data) in &fields {
+                        values.push((field, get_value(data, &variables)?));
+                    }
+                    let res = self.new_struct(&name, values)?;
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::FieldLoad(res_var, object, field) => {
+                    let res = *self.get_field(get_value(&object, &variables)?, &field)?;
+                    variables.insert(res_var, res);
+                },
+                IrInstruction::FieldStore(object, field, data) => {
+                    let (object, value) = (get_value(&object, &variables)?, get_value(&data, &variables)?);
+                    *self.get_field(object, &field)? = value;
+                },
+                IrInstruction::Return(data) => {
+                    return get_value(&data, &variables);
+                },
+                IrInstruction::KeepAlive(_) => ()
+            }
+        }
+    }
+
+    fn allocate(&mut self, num_elements: i64) -> InterpreterResult<()> {
+        if self.heap_size + num_elements + 1 > MAX_HEAP_SIZE {
+            return Err(InterpreterError::OutOfMemory(num_elements));
+        }
+        self.heap_size += num_elements + 1;
+        Ok(())
+    }
+
+    fn new_array(&mut self, size: i64) -> InterpreterResult<i64> {
+        if !(0..=MAX_ARRAY_SIZE).contains(&size) {
+            return Err(InterpreterError::InvalidArraySize(size));
+        }
+        self.allocate(size)?;
+        self.arrays.push(vec![0; size as usize]);
+        Ok(self.arrays.len() as i64)
+    }
+
+    fn get_array(&mut self, array: i64) -> InterpreterResult<&mut Vec<i64>> {
+        match usize::try_from(array.wrapping_sub(1)).ok().and_then(|idx| self.arrays.get_mut(idx)) {
+            Some(a) => Ok(a),
+            None => Err(InterpreterError::InvalidArray(array))
+        }
+    }
+
+    fn get_element(&mut self, array: i64, index: i64) -> InterpreterResult<&mut i64> {
+        let array = self.get_array(array)?;
+        let length = array.len() as i64;
+        match usize::try_from(index).ok().and_then(|idx| array.get_mut(idx)) {
+            Some(element) => Ok(element),
+            None => Err(InterpreterError::IndexOutOfBounds(index, length))
+        }
+    }
+
+    //the fields are stored in the declaration order
+    fn new_struct(&mut self, name: &String, values: Vec<(&String, i64)>) -> InterpreterResult<i64> {
+        let id = self.program.structs.iter().position(|s| &s.name == name).expect("structs are checked by the semantic analysis");
+        self.allocate(self.program.structs[id].fields.len() as i64)?;
+        let mut fields = vec![0; self.program.structs[id].fields.len()];
+        for (field, value) in values {
+            let idx = self.program.structs[id].fields.iter().position(|f| f == field).expect("fields are checked by the semantic analysis");
+            fields[idx] = value;
+        }
+        self.structs.push((id, fields));
+        Ok(-(self.structs.len() as i64))
+    }
+
+    fn get_field(&mut self, object: i64, field: &String) -> InterpreterResult<&mut i64> {
+        let element = usize::try_from(-(object as i128) - 1).ok().and_then(|idx| self.structs.get_mut(idx));
+        if let Some((id, fields)) = element {
+            if let Some(idx) = self.program.structs[*id].fields.iter().position(|f| f == field) {
+                return Ok(&mut fields[idx]);
+            }
+        }
+        Err(InterpreterError::InvalidField(field.to_owned()))
+    }
+
+    //functions are referenced by their index + 1 (internal functions first, like the function table of the jit)
+    fn get_function_reference(&self, name: &String) -> i64 {
+        let mut names = self.program.functions.iter().map(|f| &f.name).chain(self.program.external_functions.iter().map(|f| &f.name));
+        names.position(|n| n == name).expect("function references are checked by the semantic analysis") as i64 + 1
+    }
+
+    fn get_function_name(&self, function: i64) -> InterpreterResult<String> {
+        let mut names = self.program.functions.iter().map(|f| &f.name).chain(self.program.external_functions.iter().map(|f| &f.name));
+        match usize::try_from(function.wrapping_sub(1)).ok().and_then(|idx| names.nth(idx)) {
+            Some(name) => Ok(name.to_owned()),
+            None => Err(InterpreterError::InvalidFunction(function))
+        }
+    }
+
+    fn get_label(&self, function: &String, label: &String) -> InterpreterResult<usize> {
+        match self.functions.get(function).unwrap().labels.get(label) {
+            Some(line) => Ok(line.to_owned()),
+            None => Err(InterpreterError::LabelDoesNotExist(label.to_owned()))
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer, parser, predefined_functions};
+
+    fn interpret(code: &str, args: Vec<i64>) -> InterpreterResult<i64> {
+        let mut program = parser::parse(&mut lexer::lex(code)).unwrap();
+        predefined_functions::add(&mut program);
+        let mut interpreter = Interpreter::new(ssa::convert(&program), false, false);
+        interpreter.execute_main(args)
+    }
+
+    #[test]
+    fn interpret_arithmetic() {
+        let code = "
+            fun main(a, b) {
+                return (a + b) * 3 - b / 2 + a % 3;
+            }
+        ";
+        assert_eq!(interpret(code, vec![4, 6]), Ok(28));
+    }
+
+    #[test]
+    fn interpret_while_loop() {
+        let code = "
+            fun main() {
+                let a = 0;
+                let i = 0;
+                while(i < 10) {
+                    i = i + 1;
+                    a = i * 3;
+                }
+                return a;
+            }
+        ";
+        assert_eq!(interpret(code, vec![]), Ok(30));
+    }
+
+    #[test]
+    fn interpret_recursion() {
+        let code = "
+            fun fac(a) {
+                if(a == 0) {
+                    return 1;
+                }
+                return a * fac(a - 1);
+            }
+
+            fun main() {
+                return fac(10);
+            }
+        ";
+        assert_eq!(interpret(code, vec![]), Ok(3628800));
+    }
+
+    #[test]
+    fn interpret_division_by_zero() {
+        let code = "
+            fun main(a) {
+                return 1 / a;
+            }
+        ";
+        assert_eq!(interpret(code, vec![0]), Err(InterpreterError::DivisionByZero));
+    }
+
+    #[test]
+    fn interpret_arrays() {
+        let code = "
+            fun main(n) {
+                let a = new_array(n);
+                for(let i = 0; i < len(a); i = i + 1) {
+                    a[i] = i * i;
+                }
+                return a[n - 1] + a[1];
+            }
+        ";
+        assert_eq!(interpret(code, vec![5]), Ok(17));
+    }
+
+    #[test]
+    fn interpret_strings() {
+        let code = "
+            fun main(i) {
+                let s = \"abc\";
+                let t = \"abc\";
+                return (s == t) * 1000 + str_len(s) * 100 + str_char_at(s, i);
+            }
+        ";
+        assert_eq!(interpret(code, vec![1]), Ok(1000 + 300 + 'b' as i64));
+        assert_eq!(interpret(code, vec![3]), Ok(1300 - 1));
+    }
+
+    #[test]
+    fn interpret_invalid_strings() {
+        let code = "
+            fun main(s) {
+                return str_len(s);
+            }
+        ";
+        assert_eq!(interpret(code, vec![12345]), Err(InterpreterError::InvalidString(12345)));
+        assert_eq!(interpret("fun main() { return str_len(\"abc\"); }", vec![]), Ok(3));
+    }
+
+    #[test]
+    fn interpret_index_out_of_bounds() {
+        let code = "
+            fun main(i) {
+                let a = new_array(3);
+                return a[i];
+            }
+        ";
+        assert_eq!(interpret(code, vec![3]), Err(InterpreterError::IndexOutOfBounds(3, 3)));
+        assert_eq!(interpret(code, vec![-1]), Err(InterpreterError::IndexOutOfBounds(-1, 3)));
+    }
+
+    #[test]
+    fn interpret_heap_limit() {
+        let code = "
+            fun main() {
+                while (1) {
+                    let a = new_array(1 << 20);
+                }
+                return 0;
+            }
+        ";
+        assert_eq!(interpret(code, vec![]), Err(InterpreterError::OutOfMemory(1 << 20)));
+    }
+
+    #[test]
+    fn interpret_function_references() {
+        let code = "
+            fun add(a, b) {
+                return a + b;
+            }
+
+            fun main(f) {
+                let g = &add;
+                if(f == 0) {
+                    f = g;
+                }
+                return f(1, 2);
+            }
+        ";
+        assert_eq!(interpret(code, vec![0]), Ok(3));
+        assert_eq!(interpret(code, vec![-1]), Err(InterpreterError::InvalidFunction(-1)));
+        assert_eq!(interpret(code, vec![2]), Err(InterpreterError::InvalidNumberOfArguments("main".to_owned(), 1, 2)));
+    }
+
+    #[test]
+    fn interpret_structs() {
+        let code = "
+            struct Point { x, y }
+            struct Line { from, to }
+
+            fun length(l) {
+                return l.to.x - l.from.x + l.to.y - l.from.y;
+            }
+
+            fun main(a) {
+                let l = Line { to: Point { y: 5, x: 4 }, from: Point { x: 1, y: a } };
+                l.from.y = l.from.y + 1;
+                if(a == 0) {
+                    return l.x;
+                }
+                return length(l);
+            }
+        ";
+        assert_eq!(interpret(code, vec![2]), Ok(3 + 2));
+        assert_eq!(interpret(code, vec![0]), Err(InterpreterError::InvalidField("x".to_owned())));
+    }
+
+    #[test]
+    fn interpret_no_return() {
+        let code = "
+            fun main() {
+                let a = 1;
+            }
+        ";
+        assert_eq!(interpret(code, vec![]), Ok(0));
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::parser::{self, Expression, VariableName};
 use crate::ssa;
+use itertools::Itertools;
 pub type Label = String;
 pub type Function = String;
 pub type ResultVariable = String;
@@ -22,12 +23,12 @@ impl NameFactory {
 
     pub fn get_label(&mut self) -> String {
         self.count_label += 1;
-        return format!("#label_{}", self.count_label)
+        format!("#label_{}", self.count_label)
     }
 
     pub fn get_variable(&mut self) -> String {
         self.count_var += 1;
-        return format!("#var_{}", self.count_var)
+        format!("#var_{}", self.count_var)
     }
 }
 
@@ -35,6 +36,7 @@ impl NameFactory {
 pub enum Data {
     Variable(String),
     Number(i64),
+    Float(f64), //used as its bits by the instructions that don't work on floats
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -43,6 +45,8 @@ pub enum IrInstruction {
     JumpFalse(Data, Label),
     Label(String),
     FunctionCall(ResultVariable, Function, Arguments),
+    IndirectCall(ResultVariable, Data, Arguments), //function reference, arguments
+    LoadFunction(ResultVariable, Function), //reference to the function
     Addition(ResultVariable, Data, Data),
     Subtraction(ResultVariable, Data, Data),
     Multiplication(ResultVariable, Data, Data),
@@ -54,14 +58,45 @@ pub enum IrInstruction {
     LessEquals(ResultVariable, Data, Data),
     Equals(ResultVariable, Data, Data),
     NotEquals(ResultVariable, Data, Data),
-    LogicAnd(ResultVariable, Data, Data),
-    LogicOr(ResultVariable, Data, Data),
+    BitwiseAnd(ResultVariable, Data, Data),
+    BitwiseOr(ResultVariable, Data, Data),
+    BitwiseXor(ResultVariable, Data, Data),
+    ShiftLeft(ResultVariable, Data, Data),
+    ShiftRight(ResultVariable, Data, Data),
+    ShiftRightLogical(ResultVariable, Data, Data),
+    Negation(ResultVariable, Data),
+    LogicNot(ResultVariable, Data),
+    BitwiseNot(ResultVariable, Data),
+    //the float instructions read and write the bits of floats
+    FloatAddition(ResultVariable, Data, Data),
+    FloatSubtraction(ResultVariable, Data, Data),
+    FloatMultiplication(ResultVariable, Data, Data),
+    FloatDivision(ResultVariable, Data, Data),
+    FloatGreater(ResultVariable, Data, Data),
+    FloatGreaterEquals(ResultVariable, Data, Data),
+    FloatLess(ResultVariable, Data, Data),
+    FloatLessEquals(ResultVariable, Data, Data),
+    FloatEquals(ResultVariable, Data, Data),
+    FloatNotEquals(ResultVariable, Data, Data),
+    FloatNegation(ResultVariable, Data),
+    IntToFloat(ResultVariable, Data),
+    FloatToInt(ResultVariable, Data),
     Assignment(ResultVariable, Data),
+    LoadGlobal(ResultVariable, VariableName),
+    StoreGlobal(VariableName, Data),
+    LoadString(ResultVariable, String), //address of the string literal
+    NewArray(ResultVariable, Data), //size
+    ArrayLength(ResultVariable, Data),
+    ArrayLoad(ResultVariable, Data, Data), //array, index
+    ArrayStore(Data, Data, Data), //array, index, value
+    NewStruct(ResultVariable, String, Vec<(String, Data)>), //struct, values of the fields in the written order
+    FieldLoad(ResultVariable, Data, String), //object, field
+    FieldStore(Data, String, Data), //object, field, value
     Return(Data),
     KeepAlive(VariableName)
 }
 
-fn handle_binary_expression(b: &Box<(Expression, Expression)> , name_factory: &mut NameFactory) -> (Data, Data, Vec<IrInstruction>) {
+fn handle_binary_expression(b: &(Expression, Expression) , name_factory: &mut NameFactory) -> (Data, Data, Vec<IrInstruction>) {
     let mut instructions = vec![];
     let (left_res, mut left_inst) = transform_expression(&b.0, name_factory);
     let (right_res, mut right_inst) = transform_expression(&b.1, name_factory);
@@ -69,11 +104,53 @@ fn handle_binary_expression(b: &Box<(Expression, Expression)> , name_factory: &m
     instructions.append(&mut right_inst);
     (left_res, right_res, instructions)
 }
+//short circuit: the right side is only evaluated if the left side is true
+//the result is assigned in both branches (like the result of a phi node)
+fn transform_logic_and(b: &(Expression, Expression), result: &ResultVariable, name_factory: &mut NameFactory) -> (Data, Vec<IrInstruction>) {
+    let (left_res, mut instructions) = transform_expression(&b.0, name_factory);
+    let false_label = name_factory.get_label();
+    let end_label = name_factory.get_label();
+    instructions.push(IrInstruction::JumpFalse(left_res, false_label.to_owned()));
+    let (right_res, mut right_inst) = transform_expression(&b.1, name_factory);
+    instructions.append(&mut right_inst);
+    instructions.push(IrInstruction::NotEquals(result.to_owned(), right_res, Data::Number(0)));
+    instructions.push(IrInstruction::Jump(end_label.to_owned()));
+    instructions.push(IrInstruction::Label(false_label));
+    instructions.push(IrInstruction::Assignment(result.to_owned(), Data::Number(0)));
+    instructions.push(IrInstruction::Label(end_label));
+    (Data::Variable(result.to_owned()), instructions)
+}
+
+//short circuit: the right side is only evaluated if the left side is false
+fn transform_logic_or(b: &(Expression, Expression), result: &ResultVariable, name_factory: &mut NameFactory) -> (Data, Vec<IrInstruction>) {
+    let (left_res, mut instructions) = transform_expression(&b.0, name_factory);
+    let right_label = name_factory.get_label();
+    let end_label = name_factory.get_label();
+    instructions.push(IrInstruction::JumpFalse(left_res, right_label.to_owned()));
+    instructions.push(IrInstruction::Assignment(result.to_owned(), Data::Number(1)));
+    instructions.push(IrInstruction::Jump(end_label.to_owned()));
+    instructions.push(IrInstruction::Label(right_label));
+    let (right_res, mut right_inst) = transform_expression(&b.1, name_factory);
+    instructions.append(&mut right_inst);
+    instructions.push(IrInstruction::NotEquals(result.to_owned(), right_res, Data::Number(0)));
+    instructions.push(IrInstruction::Label(end_label));
+    (Data::Variable(result.to_owned()), instructions)
+}
+
+fn handle_unary_expression(e: &Expression, result: &ResultVariable, name_factory: &mut NameFactory, f: fn(ResultVariable, Data) -> IrInstruction) -> (Data, Vec<IrInstruction>) {
+    let (res, mut instructions) = transform_expression(e, name_factory);
+    instructions.push(f(result.to_owned(), res));
+    (Data::Variable(result.to_owned()), instructions)
+}
+
 fn transform_expression(expression: &parser::Expression, name_factory: &mut NameFactory) -> (Data, Vec<IrInstruction>) {
     let result = &name_factory.get_variable();
     match expression {
         parser::Expression::Number(n) => (Data::Number(n.to_owned()), vec![]),
+        parser::Expression::Float(f) => (Data::Float(f.to_owned()), vec![]),
         parser::Expression::Variable(v) => (Data::Variable(v.to_owned()), vec![]),
+        parser::Expression::Global(v) => (Data::Variable(result.to_owned()), vec![IrInstruction::LoadGlobal(result.to_owned(), v.to_owned())]),
+        parser::Expression::String(s) => (Data::Variable(result.to_owned()), vec![IrInstruction::LoadString(result.to_owned(), s.to_owned())]),
         parser::Expression::Addition(b) => {
             let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
             instructions.push(IrInstruction::Addition(result.to_owned(), left_res, right_res));
@@ -129,14 +206,46 @@ fn transform_expression(expression: &parser::Expression, name_factory: &mut Name
             instructions.push(IrInstruction::NotEquals(result.to_owned(), left_res, right_res));
             (Data::Variable(result.to_owned()), instructions)
         },
-        parser::Expression::LogicAnd(b) => {
+        parser::Expression::LogicAnd(b) => transform_logic_and(b, result, name_factory),
+        parser::Expression::LogicOr(b) => transform_logic_or(b, result, name_factory),
+        parser::Expression::BitwiseAnd(b) => {
             let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
-            instructions.push(IrInstruction::LogicAnd(result.to_owned(), left_res, right_res));
+            instructions.push(IrInstruction::BitwiseAnd(result.to_owned(), left_res, right_res));
             (Data::Variable(result.to_owned()), instructions)
         },
-        parser::Expression::LogicOr(b) => {
+        parser::Expression::BitwiseOr(b) => {
             let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
-            instructions.push(IrInstruction::LogicOr(result.to_owned(), left_res, right_res));
+            instructions.push(IrInstruction::BitwiseOr(result.to_owned(), left_res, right_res));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::BitwiseXor(b) => {
+            let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
+            instructions.push(IrInstruction::BitwiseXor(result.to_owned(), left_res, right_res));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::ShiftLeft(b) => {
+            let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
+            instructions.push(IrInstruction::ShiftLeft(result.to_owned(), left_res, right_res));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::ShiftRight(b) => {
+            let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
+            instructions.push(IrInstruction::ShiftRight(result.to_owned(), left_res, right_res));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::ShiftRightLogical(b) => {
+            let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
+            instructions.push(IrInstruction::ShiftRightLogical(result.to_owned(), left_res, right_res));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::Negation(e) => handle_unary_expression(e, result, name_factory, IrInstruction::Negation),
+        parser::Expression::LogicNot(e) => handle_unary_expression(e, result, name_factory, IrInstruction::LogicNot),
+        parser::Expression::BitwiseNot(e) => handle_unary_expression(e, result, name_factory, IrInstruction::BitwiseNot),
+        parser::Expression::NewArray(e) => handle_unary_expression(e, result, name_factory, IrInstruction::NewArray),
+        parser::Expression::ArrayLength(e) => handle_unary_expression(e, result, name_factory, IrInstruction::ArrayLength),
+        parser::Expression::ArrayAccess(b) => {
+            let (array_res, index_res, mut instructions) = handle_binary_expression(b, name_factory);
+            instructions.push(IrInstruction::ArrayLoad(result.to_owned(), array_res, index_res));
             (Data::Variable(result.to_owned()), instructions)
         },
         parser::Expression::FunctionCall(f) => {
@@ -150,24 +259,117 @@ fn transform_expression(expression: &parser::Expression, name_factory: &mut Name
             }
             instructions.push(IrInstruction::FunctionCall(result.to_owned(), f.name.to_owned(), arguments));
             (Data::Variable(result.to_owned()), instructions)
-        }
+        },
+        parser::Expression::FunctionReference(f) => (Data::Variable(result.to_owned()), vec![IrInstruction::LoadFunction(result.to_owned(), f.to_owned())]),
+        parser::Expression::IndirectCall(c) => transform_indirect_call(c, result, name_factory),
+        parser::Expression::Cast(e, _) => transform_expression(e, name_factory),
+        parser::Expression::FloatAddition(b) => {
+            let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
+            instructions.push(IrInstruction::FloatAddition(result.to_owned(), left_res, right_res));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::FloatSubtraction(b) => {
+            let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
+            instructions.push(IrInstruction::FloatSubtraction(result.to_owned(), left_res, right_res));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::FloatMultiplication(b) => {
+            let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
+            instructions.push(IrInstruction::FloatMultiplication(result.to_owned(), left_res, right_res));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::FloatDivision(b) => {
+            let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
+            instructions.push(IrInstruction::FloatDivision(result.to_owned(), left_res, right_res));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::FloatGreater(b) => {
+            let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
+            instructions.push(IrInstruction::FloatGreater(result.to_owned(), left_res, right_res));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::FloatGreaterEquals(b) => {
+            let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
+            instructions.push(IrInstruction::FloatGreaterEquals(result.to_owned(), left_res, right_res));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::FloatLess(b) => {
+            let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
+            instructions.push(IrInstruction::FloatLess(result.to_owned(), left_res, right_res));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::FloatLessEquals(b) => {
+            let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
+            instructions.push(IrInstruction::FloatLessEquals(result.to_owned(), left_res, right_res));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::FloatEquals(b) => {
+            let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
+            instructions.push(IrInstruction::FloatEquals(result.to_owned(), left_res, right_res));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::FloatNotEquals(b) => {
+            let (left_res, right_res, mut instructions) = handle_binary_expression(b, name_factory);
+            instructions.push(IrInstruction::FloatNotEquals(result.to_owned(), left_res, right_res));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::FloatNegation(e) => handle_unary_expression(e, result, name_factory, IrInstruction::FloatNegation),
+        parser::Expression::IntToFloat(e) => handle_unary_expression(e, result, name_factory, IrInstruction::IntToFloat),
+        parser::Expression::FloatToInt(e) => handle_unary_expression(e, result, name_factory, IrInstruction::FloatToInt),
+        parser::Expression::NewStruct(n) => {
+            let mut instructions = vec![];
+            let mut fields = vec![];
+            for (field, e) in &n.fields {
+                let (res, mut inst) = transform_expression(e, name_factory);
+                instructions.append(&mut inst);
+                fields.push((field.to_owned(), res));
+            }
+            instructions.push(IrInstruction::NewStruct(result.to_owned(), n.name.to_owned(), fields));
+            (Data::Variable(result.to_owned()), instructions)
+        },
+        parser::Expression::FieldAccess(e, field) => {
+            let (object, mut instructions) = transform_expression(e, name_factory);
+            instructions.push(IrInstruction::FieldLoad(result.to_owned(), object, field.to_owned()));
+            (Data::Variable(result.to_owned()), instructions)
+        },
     }
 }
 
-fn transform_if_statement(if_statement: &ssa::SsaIfStatement, phi_nodes: &ssa::PhiNodes, name_factory: &mut NameFactory) -> Vec<IrInstruction> {
+fn transform_indirect_call(indirect_call: &parser::IndirectCall, result: &ResultVariable, name_factory: &mut NameFactory) -> (Data, Vec<IrInstruction>) {
+    let (function, mut instructions) = transform_expression(&indirect_call.function, name_factory);
+    let mut arguments = vec![];
+    for arg in &indirect_call.arguments {
+        let (res_var, mut inst) = transform_expression(arg, name_factory);
+        arguments.push(res_var);
+        instructions.append(&mut inst);
+    }
+    instructions.push(IrInstruction::IndirectCall(result.to_owned(), function, arguments));
+    (Data::Variable(result.to_owned()), instructions)
+}
+
+//targets of break and continue
+struct LoopLabels {
+    next: Label, //condition of a while loop, step of a for loop
+    end: Label
+}
+
+fn transform_if_statement(if_statement: &ssa::SsaIfStatement, phi_nodes: &ssa::PhiNodes, loop_labels: Option<&LoopLabels>, name_factory: &mut NameFactory) -> Vec<IrInstruction> {
     let mut instructions: Vec<IrInstruction> = vec![];
     let (result, mut condition_ir) = transform_expression(&if_statement.condition, name_factory);
     instructions.append(&mut condition_ir);
     let false_if_label = &name_factory.get_label();
     let true_if_label = &name_factory.get_label();
     instructions.push(IrInstruction::JumpFalse(result, false_if_label.to_owned()));
-    instructions.append(&mut transform_block(&if_statement.block, name_factory));
+    instructions.append(&mut transform_block(&if_statement.block, loop_labels, name_factory));
     //fix inner phi nodes
     for phi in phi_nodes{
         instructions.push(IrInstruction::Assignment(phi.result_var.to_owned(), Data::Variable(phi.inner_option.to_owned())));
     }
     instructions.push(IrInstruction::Jump(true_if_label.to_owned()));
     instructions.push(IrInstruction::Label(false_if_label.to_owned()));
+    if let Some(else_block) = &if_statement.else_block {
+        instructions.append(&mut transform_block(else_block, loop_labels, name_factory));
+    }
     //fix outer phi nodes
     for phi in phi_nodes{
         instructions.push(IrInstruction::Assignment(phi.result_var.to_owned(), Data::Variable(phi.outer_option.to_owned())));
@@ -184,51 +386,164 @@ fn transform_assignment(assignment: &ssa::SsaAssignment, name_factory: &mut Name
     instructions
 }
 
-fn transform_while_loop(while_loop: &ssa::SsaWhileLoop, phi_nodes: &ssa::PhiNodes, loop_phi_nodes: &ssa::LoopPhiNodes, name_factory: &mut NameFactory) -> Vec<IrInstruction> {
+fn transform_global_assignment(assignment: &ssa::SsaAssignment, name_factory: &mut NameFactory) -> Vec<IrInstruction> {
     let mut instructions: Vec<IrInstruction> = vec![];
-    let start_loop_label = &name_factory.get_label();
-    let end_loop_label = &name_factory.get_label();
-    let init_false_loop_label = &name_factory.get_label();
-    let inner_loop_label = &name_factory.get_label();
-    let total_end_label = &name_factory.get_label();
+    let (result, mut expression_ir) = transform_expression(&assignment.expression, name_factory);
+    instructions.append(&mut expression_ir);
+    instructions.push(IrInstruction::StoreGlobal(assignment.variable_name.to_owned(), result));
+    instructions
+}
+
+fn transform_array_assignment(assignment: &ssa::SsaArrayAssignment, name_factory: &mut NameFactory) -> Vec<IrInstruction> {
+    let mut instructions: Vec<IrInstruction> = vec![];
+    let (array, mut array_ir) = transform_expression(&assignment.array, name_factory);
+    let (index, mut index_ir) = transform_expression(&assignment.index, name_factory);
+    let (value, mut value_ir) = transform_expression(&assignment.expression, name_factory);
+    instructions.append(&mut array_ir);
+    instructions.append(&mut index_ir);
+    instructions.append(&mut value_ir);
+    instructions.push(IrInstruction::ArrayStore(array, index, value));
+    instructions
+}
 
+fn transform_field_assignment(assignment: &ssa::SsaFieldAssignment, name_factory: &mut NameFactory) -> Vec<IrInstruction> {
+    let mut instructions: Vec<IrInstruction> = vec![];
+    let (object, mut object_ir) = transform_expression(&assignment.object, name_factory);
+    let (value, mut value_ir) = transform_expression(&assignment.expression, name_factory);
+    instructions.append(&mut object_ir);
+    instructions.append(&mut value_ir);
+    instructions.push(IrInstruction::FieldStore(object, assignment.field.to_owned(), value));
+    instructions
+}
 
+fn transform_while_loop(while_loop: &ssa::SsaWhileLoop, phi_nodes: &ssa::PhiNodes, loop_phi_nodes: &ssa::LoopPhiNodes, name_factory: &mut NameFactory) -> Vec<IrInstruction> {
+    let mut instructions: Vec<IrInstruction> = vec![];
+    let start_label = &name_factory.get_label();
+    let end_label = name_factory.get_label();
+    let inner_loop_label = &name_factory.get_label();
+    let step_label = while_loop.step.as_ref().map(|_| name_factory.get_label());
+    let loop_labels = LoopLabels { next: step_label.clone().unwrap_or(start_label.to_owned()), end: end_label };
 
+    //the condition is generated twice (before the loop and for every iteration), it can contain labels
+    let (init_result, mut init_condition_ir) = transform_expression(&while_loop.condition, name_factory);
     let (result, mut condition_ir) = transform_expression(&while_loop.condition, name_factory);
-    instructions.append(&mut condition_ir.clone());
-    instructions.push(IrInstruction::JumpFalse(result.clone(), init_false_loop_label.to_owned()));
+    //variables that are read in the loop are needed again in the next iteration
+    let mut used_vars = get_used_variables(&condition_ir);
+    if let Data::Variable(v) = &result {
+        used_vars.push(v.to_owned());
+    }
+    instructions.append(&mut init_condition_ir);
+    instructions.push(IrInstruction::JumpFalse(init_result, loop_labels.end.to_owned()));
     //init inner block vars
     for phi in phi_nodes {
         instructions.push(IrInstruction::Assignment(phi.inner_option.to_owned(), Data::Variable(phi.outer_option.to_owned())));
     }
     instructions.push(IrInstruction::Jump(inner_loop_label.to_owned()));
-    instructions.push(IrInstruction::Label(start_loop_label.to_owned()));
+    instructions.push(IrInstruction::Label(start_label.to_owned()));
     instructions.append(&mut condition_ir);
-    instructions.push(IrInstruction::JumpFalse(result, end_loop_label.to_owned()));
+    instructions.push(IrInstruction::JumpFalse(result, loop_labels.end.to_owned()));
     instructions.push(IrInstruction::Label(inner_loop_label.to_owned()));
-    instructions.append(&mut transform_block(&while_loop.block, name_factory));
+    let mut block_ir = transform_block(&while_loop.block, Some(&loop_labels), name_factory);
+    used_vars.append(&mut get_used_variables(&block_ir));
+    instructions.append(&mut block_ir);
     //condition phi nodes
-    for loop_phi in loop_phi_nodes {
-        instructions.push(IrInstruction::Assignment(loop_phi.condition_var.to_owned(), Data::Variable(loop_phi.inner_var.to_owned())));
-        instructions.push(IrInstruction::KeepAlive(loop_phi.condition_var.to_owned()));
+    instructions.append(&mut transform_loop_phi_nodes(loop_phi_nodes));
+    if let (Some(step), Some(step_label)) = (&while_loop.step, step_label) {
+        instructions.push(IrInstruction::Label(step_label));
+        let mut step_ir = transform_block(&step.block, None, name_factory);
+        used_vars.append(&mut get_used_variables(&step_ir));
+        instructions.append(&mut step_ir);
+        instructions.append(&mut transform_loop_phi_nodes(&step.phi_nodes));
     }
-    instructions.push(IrInstruction::Jump(start_loop_label.to_owned()));
-    instructions.push(IrInstruction::Label(init_false_loop_label.to_owned()));
-    //outer nodes
-    for phi in phi_nodes {
-        instructions.push(IrInstruction::Assignment(phi.result_var.to_owned(), Data::Variable(phi.outer_option.to_owned())));
+    for var in used_vars.iter().chain(loop_phi_nodes.iter().map(|l| &l.condition_var)).unique() {
+        instructions.push(IrInstruction::KeepAlive(var.to_owned()));
     }
-    instructions.push(IrInstruction::Jump(total_end_label.to_owned()));
-    instructions.push(IrInstruction::Label(end_loop_label.to_owned()));
-    //inner nodes
+    instructions.push(IrInstruction::Jump(start_label.to_owned()));
+    instructions.push(IrInstruction::Label(loop_labels.end.to_owned()));
+    //every exit (condition or break) leaves the current values in the variables that are read by the condition
     for phi in phi_nodes {
-        instructions.push(IrInstruction::Assignment(phi.result_var.to_owned(), Data::Variable(phi.inner_option.to_owned())));
+        instructions.push(IrInstruction::Assignment(phi.result_var.to_owned(), Data::Variable(phi.outer_option.to_owned())));
     }
-    instructions.push(IrInstruction::Label(total_end_label.to_owned()));
 
     instructions
 }
 
+fn transform_loop_phi_nodes(loop_phi_nodes: &ssa::LoopPhiNodes) -> Vec<IrInstruction> {
+    loop_phi_nodes.iter().map(|loop_phi| IrInstruction::Assignment(loop_phi.condition_var.to_owned(), Data::Variable(loop_phi.inner_var.to_owned()))).collect()
+}
+
+fn transform_loop_control(loop_phi_nodes: &ssa::LoopPhiNodes, target: &Label) -> Vec<IrInstruction> {
+    let mut instructions = transform_loop_phi_nodes(loop_phi_nodes);
+    instructions.push(IrInstruction::Jump(target.to_owned()));
+    instructions
+}
+
+pub fn get_result_variable(inst: &IrInstruction) -> Option<&ResultVariable> {
+    match inst {
+        IrInstruction::Jump(_) | IrInstruction::JumpFalse(_, _) | IrInstruction::Label(_) |
+        IrInstruction::Return(_) | IrInstruction::KeepAlive(_) | IrInstruction::StoreGlobal(_, _) | IrInstruction::ArrayStore(_, _, _) |
+        IrInstruction::FieldStore(_, _, _) => None,
+        IrInstruction::FunctionCall(res, _, _) | IrInstruction::Assignment(res, _) | IrInstruction::LoadGlobal(res, _) | IrInstruction::LoadString(res, _) |
+        IrInstruction::IndirectCall(res, _, _) | IrInstruction::LoadFunction(res, _) |
+        IrInstruction::Negation(res, _) |
+        IrInstruction::LogicNot(res, _) | IrInstruction::BitwiseNot(res, _) | IrInstruction::NewArray(res, _) | IrInstruction::ArrayLength(res, _) |
+        IrInstruction::ArrayLoad(res, _, _) | IrInstruction::NewStruct(res, _, _) | IrInstruction::FieldLoad(res, _, _) |
+        IrInstruction::Addition(res, _, _) | IrInstruction::Subtraction(res, _, _) | IrInstruction::Multiplication(res, _, _) |
+        IrInstruction::Division(res, _, _) | IrInstruction::Modulo(res, _, _) | IrInstruction::Greater(res, _, _) |
+        IrInstruction::GreaterEquals(res, _, _) | IrInstruction::Less(res, _, _) | IrInstruction::LessEquals(res, _, _) |
+        IrInstruction::Equals(res, _, _) | IrInstruction::NotEquals(res, _, _) |
+        IrInstruction::BitwiseAnd(res, _, _) | IrInstruction::BitwiseOr(res, _, _) |
+        IrInstruction::BitwiseXor(res, _, _) | IrInstruction::ShiftLeft(res, _, _) | IrInstruction::ShiftRight(res, _, _) |
+        IrInstruction::ShiftRightLogical(res, _, _) |
+        IrInstruction::FloatAddition(res, _, _) | IrInstruction::FloatSubtraction(res, _, _) | IrInstruction::FloatMultiplication(res, _, _) |
+        IrInstruction::FloatDivision(res, _, _) | IrInstruction::FloatGreater(res, _, _) | IrInstruction::FloatGreaterEquals(res, _, _) |
+        IrInstruction::FloatLess(res, _, _) | IrInstruction::FloatLessEquals(res, _, _) | IrInstruction::FloatEquals(res, _, _) |
+        IrInstruction::FloatNotEquals(res, _, _) | IrInstruction::FloatNegation(res, _) | IrInstruction::IntToFloat(res, _) |
+        IrInstruction::FloatToInt(res, _) => Some(res)
+    }
+}
+
+//operands that are read by the instruction (KeepAlive doesn't count as a read)
+pub fn get_operands(inst: &IrInstruction) -> Vec<&Data> {
+    match inst {
+        IrInstruction::Jump(_) | IrInstruction::Label(_) | IrInstruction::KeepAlive(_) | IrInstruction::LoadGlobal(_, _) |
+        IrInstruction::LoadString(_, _) | IrInstruction::LoadFunction(_, _) => vec![],
+        IrInstruction::JumpFalse(d, _) | IrInstruction::Assignment(_, d) | IrInstruction::StoreGlobal(_, d) | IrInstruction::Return(d) |
+        IrInstruction::Negation(_, d) | IrInstruction::LogicNot(_, d) | IrInstruction::BitwiseNot(_, d) |
+        IrInstruction::NewArray(_, d) | IrInstruction::ArrayLength(_, d) |
+        IrInstruction::FloatNegation(_, d) | IrInstruction::IntToFloat(_, d) | IrInstruction::FloatToInt(_, d) |
+        IrInstruction::FieldLoad(_, d, _) => vec![d],
+        IrInstruction::ArrayStore(d1, d2, d3) => vec![d1, d2, d3],
+        IrInstruction::FieldStore(d1, _, d2) => vec![d1, d2],
+        IrInstruction::NewStruct(_, _, fields) => fields.iter().map(|(_, d)| d).collect(),
+        IrInstruction::FunctionCall(_, _, args) => args.iter().collect(),
+        IrInstruction::IndirectCall(_, function, args) => std::iter::once(function).chain(args.iter()).collect(),
+        IrInstruction::Addition(_, d1, d2) | IrInstruction::Subtraction(_, d1, d2) | IrInstruction::Multiplication(_, d1, d2) |
+        IrInstruction::Division(_, d1, d2) | IrInstruction::Modulo(_, d1, d2) | IrInstruction::Greater(_, d1, d2) |
+        IrInstruction::GreaterEquals(_, d1, d2) | IrInstruction::Less(_, d1, d2) | IrInstruction::LessEquals(_, d1, d2) |
+        IrInstruction::Equals(_, d1, d2) | IrInstruction::NotEquals(_, d1, d2) |
+        IrInstruction::BitwiseAnd(_, d1, d2) | IrInstruction::BitwiseOr(_, d1, d2) |
+        IrInstruction::BitwiseXor(_, d1, d2) | IrInstruction::ShiftLeft(_, d1, d2) | IrInstruction::ShiftRight(_, d1, d2) |
+        IrInstruction::ShiftRightLogical(_, d1, d2) | IrInstruction::ArrayLoad(_, d1, d2) |
+        IrInstruction::FloatAddition(_, d1, d2) | IrInstruction::FloatSubtraction(_, d1, d2) | IrInstruction::FloatMultiplication(_, d1, d2) |
+        IrInstruction::FloatDivision(_, d1, d2) | IrInstruction::FloatGreater(_, d1, d2) | IrInstruction::FloatGreaterEquals(_, d1, d2) |
+        IrInstruction::FloatLess(_, d1, d2) | IrInstruction::FloatLessEquals(_, d1, d2) | IrInstruction::FloatEquals(_, d1, d2) |
+        IrInstruction::FloatNotEquals(_, d1, d2) => vec![d1, d2]
+    }
+}
+
+fn get_used_variables(instructions: &Vec<IrInstruction>) -> Vec<VariableName> {
+    let mut vars = vec![];
+    for inst in instructions {
+        for d in get_operands(inst) {
+            if let Data::Variable(v) = d {
+                vars.push(v.to_owned());
+            }
+        }
+    }
+    vars
+}
+
 fn transform_function_call(function_call: &ssa::SsaFunctionCall, name_factory: &mut NameFactory) -> Vec<IrInstruction> {
     let mut instructions: Vec<IrInstruction> = vec![];
     let mut arguments: Arguments = vec![];
@@ -252,26 +567,33 @@ fn transform_return(expression: &parser::Expression, name_factory: &mut NameFact
 }
 
 
-fn transform_statement(statement: &ssa::SsaStatement, name_factory: &mut NameFactory) -> Vec<IrInstruction> {
+fn transform_statement(statement: &ssa::SsaStatement, loop_labels: Option<&LoopLabels>, name_factory: &mut NameFactory) -> Vec<IrInstruction> {
     match statement {
         ssa::SsaStatement::Assignment(a) => transform_assignment(a, name_factory),
-        ssa::SsaStatement::IfStatement(s, phi) => transform_if_statement(s, phi, name_factory),
+        ssa::SsaStatement::GlobalAssignment(a) => transform_global_assignment(a, name_factory),
+        ssa::SsaStatement::ArrayAssignment(a) => transform_array_assignment(a, name_factory),
+        ssa::SsaStatement::FieldAssignment(a) => transform_field_assignment(a, name_factory),
+        ssa::SsaStatement::IfStatement(s, phi) => transform_if_statement(s, phi, loop_labels, name_factory),
         ssa::SsaStatement::FunctionCall(f) => transform_function_call(f, name_factory),
+        ssa::SsaStatement::IndirectCall(c) => transform_indirect_call(c, &name_factory.get_variable(), name_factory).1,
         ssa::SsaStatement::WhileLoop(l, phi, loop_phi) => transform_while_loop(l, phi, loop_phi, name_factory),
-        ssa::SsaStatement::Return(e) =>transform_return(e, name_factory)
+        ssa::SsaStatement::Return(e) =>transform_return(e, name_factory),
+        //the semantic check makes sure that break and continue are only used in loops
+        ssa::SsaStatement::Break(loop_phi) => transform_loop_control(loop_phi, &loop_labels.expect("break outside of a loop").end),
+        ssa::SsaStatement::Continue(loop_phi) => transform_loop_control(loop_phi, &loop_labels.expect("continue outside of a loop").next)
     }
 }
 
-fn transform_block(block: &ssa::SsaBlock, name_factory: &mut NameFactory) -> Vec<IrInstruction> {
+fn transform_block(block: &ssa::SsaBlock, loop_labels: Option<&LoopLabels>, name_factory: &mut NameFactory) -> Vec<IrInstruction> {
     let mut instructions: Vec<IrInstruction> = vec![];
     for statement in block {
-        instructions.append(&mut transform_statement(statement, name_factory));
+        instructions.append(&mut transform_statement(statement, loop_labels, name_factory));
     }
     instructions
 }
 
 pub fn transform(function: &ssa::SsaFunction) -> Vec<IrInstruction> {
-    transform_block(&function.block, &mut NameFactory::new())
+    transform_block(&function.block, None, &mut NameFactory::new())
 }
 
 
@@ -284,10 +606,10 @@ mod tests {
     fn ir_test_assignment() {
         let code = "
             fun test() {
-                a = 1;
+                let a = 1;
             }
         ";
-        let prog = ssa::convert(&parser::parse(&mut lexer::lex(&code)).unwrap());
+        let prog = ssa::convert(&parser::parse(&mut lexer::lex(code)).unwrap());
         let ir = transform(&prog.functions[0]);
         assert_eq!(ir, [IrInstruction::Assignment("#var_a_#0".to_owned(), Data::Number(1))])
     }
@@ -299,7 +621,7 @@ mod tests {
                 abc();
             }
         ";
-        let prog = ssa::convert(&parser::parse(&mut lexer::lex(&code)).unwrap());
+        let prog = ssa::convert(&parser::parse(&mut lexer::lex(code)).unwrap());
         let ir = transform(&prog.functions[0]);
         assert_eq!(ir, [IrInstruction::FunctionCall("#var_1".to_owned(), "abc".to_owned(), vec![])])
     }
@@ -315,20 +637,49 @@ mod tests {
                 }
             }
         ";
-        let prog = ssa::convert(&parser::parse(&mut lexer::lex(&code)).unwrap());
+        let prog = ssa::convert(&parser::parse(&mut lexer::lex(code)).unwrap());
         let ir = transform(&prog.functions[0]);
         assert_eq!(ir, [IrInstruction::JumpFalse(Data::Number(1), "#label_1".to_owned()), IrInstruction::JumpFalse(Data::Number(2), "#label_3".to_owned()), IrInstruction::Jump("#label_4".to_owned()), IrInstruction::Label("#label_3".to_owned()), IrInstruction::Label("#label_4".to_owned()), IrInstruction::Jump("#label_2".to_owned()), IrInstruction::Label("#label_1".to_owned()), IrInstruction::Label("#label_2".to_owned())] )
     }
 
+    #[test]
+    fn ir_test_if_else_phi_nodes() {
+        let code = "
+            fun test(c) {
+                let a = 1;
+                if(c){
+                    a = 2;
+                } else {
+                    a = 3;
+                }
+                return a;
+            }
+        ";
+        let prog = ssa::convert(&parser::parse(&mut lexer::lex(code)).unwrap());
+        let ir = transform(&prog.functions[0]);
+        assert_eq!(ir, [
+            IrInstruction::Assignment("#var_a_#0".to_owned(), Data::Number(1)),
+            IrInstruction::JumpFalse(Data::Variable("#var_c_#0".to_owned()), "#label_1".to_owned()),
+            IrInstruction::Assignment("#var_a_#1".to_owned(), Data::Number(2)),
+            IrInstruction::Assignment("#var_a_#3".to_owned(), Data::Variable("#var_a_#1".to_owned())),
+            IrInstruction::Jump("#label_2".to_owned()),
+            IrInstruction::Label("#label_1".to_owned()),
+            IrInstruction::Assignment("#var_a_#2".to_owned(), Data::Number(3)),
+            IrInstruction::Assignment("#var_a_#3".to_owned(), Data::Variable("#var_a_#2".to_owned())),
+            IrInstruction::Label("#label_2".to_owned()),
+            IrInstruction::Return(Data::Variable("#var_a_#3".to_owned()))
+        ])
+    }
+
     #[test]
     fn ir_test_function_call_with_expressions_as_arguments_and_assignment() {
         let code = "
             fun test() {
-                c = abc(a, 1, 3 && 4, b);
+                let c = abc(a, 1, 3 && 4, b);
             }
         ";
-        let prog = ssa::convert(&parser::parse(&mut lexer::lex(&code)).unwrap());
+        let prog = ssa::convert(&parser::parse(&mut lexer::lex(code)).unwrap());
         let ir = transform(&prog.functions[0]);
-        assert_eq!(ir, [IrInstruction::LogicAnd("#var_5".to_owned(), Data::Number(3), Data::Number(4)), IrInstruction::FunctionCall("#var_2".to_owned(), "abc".to_owned(), [Data::Variable("#var_a_#0".to_owned()), Data::Number(1), Data::Variable("#var_5".to_owned()), Data::Variable("#var_b_#0".to_owned())].to_vec()), IrInstruction::Assignment("#var_c_#0".to_owned(), Data::Variable("#var_2".to_owned()))])
+        assert_eq!(ir, [IrInstruction::JumpFalse(Data::Number(3), "#label_1".to_owned()), IrInstruction::NotEquals("#var_5".to_owned(), Data::Number(4), Data::Number(0)), IrInstruction::Jump("#label_2".to_owned()), IrInstruction::Label("#label_1".to_owned()), IrInstruction::Assignment("#var_5".to_owned(), Data::Number(0)), IrInstruction::Label("#label_2".to_owned()), IrInstruction::FunctionCall("#var_2".to_owned(), "abc".to_owned(), [Data::Variable("#var_a_#0".to_owned()), Data::Number(1), Data::Variable("#var_5".to_owned()), Data::Variable("#var_b_#0".to_owned())].to_vec()), IrInstruction::Assignment("#var_c_#0".to_owned(), Data::Variable("#var_2".to_owned()))])
     }
 }
@@ -1,30 +1,171 @@
 use std::collections::HashMap;
 use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
 use bimap::BiMap;
 use log::debug;
 use std::arch::asm;
-use crate::memory::Executable;
-use crate::memory::ExecuteableMemory;
-use crate::memory::Writeable;
+use crate::memory::CodeArena;
+use crate::memory::CodeRange;
+use crate::memory::StringTable;
+use itertools::Itertools;
+use crate::parser;
 use crate::ssa;
 use crate::ir;
 use crate::asm;
-use crate::memory;
+use crate::optimizer;
+use crate::predefined_functions::ExternalFunction;
 pub type FunctionAddress = u64;
 pub type FunctionId = i64;
 
+//arrays can have at most this many elements
+pub const MAX_ARRAY_SIZE: i64 = 1 << 24;
+//arrays and structs can use at most this many words together (one word for the header and one for every element or
+//field), objects are only freed when the program ends
+pub const MAX_HEAP_SIZE: i64 = 1 << 25;
+
+//error codes that are written into the runtime by the generated code
+pub const RUNTIME_ERROR_INDEX_OUT_OF_BOUNDS: i32 = 1;
+pub const RUNTIME_ERROR_INVALID_ARRAY_SIZE: i32 = 2;
+pub const RUNTIME_ERROR_INVALID_ARRAY: i32 = 3;
+pub const RUNTIME_ERROR_INVALID_FUNCTION: i32 = 4;
+pub const RUNTIME_ERROR_INVALID_NUMBER_OF_ARGUMENTS: i32 = 5;
+pub const RUNTIME_ERROR_INVALID_FIELD: i32 = 6;
+pub const RUNTIME_ERROR_OUT_OF_MEMORY: i32 = 7;
+pub const RUNTIME_ERROR_INVALID_STRING: i32 = 8;
+pub const RUNTIME_ERROR_DIVISION_BY_ZERO: i32 = 9;
+pub const RUNTIME_ERROR_DIVISION_OVERFLOW: i32 = 10;
+
+// State that is shared with the generated code (it accesses the fields with fixed offsets).
+// Arrays and structs are allocated here and freed together with the function tracker.
+// The first element of every array is its length, the first element of every struct is !id (always negative)
+// followed by the fields in the declaration order.
+// Arrays and structs (except the ones in a stack frame) are referenced by handles, the generated code looks up the
+// address of an object in the object table (handles that weren't returned by the runtime are out of range or map to 0).
+#[repr(C)]
+pub struct Runtime {
+    pub error: i64, //0 if no error occured
+    pub value: i64, //index, size, array, function reference, number of arguments, field or string that caused the error
+    pub length: i64, //length of the array if the index was out of bounds, number of parameters if the number of arguments was wrong
+    pub stack_pointer: u64, //stack pointer of the entry function, it is restored when an error occurs
+    pub object_table: *const i64, //address of the object of every handle
+    pub num_handles: u64, //handles are less than this
+    object_addresses: Vec<i64>, //the object table, handle 0 is never valid
+    heap_size: i64, //words used by all objects
+    objects: Vec<Box<[i64]>>,
+    field_names: Vec<String> //fields are reported by their index
+}
+
+impl Runtime {
+    fn new(field_names: Vec<String>) -> Self {
+        let object_addresses = vec![0];
+        Runtime {
+            error: 0,
+            value: 0,
+            length: 0,
+            stack_pointer: 0,
+            object_table: object_addresses.as_ptr(),
+            num_handles: object_addresses.len() as u64,
+            object_addresses,
+            heap_size: 0,
+            objects: vec![],
+            field_names
+        }
+    }
+
+    //address of the header, 0 (and sets the error) if the heap is full
+    fn allocate(&mut self, header: i64, num_elements: i64) -> i64 {
+        if self.heap_size + num_elements + 1 > MAX_HEAP_SIZE {
+            self.error = RUNTIME_ERROR_OUT_OF_MEMORY as i64;
+            self.value = num_elements;
+            return 0;
+        }
+        self.heap_size += num_elements + 1;
+        let mut object = vec![0; num_elements as usize + 1].into_boxed_slice();
+        object[0] = header;
+        let address = object.as_mut_ptr() as i64;
+        self.objects.push(object);
+        address
+    }
+
+    //handle of the array, 0 (and sets the error) if the size is invalid
+    pub fn allocate_array(&mut self, size: i64) -> i64 {
+        if !(0..=MAX_ARRAY_SIZE).contains(&size) {
+            self.error = RUNTIME_ERROR_INVALID_ARRAY_SIZE as i64;
+            self.value = size;
+            return 0;
+        }
+        let address = self.allocate(size, size);
+        self.add_handle(address)
+    }
+
+    //handle of the struct, 0 if the heap is full. the fields are initialized by the generated code
+    pub fn allocate_struct(&mut self, id: i64, num_fields: i64) -> i64 {
+        let address = self.allocate(!id, num_fields);
+        self.add_handle(address)
+    }
+
+    fn add_handle(&mut self, address: i64) -> i64 {
+        if address == 0 {
+            return 0;
+        }
+        self.object_addresses.push(address);
+        self.object_table = self.object_addresses.as_ptr();
+        self.num_handles = self.object_addresses.len() as u64;
+        self.num_handles as i64 - 1
+    }
+
+    fn check_error(&mut self) -> Result<(), JitError> {
+        let error = match self.error as i32 {
+            0 => return Ok(()),
+            RUNTIME_ERROR_INDEX_OUT_OF_BOUNDS => JitError::IndexOutOfBounds(self.value, self.length),
+            RUNTIME_ERROR_INVALID_ARRAY_SIZE => JitError::InvalidArraySize(self.value),
+            RUNTIME_ERROR_INVALID_FUNCTION => JitError::InvalidFunction(self.value),
+            RUNTIME_ERROR_INVALID_NUMBER_OF_ARGUMENTS => JitError::InvalidNumberOfArgumentsForReference(self.length, self.value),
+            RUNTIME_ERROR_INVALID_FIELD => JitError::InvalidField(self.field_names[self.value as usize].to_owned()),
+            RUNTIME_ERROR_OUT_OF_MEMORY => JitError::OutOfMemory(self.value),
+            RUNTIME_ERROR_INVALID_STRING => JitError::InvalidString(self.value),
+            RUNTIME_ERROR_DIVISION_BY_ZERO => JitError::DivisionByZero,
+            RUNTIME_ERROR_DIVISION_OVERFLOW => JitError::DivisionOverflow,
+            _ => JitError::InvalidArray(self.value)
+        };
+        self.error = 0;
+        Err(error)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Tier {
+    Baseline, //counts invocations and loop iterations
+    Optimized
+}
+
 
 pub struct FunctionTracker{
     name_id_mapping: BiMap<String, FunctionId>,
     id_external_fun_mapping: HashMap<FunctionId, FunctionAddress>,
-    id_memory_mapping: HashMap<FunctionId, ExecuteableMemory>,
+    id_code_mapping: HashMap<FunctionId, CodeRange>,
+    retired_code: Vec<CodeRange>, //replaced by a higher tier, freed once no jit code is executing
+    code_arena: CodeArena,
+    function_table: Box<[AtomicU64]>,
+    num_parameters: Box<[i64]>, //same order as the function table, checked by indirect calls
+    id_tier_mapping: HashMap<FunctionId, Tier>,
+    counters: Box<[u64]>,
+    globals: Box<[i64]>, //data section, one slot per global (same order as in the program)
+    field_offsets: Vec<Box<[i64]>>, //byte offset of a field in every struct (0 if the struct doesn't have it), same order as the field names of the runtime
+    runtime: Box<Runtime>,
+    strings: StringTable, //read-only data section
+    tier_up_threshold: Option<u64>,
     program: ssa::SsaProgram,
     print_ir: bool,
-    print_asm: bool
+    print_asm: bool,
+    optimize: bool
 }
 
+//the functions are called through entry functions, they return to the caller if a runtime error occurs
 pub struct MainFunction {
     function: extern "C" fn() -> i64,
+    init_globals: Option<extern "C" fn() -> i64>,
+    runtime: *mut Runtime,
     num_args: u64,
 }
 
@@ -36,6 +177,26 @@ impl std::fmt::Display for JitError {
                 write!(f, "Invalid number of arguments for main function! Expected {:?}. Found {:?}.", expected, found),
             Self::TooManyArguments =>
                 write!(f, "The execute function can't handle so many arguments (JIT limitation)"),
+            Self::IndexOutOfBounds(index, length) =>
+                write!(f, "index {} out of bounds for array of length {}", index, length),
+            Self::InvalidArraySize(size) =>
+                write!(f, "invalid array size: {}", size),
+            Self::InvalidArray(value) =>
+                write!(f, "not an array: {}", value),
+            Self::InvalidFunction(value) =>
+                write!(f, "not a function: {}", value),
+            Self::InvalidNumberOfArgumentsForReference(expected, found) =>
+                write!(f, "Invalid number of arguments for function reference! Expected {:?}. Found {:?}.", expected, found),
+            Self::InvalidField(field) =>
+                write!(f, "not a struct with the field: {}", field),
+            Self::OutOfMemory(size) =>
+                write!(f, "out of memory, can't allocate an object with {} elements", size),
+            Self::InvalidString(value) =>
+                write!(f, "not a string: {}", value),
+            Self::DivisionByZero =>
+                write!(f, "division by zero"),
+            Self::DivisionOverflow =>
+                write!(f, "division overflow: {} / -1", i64::MIN),
         }
     }
 }
@@ -43,7 +204,17 @@ impl std::fmt::Display for JitError {
 #[derive(Debug, PartialEq)]
 pub enum JitError {
     InvalidNumberOfArguments(u64, u64),
-    TooManyArguments
+    TooManyArguments,
+    IndexOutOfBounds(i64, i64),
+    InvalidArraySize(i64),
+    InvalidArray(i64),
+    InvalidFunction(i64),
+    InvalidNumberOfArgumentsForReference(i64, i64),
+    InvalidField(String),
+    OutOfMemory(i64),
+    InvalidString(i64),
+    DivisionByZero,
+    DivisionOverflow
 }
 
 impl MainFunction {
@@ -54,6 +225,11 @@ impl MainFunction {
                 return Err(JitError::InvalidNumberOfArguments(self.num_args, args.len() as u64))
             }
 
+            if let Some(init_globals) = self.init_globals {
+                init_globals();
+                (*self.runtime).check_error()?;
+            }
+
             let rdi: i64;
             let rsi: i64;
             let rdx: i64;
@@ -91,6 +267,7 @@ impl MainFunction {
             asm!("mov r10, {}", in(reg) r10);
             asm!("mov r11, {}", in(reg) r11);
 
+            (*self.runtime).check_error()?;
             Ok(res)
         }
     }
@@ -98,39 +275,80 @@ impl MainFunction {
 
 
 impl FunctionTracker {
-    pub fn new(program: ssa::SsaProgram, print_ir: bool, print_asm: bool) -> Self {
+    pub fn new(program: ssa::SsaProgram, print_ir: bool, print_asm: bool, tier_up_threshold: Option<u64>, optimize: bool) -> Self {
         let mut name_id_mapping = BiMap::new();
         for (idx, name) in program.functions.iter().map(|f| f.name.to_owned() ).enumerate() {
             name_id_mapping.insert(name, idx as i64);
 
         }
 
+        //one slot per function, internal functions first (0 = not compiled yet)
+        let function_table: Box<[AtomicU64]> = (0..program.functions.len() + program.external_functions.len()).map(|_| AtomicU64::new(0)).collect();
+
+        let num_parameters = program.functions.iter().map(|f| f.parameters.len() as i64)
+            .chain(program.external_functions.iter().map(|f| f.parameters.len() as i64)).collect();
+
         let mut id_external_fun_mapping = HashMap::new();
         for (idx, fun) in program.external_functions.iter().enumerate() {
             name_id_mapping.insert(fun.name.to_owned(), -(idx as i64) - 1);
             id_external_fun_mapping.insert(-(idx as i64) - 1, fun.address.to_owned());
+            function_table[program.functions.len() + idx].store(fun.address.to_owned(), Ordering::Release);
         }
 
 
+        let field_names: Vec<String> = program.structs.iter().flat_map(|s| s.fields.iter().cloned()).unique().collect();
+        let field_offsets = field_names.iter().map(|field| program.structs.iter().map(|s| match s.fields.iter().position(|f| f == field) {
+            Some(idx) => (idx as i64 + 1) * 8,
+            None => 0
+        }).collect()).collect();
 
         FunctionTracker {
-            name_id_mapping: name_id_mapping,
-            id_external_fun_mapping: id_external_fun_mapping,
-            id_memory_mapping: HashMap::new(),
-            program: program,
-            print_ir: print_ir,
-            print_asm: print_asm
+            name_id_mapping,
+            id_external_fun_mapping,
+            id_code_mapping: HashMap::new(),
+            retired_code: vec![],
+            code_arena: CodeArena::new(),
+            function_table,
+            num_parameters,
+            id_tier_mapping: HashMap::new(),
+            counters: vec![0; program.functions.len()].into_boxed_slice(),
+            globals: vec![0; program.globals.len()].into_boxed_slice(),
+            field_offsets,
+            runtime: Box::new(Runtime::new(field_names)),
+            strings: StringTable::new(&program.strings),
+            tier_up_threshold,
+            program,
+            print_ir,
+            print_asm,
+            optimize
         }
     }
 
     pub fn get_main_function(&mut self) -> MainFunction {
-        let id = self.get_id(&"main".to_owned());
-        let fun = self.program.functions.iter().find(|f| f.name == "main".to_owned()).unwrap().clone();
-        MainFunction{function: self.complile_function(id), num_args: fun.parameters.len() as u64}
+        let id = self.get_id("main");
+        let fun = self.program.functions.iter().find(|f| f.name == "main").unwrap().clone();
+        let tier = self.get_initial_tier();
+        let init_globals = self.name_id_mapping.get_by_left(ssa::GLOBALS_INIT_FUNCTION).copied().map(|init_id| {
+            self.complile_function(init_id, tier);
+            self.compile_entry(init_id)
+        });
+        self.complile_function(id, tier);
+        let function = self.compile_entry(id);
+        MainFunction{function, init_globals, runtime: self.get_runtime_address() as *mut Runtime, num_args: fun.parameters.len() as u64}
+    }
+
+    //calls the function through its function table slot and restores the stack after a runtime error
+    fn compile_entry(&mut self, id: FunctionId) -> extern "C" fn() -> i64 {
+        let slot = self.get_function_slot(id);
+        let stack_pointer = &mut self.runtime.stack_pointer as *mut u64 as u64;
+        let is = asm::generate_entry(slot, stack_pointer).unwrap();
+        let bytes = asm::assemble(&is, 0).unwrap();
+        let range = self.code_arena.allocate(&bytes).unwrap();
+        self.code_arena.as_function(&range).unwrap()
     }
 
 
-    pub fn get_id(&mut self, name: &String) -> FunctionId{
+    pub fn get_id(&mut self, name: &str) -> FunctionId{
         match self.name_id_mapping.get_by_left(name) {
             None =>{
                 panic!("All the ids should have been defined, but couldn't find id for: {}", name)
@@ -139,13 +357,131 @@ impl FunctionTracker {
         }
     }
 
-    fn complile_function(&mut self, id: i64) -> extern "C" fn() -> i64 {
+    fn get_slot_index(&self, id: FunctionId) -> usize {
+        if id >= 0 {
+            id as usize
+        }else{
+            self.program.functions.len() + (-id - 1) as usize
+        }
+    }
+
+    // address of the function table entry, generated code calls through it
+    pub fn get_function_slot(&mut self, id: FunctionId) -> u64 {
+        let idx = self.get_slot_index(id);
+        self.function_table[idx].as_ptr() as u64
+    }
+
+    // function references are the index of the function table slot + 1 (0 is never a valid reference)
+    pub fn get_function_reference(&mut self, name: &str) -> i64 {
+        let id = self.get_id(name);
+        self.get_slot_index(id) as i64 + 1
+    }
+
+    pub fn get_function_table_address(&mut self) -> u64 {
+        self.function_table.as_ptr() as u64
+    }
+
+    pub fn get_function_table_len(&self) -> usize {
+        self.function_table.len()
+    }
+
+    // address of the number of parameters of the first function table slot
+    pub fn get_num_parameters_address(&mut self) -> u64 {
+        self.num_parameters.as_ptr() as u64
+    }
+
+    // address of the invocation/back-edge counter of a baseline function
+    pub fn get_counter_address(&mut self, id: FunctionId) -> u64 {
+        &mut self.counters[id as usize] as *mut u64 as u64
+    }
+
+    // address of the slot of a global variable in the data section
+    pub fn get_global_address(&mut self, name: &str) -> u64 {
+        let idx = self.program.globals.iter().position(|g| g == name).expect("globals are checked by the semantic analysis");
+        &mut self.globals[idx] as *mut i64 as u64
+    }
+
+    // address of a string literal in the read-only data section
+    pub fn get_string_address(&mut self, s: &str) -> u64 {
+        self.strings.get_address(s).expect("strings are collected by the ssa conversion")
+    }
+
+    // id and declaration of a struct, the id is stored in the header of every instance
+    pub fn get_struct(&self, name: &String) -> (i64, parser::Struct) {
+        let id = self.program.structs.iter().position(|s| &s.name == name).expect("structs are checked by the semantic analysis");
+        (id as i64, self.program.structs[id].clone())
+    }
+
+    pub fn get_num_structs(&self) -> usize {
+        self.program.structs.len()
+    }
+
+    // index of the field (reported by runtime errors), byte offsets of the field in the structs that have it
+    // and the address of the offsets of the field in every struct
+    pub fn get_field(&mut self, field: &str) -> (i64, Vec<(i64, i64)>, u64) {
+        let idx = self.runtime.field_names.iter().position(|f| f == field).expect("fields are checked by the semantic analysis");
+        let offsets = &self.field_offsets[idx];
+        let structs = offsets.iter().enumerate().filter(|(_, offset)| **offset != 0).map(|(id, offset)| (id as i64, *offset)).collect();
+        (idx as i64, structs, offsets.as_ptr() as u64)
+    }
+
+    // external functions with float parameters or results are called with the float arguments in xmm registers
+    pub fn get_float_external(&self, name: &str) -> Option<ExternalFunction> {
+        self.program.external_functions.iter().find(|f| f.name == name && f.uses_floats()).cloned()
+    }
+
+    // address of the runtime (errors, arrays and structs)
+    pub fn get_runtime_address(&mut self) -> u64 {
+        &mut *self.runtime as *mut Runtime as u64
+    }
+
+    pub fn get_tier_up_threshold(&self) -> Option<u64> {
+        self.tier_up_threshold
+    }
+
+    #[allow(dead_code)]
+    pub fn get_tier(&self, id: FunctionId) -> Option<Tier> {
+        self.id_tier_mapping.get(&id).copied()
+    }
+
+    fn get_initial_tier(&self) -> Tier {
+        match self.tier_up_threshold {
+            Some(_) => Tier::Baseline,
+            None => Tier::Optimized
+        }
+    }
+
+    pub fn tier_up(&mut self, id: FunctionId) {
+        if self.id_tier_mapping.get(&id) == Some(&Tier::Optimized) {
+            return;
+        }
+        // the baseline code may still be executing further up in the call stack, it is retired instead of freed
+        self.complile_function(id, Tier::Optimized);
+    }
+
+    // must only be called while no jit code is executing (e.g. after the main function returned)
+    pub fn free_retired_code(&mut self) {
+        for range in self.retired_code.drain(..) {
+            self.code_arena.free(range);
+        }
+    }
+
+    // the optimized tier always runs the optimizer, the baseline tier is compiled as fast as possible
+    fn get_ir(&self, fun: &ssa::SsaFunction, tier: Tier) -> Vec<ir::IrInstruction> {
+        let ir = ir::transform(fun);
+        match tier {
+            Tier::Optimized if self.optimize || self.tier_up_threshold.is_some() => optimizer::optimize(ir, &fun.parameters),
+            _ => ir
+        }
+    }
+
+    fn complile_function(&mut self, id: i64, tier: Tier) -> extern "C" fn() -> i64 {
         let name = self.name_id_mapping.get_by_right(&id).unwrap().clone();
 
-        debug!("Compiling function: {} with id {}", name, id);
+        debug!("Compiling function: {} with id {} ({:?})", name, id, tier);
 
-        let fun = self.program.functions.iter().find(|f| f.name == name.to_owned()).unwrap().clone();
-        let ir = ir::transform(&fun);
+        let fun = self.program.functions.iter().find(|f| f.name == name).unwrap().clone();
+        let ir = self.get_ir(&fun, tier);
 
         if self.print_ir {
             println!("\n\n##### IR Output Start [Function: {}] #####", name);
@@ -153,7 +489,7 @@ impl FunctionTracker {
             println!("##### IR Output End [Function: {}] #####", name);
         }
 
-        let is = asm::generate(&ir, &fun.parameters, self).unwrap();
+        let is = asm::generate(&ir, &fun.parameters, id, tier, self).unwrap();
         let bytes = asm::assemble(&is, 0).unwrap();
 
         if self.print_asm {
@@ -162,35 +498,232 @@ impl FunctionTracker {
             println!("##### Bytes Output End [Function: {}] #####", name);
         }
 
-        let mut memory = memory::ExecuteableMemory::new(bytes.len());
-        memory.write(&bytes);
-        let compiled_function = memory.as_function();
-        self.id_memory_mapping.insert(id, memory);
+        let range = self.code_arena.allocate(&bytes).unwrap();
+        let compiled_function = self.code_arena.as_function(&range).unwrap();
+        if let Some(replaced) = self.id_code_mapping.insert(id, range) {
+            self.retired_code.push(replaced);
+        }
+        self.id_tier_mapping.insert(id, tier);
+        //swap the entry point, following calls use the new code
+        let idx = self.get_slot_index(id);
+        self.function_table[idx].store(compiled_function as usize as u64, Ordering::Release);
         compiled_function
     }
 
     pub fn get_function_address(&mut self, id: FunctionId) -> FunctionAddress {
         if id >= 0 {
-            match self.id_memory_mapping.get_mut(&id) {
-                None => self.complile_function(id) as u64,
-                Some(mem) => {
-                    mem.as_function() as u64
+            match self.id_code_mapping.get(&id).copied() {
+                None => {
+                    let tier = self.get_initial_tier();
+                    self.complile_function(id, tier) as usize as u64
+                },
+                Some(range) => {
+                    self.code_arena.as_function(&range).unwrap() as usize as u64
                 }
             }
         }else{
             match self.id_external_fun_mapping.get_mut(&id) {
                 None => panic!("The function with {} does not exit", id),
-                Some(v) => return v.to_owned()
+                Some(v) => v.to_owned()
             }
         }
 
     }
 
-
+    //returns 0 and sets the runtime error if the reference is invalid or the number of arguments doesn't match
+    pub fn resolve_function_reference(&mut self, function: i64, num_args: i64) -> FunctionAddress {
+        let idx = match usize::try_from(function.wrapping_sub(1)) {
+            Ok(idx) if idx < self.function_table.len() => idx,
+            _ => {
+                self.runtime.error = RUNTIME_ERROR_INVALID_FUNCTION as i64;
+                self.runtime.value = function;
+                return 0;
+            }
+        };
+        if self.num_parameters[idx] != num_args {
+            self.runtime.error = RUNTIME_ERROR_INVALID_NUMBER_OF_ARGUMENTS as i64;
+            self.runtime.value = num_args;
+            self.runtime.length = self.num_parameters[idx];
+            return 0;
+        }
+        let num_functions = self.program.functions.len();
+        let id = if idx < num_functions { idx as i64 } else { -((idx - num_functions) as i64) - 1 };
+        self.get_function_address(id)
+    }
 }
 
 
 #[no_mangle]
 pub extern "C" fn jit_callback(function_tracker: &mut FunctionTracker, function_id: FunctionId) -> FunctionAddress{
+    debug!("Resolving function with id {}", function_id);
     function_tracker.get_function_address(function_id)
 }
+
+#[no_mangle]
+pub extern "C" fn jit_indirect_callback(function_tracker: &mut FunctionTracker, function: i64, num_args: i64) -> FunctionAddress {
+    debug!("Resolving function reference {}", function);
+    function_tracker.resolve_function_reference(function, num_args)
+}
+
+#[no_mangle]
+pub extern "C" fn jit_tier_up_callback(function_tracker: &mut FunctionTracker, function_id: FunctionId) {
+    debug!("Tier up function with id {}", function_id);
+    function_tracker.tier_up(function_id);
+}
+
+#[no_mangle]
+pub extern "C" fn jit_new_array(runtime: &mut Runtime, size: i64) -> i64 {
+    debug!("Allocating array with size {}", size);
+    runtime.allocate_array(size)
+}
+
+#[no_mangle]
+pub extern "C" fn jit_new_struct(runtime: &mut Runtime, id: i64, num_fields: i64) -> i64 {
+    debug!("Allocating struct with id {}", id);
+    runtime.allocate_struct(id, num_fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer, parser, predefined_functions, semantic, types};
+    use crate::interpreter::Interpreter;
+
+    fn get_tracker(code: &str, tier_up_threshold: Option<u64>, optimize: bool) -> FunctionTracker {
+        let mut program = parser::parse(&mut lexer::lex(code)).unwrap();
+        predefined_functions::add(&mut program);
+        semantic::check(&program).unwrap();
+        types::check(&mut program).unwrap();
+        FunctionTracker::new(ssa::convert(&program), false, false, tier_up_threshold, optimize)
+    }
+
+    fn execute(code: &str, tier_up_threshold: Option<u64>, optimize: bool) -> Result<i64, JitError> {
+        let mut tracker = get_tracker(code, tier_up_threshold, optimize);
+        let mut main_function = tracker.get_main_function();
+        main_function.execute(vec![])
+    }
+
+    #[test]
+    fn arrays_are_checked_against_the_object_table() {
+        let codes = [
+            "fun main() { let x = 4096; x[0] = 7; return x[0]; }",
+            "fun main() { let x = 4096; return x[0]; }",
+            "fun main() { let x = 4096; return len(x); }"
+        ];
+        for code in codes {
+            for (tier_up_threshold, optimize) in [(None, false), (Some(1), true)] {
+                assert_eq!(execute(code, tier_up_threshold, optimize), Err(JitError::InvalidArray(4096)), "{}", code);
+            }
+        }
+        assert_eq!(execute("fun main() { let x = 0; return len(x); }", None, false), Err(JitError::InvalidArray(0)));
+        assert_eq!(execute("fun main() { let x = new_array(2); return len(x + 1); }", None, false), Err(JitError::InvalidArray(2)));
+        assert_eq!(execute("fun main() { let x = new_array(2); return len(0 - x); }", None, false), Err(JitError::InvalidArray(-1)));
+    }
+
+    #[test]
+    fn fields_are_checked_against_the_object_table() {
+        let codes = [
+            "struct P { x, y } fun main() { let a = 8000; return a.x; }",
+            "struct P { x, y } fun main() { let a = 8000; a.x = 1; return 0; }",
+            "struct P { x, y } fun main() { let a = new_array(2); return a.x; }",
+            "struct P { x, y } fun f(a) { return a.x; } fun main() { return f(0 - 1); }",
+            "struct P { x, y } fun main() { let a = P { x: 1, y: 2 }; if (a.y == 2) { a = 8000; } return a.x; }"
+        ];
+        for code in codes {
+            for (tier_up_threshold, optimize) in [(None, false), (Some(1), true)] {
+                assert_eq!(execute(code, tier_up_threshold, optimize), Err(JitError::InvalidField("x".to_owned())), "{}", code);
+            }
+        }
+        let code = "struct P { x, y } fun main() { let a = P { x: 1, y: 2 }; let b = a; if (b.y == 2) { b = P { x: 3, y: 4 }; } return a.x + b.x; }";
+        assert_eq!(execute(code, None, false), Ok(4));
+        let res = execute("struct P { x, y } fun main() { let a = P { x: 1, y: 2 }; return len(a); }", None, false);
+        assert!(matches!(res, Err(JitError::InvalidArray(_))), "{:?}", res);
+    }
+
+    #[test]
+    fn string_functions_check_their_argument() {
+        let codes = [
+            "fun main() { print_str(12345); return 0; }",
+            "fun main() { return str_len(12345); }",
+            "fun main() { println_str(12345); return 0; }",
+            "fun main() { let f = &println_str; f(12345); return 0; }"
+        ];
+        for code in codes {
+            for (tier_up_threshold, optimize) in [(None, false), (Some(1), true)] {
+                assert_eq!(execute(code, tier_up_threshold, optimize), Err(JitError::InvalidString(12345)), "{}", code);
+            }
+        }
+        //inside of a string
+        let res = execute("fun main() { let s = \"abc\"; return str_char_at(s + 8, 0); }", None, false);
+        assert!(matches!(res, Err(JitError::InvalidString(_))), "{:?}", res);
+        //the error was reset
+        assert_eq!(execute("fun main() { let s = \"abc\"; return str_len(s); }", None, false), Ok(3));
+    }
+
+    #[test]
+    fn casts_convert_untyped_values() {
+        let code = "
+            fun half(a) -> float { return a as float / 2.0; }
+            fun main() { let a = new_array(1); a[0] = 2.5; return (half(3) * 10.0) as int + (float_from_bits(a[0]) * 100.0) as int; }
+        ";
+        for (tier_up_threshold, optimize) in [(None, false), (Some(1), true)] {
+            assert_eq!(execute(code, tier_up_threshold, optimize), Ok(15 + 250));
+        }
+    }
+
+    #[test]
+    fn division_errors_match_the_interpreter() {
+        let codes = [
+            ("fun f(a, b) { return a / b; } fun main() { return f(1, 0); }", JitError::DivisionByZero),
+            ("fun f(a, b) { return a % b; } fun main() { return f(1, 0); }", JitError::DivisionByZero),
+            ("fun f(a, b) { return a / b; } fun main() { return f(0 - 9223372036854775807 - 1, 0 - 1); }", JitError::DivisionOverflow),
+            ("fun f(a, b) { return a % b; } fun main() { return f(0 - 9223372036854775807 - 1, 0 - 1); }", JitError::DivisionOverflow)
+        ];
+        for (code, error) in codes {
+            for (tier_up_threshold, optimize) in [(None, false), (Some(1), true)] {
+                assert_eq!(execute(code, tier_up_threshold, optimize).map_err(|err| err.to_string()), Err(error.to_string()), "{}", code);
+            }
+            let mut program = parser::parse(&mut lexer::lex(code)).unwrap();
+            predefined_functions::add(&mut program);
+            let mut interpreter = Interpreter::new(ssa::convert(&program), false, false);
+            assert_eq!(interpreter.execute_main(vec![]).unwrap_err().to_string(), error.to_string(), "{}", code);
+        }
+        assert_eq!(execute("fun f(a, b) { return a / b + a % b; } fun main() { return f(0 - 7, 0 - 1); }", None, false), Ok(7));
+    }
+
+    #[test]
+    fn heap_is_limited() {
+        let code = "fun main() { while (1) { let a = new_array(1 << 20); } return 0; }";
+        assert_eq!(execute(code, None, false), Err(JitError::OutOfMemory(1 << 20)));
+        //the arrays fill the heap except for 2 words (a struct with 2 fields needs 3), the struct escapes and is allocated by the runtime
+        let code = "
+            struct P { x, y }
+            fun f() { let p = P { x: 1, y: 2 }; return p; }
+            fun main() { let a = new_array(1 << 24); let b = new_array((1 << 24) - 4); f(); return 0; }
+        ";
+        assert_eq!(execute(code, None, false), Err(JitError::OutOfMemory(2)));
+        assert_eq!(execute(&code.replace("- 4", "- 5"), None, false), Ok(0));
+    }
+
+    #[test]
+    fn optimized_tier_runs_the_optimizer_without_optimize_flag() {
+        let tracker = get_tracker("fun f(a) { let b = 2 * 3; return a + b; } fun main() { return f(1); }", Some(2), false);
+        let fun = tracker.program.functions.iter().find(|f| f.name == "f").unwrap().clone();
+        let baseline = tracker.get_ir(&fun, Tier::Baseline);
+        assert_eq!(baseline, ir::transform(&fun));
+        assert_eq!(tracker.get_ir(&fun, Tier::Optimized), optimizer::optimize(baseline, &fun.parameters));
+    }
+
+    #[test]
+    fn tier_up_retires_baseline_code() {
+        let code = "fun f(a) { return a + 1; } fun main() { let s = f(0); s = f(s); s = f(s); return s; }";
+        let mut tracker = get_tracker(code, Some(2), false);
+        let mut main_function = tracker.get_main_function();
+        assert_eq!(main_function.execute(vec![]).unwrap(), 3);
+        let id = tracker.get_id("f");
+        assert_eq!(tracker.get_tier(id), Some(Tier::Optimized));
+        assert_eq!(tracker.retired_code.len(), 1);
+        tracker.free_retired_code();
+        assert!(tracker.retired_code.is_empty());
+    }
+}
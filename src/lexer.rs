@@ -1,9 +1,11 @@
 use logos::Logos;
-use std::num::ParseIntError;
+use std::num::{ParseFloatError, ParseIntError};
 
 #[derive(Default, Debug, Clone, PartialEq)]
 pub enum LexingError {
     InvalidInteger(String),
+    InvalidFloat(String),
+    InvalidEscapeSequence(String),
     #[default]
     NonAsciiCharacter,
 }
@@ -19,8 +21,14 @@ impl From<ParseIntError> for LexingError {
     }
 }
 
+impl From<ParseFloatError> for LexingError {
+    fn from(err: ParseFloatError) -> Self {
+        LexingError::InvalidFloat(err.to_string())
+    }
+}
 
-#[derive(Logos, Debug, PartialEq, Eq, Clone)]
+
+#[derive(Logos, Debug, PartialEq, Clone)]
 #[logos(error = LexingError)]
 #[logos(skip r"([ \t\n\r\f]+|(\/\/.*))")]
 pub enum Token {
@@ -36,22 +44,70 @@ pub enum Token {
     #[token(")")]
     ClosingRoundBracket,
 
+    #[token("[")]
+    OpeningSquareBracket,
+
+    #[token("]")]
+    ClosingSquareBracket,
+
     #[token(";")]
     Semicolon,
 
     #[token(",")]
     Comma,
 
+    #[token(":")]
+    Colon,
+
+    #[token("->")]
+    Arrow,
+
+    #[token(".")]
+    Dot,
+
     #[token("if")]
     IfStatement,
 
+    #[token("else")]
+    Else,
+
     #[token("while")]
     WhileLoop,
 
+    #[token("for")]
+    ForLoop,
+
+    #[token("break")]
+    Break,
+
+    #[token("continue")]
+    Continue,
+
     #[token("fun")]
     Function,
 
-    #[regex(r"[A-z]([A-z]|[0-9])*", |lex| lex.slice().to_owned())]
+    #[token("const")]
+    Constant,
+
+    #[token("global")]
+    Global,
+
+    #[token("struct")]
+    Struct,
+
+    #[token("let")]
+    Let,
+
+    #[token("new_array")]
+    NewArray,
+
+    #[token("len")]
+    ArrayLength,
+
+    #[token("as")]
+    As,
+
+    #[regex(r"[A-Za-z_][A-Za-z0-9_]*", |lex| lex.slice().to_owned())]
     Identifier(String),
 
     #[token("&&")]
@@ -60,6 +116,30 @@ pub enum Token {
     #[token("||")]
     LogicOr,
 
+    #[token("&")]
+    BitwiseAnd,
+
+    #[token("|")]
+    BitwiseOr,
+
+    #[token("^")]
+    BitwiseXor,
+
+    #[token("<<")]
+    ShiftLeft,
+
+    #[token(">>")]
+    ShiftRight,
+
+    #[token(">>>")]
+    ShiftRightLogical,
+
+    #[token("!")]
+    LogicNot,
+
+    #[token("~")]
+    BitwiseNot,
+
     #[token("=")]
     Assignment,
 
@@ -96,13 +176,88 @@ pub enum Token {
     #[token("%")]
     Modulo,
 
+    #[token("+=")]
+    AdditionAssignment,
+
+    #[token("-=")]
+    SubtractionAssignment,
+
+    #[token("*=")]
+    MultiplicationAssignment,
+
+    #[token("/=")]
+    DivisionAssignment,
+
+    #[token("%=")]
+    ModuloAssignment,
+
+    #[token("&=")]
+    BitwiseAndAssignment,
+
+    #[token("|=")]
+    BitwiseOrAssignment,
+
+    #[token("^=")]
+    BitwiseXorAssignment,
+
+    #[token("<<=")]
+    ShiftLeftAssignment,
+
+    #[token(">>=")]
+    ShiftRightAssignment,
+
+    #[token(">>>=")]
+    ShiftRightLogicalAssignment,
+
+    //`--x` is a decrement token, not a double negation
+    #[token("++")]
+    Increment,
+
+    #[token("--")]
+    Decrement,
+
     #[token("return")]
     Return,
 
-    #[regex("-?[0-9]+", |lex| lex.slice().parse())]
-    #[regex("'[ -~]'", |lex| lex.slice().as_bytes()[1] as i64)]
+    //the sign is not part of the number (`-` is parsed as negation)
+    #[regex("[0-9]+", |lex| lex.slice().parse())]
     Number(i64),
 
+    #[regex("'[ -~]'", |lex| lex.slice().as_bytes()[1] as i64)]
+    Character(i64),
+
+    //digits are required on both sides of the dot, the exponent is optional
+    #[regex(r"[0-9]+\.[0-9]+([eE][+-]?[0-9]+)?", |lex| lex.slice().parse())]
+    Float(f64),
+
+    //printable ascii characters, `"` and `\` have to be escaped
+    #[regex(r#""([ !#-\[\]-~]|\\[ -~])*""#, unescape_string)]
+    String(String),
+
+}
+
+//replaces the escape sequences and removes the quotes
+fn unescape_string(lex: &mut logos::Lexer<Token>) -> Result<String, LexingError> {
+    let slice = lex.slice();
+    let mut res = String::new();
+    let mut chars = slice[1..slice.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => res.push('\n'),
+            Some('t') => res.push('\t'),
+            Some('r') => res.push('\r'),
+            Some('0') => res.push('\0'),
+            Some('\\') => res.push('\\'),
+            Some('"') => res.push('"'),
+            Some(e) => return Err(LexingError::InvalidEscapeSequence(format!("\\{}", e))),
+            None => return Err(LexingError::InvalidEscapeSequence("\\".to_owned()))
+        }
+    }
+    Ok(res)
 }
 
 // use this function to lex the code
@@ -140,4 +295,153 @@ mod tests {
         assert_eq!(Some(Ok(Token::Semicolon)), lex.next());
         assert_eq!(Some(Ok(Token::ClosingCurlyBracket)), lex.next());
     }
+
+    #[test]
+    fn lexer_subtraction_without_spaces() {
+        let mut lex = Token::lexer("a-1");
+        assert_eq!(Some(Ok(Token::Identifier("a".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::Subtraction)), lex.next());
+        assert_eq!(Some(Ok(Token::Number(1))), lex.next());
+    }
+
+    #[test]
+    fn lexer_bitwise_operators() {
+        let mut lex = Token::lexer("& && | || ^ << <= >> >>> >=");
+        assert_eq!(Some(Ok(Token::BitwiseAnd)), lex.next());
+        assert_eq!(Some(Ok(Token::LogicAnd)), lex.next());
+        assert_eq!(Some(Ok(Token::BitwiseOr)), lex.next());
+        assert_eq!(Some(Ok(Token::LogicOr)), lex.next());
+        assert_eq!(Some(Ok(Token::BitwiseXor)), lex.next());
+        assert_eq!(Some(Ok(Token::ShiftLeft)), lex.next());
+        assert_eq!(Some(Ok(Token::LessEquals)), lex.next());
+        assert_eq!(Some(Ok(Token::ShiftRight)), lex.next());
+        assert_eq!(Some(Ok(Token::ShiftRightLogical)), lex.next());
+        assert_eq!(Some(Ok(Token::GreaterEquals)), lex.next());
+    }
+
+    #[test]
+    fn lexer_unary_operators() {
+        let mut lex = Token::lexer("!a != ~-1");
+        assert_eq!(Some(Ok(Token::LogicNot)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("a".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::NotEquals)), lex.next());
+        assert_eq!(Some(Ok(Token::BitwiseNot)), lex.next());
+        assert_eq!(Some(Ok(Token::Subtraction)), lex.next());
+        assert_eq!(Some(Ok(Token::Number(1))), lex.next());
+    }
+
+    #[test]
+    fn lexer_arrays() {
+        let mut lex = Token::lexer("a = new_array(len(b));a[1]");
+        assert_eq!(Some(Ok(Token::Identifier("a".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::Assignment)), lex.next());
+        assert_eq!(Some(Ok(Token::NewArray)), lex.next());
+        assert_eq!(Some(Ok(Token::OpeningRoundBracket)), lex.next());
+        assert_eq!(Some(Ok(Token::ArrayLength)), lex.next());
+        assert_eq!(Some(Ok(Token::OpeningRoundBracket)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("b".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::ClosingRoundBracket)), lex.next());
+        assert_eq!(Some(Ok(Token::ClosingRoundBracket)), lex.next());
+        assert_eq!(Some(Ok(Token::Semicolon)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("a".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::OpeningSquareBracket)), lex.next());
+        assert_eq!(Some(Ok(Token::Number(1))), lex.next());
+        assert_eq!(Some(Ok(Token::ClosingSquareBracket)), lex.next());
+    }
+
+    #[test]
+    fn lexer_strings() {
+        let mut lex = Token::lexer(r#"print_str("a // b");"\"\n\t\\" "" '"'"#);
+        assert_eq!(Some(Ok(Token::Identifier("print_str".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::OpeningRoundBracket)), lex.next());
+        assert_eq!(Some(Ok(Token::String("a // b".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::ClosingRoundBracket)), lex.next());
+        assert_eq!(Some(Ok(Token::Semicolon)), lex.next());
+        assert_eq!(Some(Ok(Token::String("\"\n\t\\".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::String("".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::Character('"' as i64))), lex.next());
+    }
+
+    #[test]
+    fn lexer_type_annotations() {
+        let mut lex = Token::lexer("fun f(a: int)->bool { c = a as char; }");
+        assert_eq!(Some(Ok(Token::Function)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("f".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::OpeningRoundBracket)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("a".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::Colon)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("int".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::ClosingRoundBracket)), lex.next());
+        assert_eq!(Some(Ok(Token::Arrow)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("bool".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::OpeningCurlyBracket)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("c".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::Assignment)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("a".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::As)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("char".to_owned()))), lex.next());
+    }
+
+    #[test]
+    fn lexer_floats() {
+        let mut lex = Token::lexer("1.5 0.25e2 3.0E-1 2");
+        assert_eq!(Some(Ok(Token::Float(1.5))), lex.next());
+        assert_eq!(Some(Ok(Token::Float(25.0))), lex.next());
+        assert_eq!(Some(Ok(Token::Float(0.3))), lex.next());
+        assert_eq!(Some(Ok(Token::Number(2))), lex.next());
+    }
+
+    #[test]
+    fn lexer_structs() {
+        let mut lex = Token::lexer("struct P { x } p.x = 1.5;");
+        assert_eq!(Some(Ok(Token::Struct)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("P".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::OpeningCurlyBracket)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("x".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::ClosingCurlyBracket)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("p".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::Dot)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("x".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::Assignment)), lex.next());
+        assert_eq!(Some(Ok(Token::Float(1.5))), lex.next());
+    }
+
+    #[test]
+    fn lexer_let() {
+        let mut lex = Token::lexer("let letter = 1;");
+        assert_eq!(Some(Ok(Token::Let)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("letter".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::Assignment)), lex.next());
+        assert_eq!(Some(Ok(Token::Number(1))), lex.next());
+        assert_eq!(Some(Ok(Token::Semicolon)), lex.next());
+    }
+
+    #[test]
+    fn lexer_compound_assignments() {
+        let mut lex = Token::lexer("a += 1; b >>>= c; i++; i--; a <= -b;");
+        assert_eq!(Some(Ok(Token::Identifier("a".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::AdditionAssignment)), lex.next());
+        assert_eq!(Some(Ok(Token::Number(1))), lex.next());
+        assert_eq!(Some(Ok(Token::Semicolon)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("b".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::ShiftRightLogicalAssignment)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("c".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::Semicolon)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("i".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::Increment)), lex.next());
+        assert_eq!(Some(Ok(Token::Semicolon)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("i".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::Decrement)), lex.next());
+        assert_eq!(Some(Ok(Token::Semicolon)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("a".to_owned()))), lex.next());
+        assert_eq!(Some(Ok(Token::LessEquals)), lex.next());
+        assert_eq!(Some(Ok(Token::Subtraction)), lex.next());
+        assert_eq!(Some(Ok(Token::Identifier("b".to_owned()))), lex.next());
+    }
+
+    #[test]
+    fn lexer_invalid_escape_sequence() {
+        let mut lex = Token::lexer(r#""a\q""#);
+        assert_eq!(Some(Err(LexingError::InvalidEscapeSequence("\\q".to_owned()))), lex.next());
+    }
 }
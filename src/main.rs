@@ -1,12 +1,21 @@
 mod lexer;
 mod parser;
 mod semantic;
+mod types;
 mod asm;
 mod memory;
 mod ir;
 mod ssa;
 mod jit;
 mod predefined_functions;
+mod interpreter;
+mod optimizer;
+#[cfg(test)]
+mod evaluator;
+#[cfg(test)]
+mod differential;
+#[cfg(test)]
+mod generator;
 use structopt::StructOpt;
 use std::str::FromStr;
 use std::path::PathBuf;
@@ -32,12 +41,12 @@ impl FromStr for LogLevel {
     }
 }
 
-impl ToString for LogLevel {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            LogLevel::Debug => "debug",
-            LogLevel::Info => "Info"
-        }.to_owned()
+            LogLevel::Debug => write!(f, "debug"),
+            LogLevel::Info => write!(f, "Info")
+        }
     }
 }
 
@@ -68,15 +77,38 @@ struct Opt {
     #[structopt(short = "a", long = "print-asm")]
     print_asm: bool,
 
+    /// enables tiered compilation, functions are recompiled with optimizations after this many calls / loop iterations
+    #[structopt(short = "t", long = "tier-up")]
+    tier_up_threshold: Option<u64>,
+
+    /// executes the ir with the interpreter instead of generating machine code
+    #[structopt(long = "interpret")]
+    interpret: bool,
+
+    /// enables optimizations (constant folding and propagation, dead code elimination)
+    #[structopt(short = "O", long = "optimize")]
+    optimize: bool,
+
     /// arguments for the passed program
     #[structopt()]
     args: Vec<i64>
 }
 
 
-fn execute_code(code: &str, args: Vec<i64>, print_parse: bool, print_ssa: bool, print_ir: bool, print_asm: bool) -> Result<i64, ()>{
+#[derive(Debug, Default)]
+struct ExecuteOptions {
+    print_parse: bool,
+    print_ssa: bool,
+    print_ir: bool,
+    print_asm: bool,
+    tier_up_threshold: Option<u64>,
+    interpret: bool,
+    optimize: bool
+}
+
+fn execute_code(code: &str, args: Vec<i64>, options: &ExecuteOptions) -> Result<i64, ()>{
     debug!("Lexing and parsing code");
-    let parse_res = parser::parse(&mut lexer::lex(&code));
+    let parse_res = parser::parse(&mut lexer::lex(code));
 
     let mut program = match parse_res {
         Ok(p) => p,
@@ -86,7 +118,7 @@ fn execute_code(code: &str, args: Vec<i64>, print_parse: bool, print_ssa: bool,
         }
     };
 
-    if print_parse {
+    if options.print_parse {
         println!("\n\n##### Parse Output Start #####");
         println!("{:#?}", program);
         println!("##### Parse Output End #####");
@@ -102,20 +134,36 @@ fn execute_code(code: &str, args: Vec<i64>, print_parse: bool, print_ssa: bool,
             return Err(());
         }
     };
+    let type_res = types::check(&mut program);
+    match type_res {
+        Ok(_) => (),
+        Err(err) => {
+            error!("Type check failed: {}", err);
+            return Err(());
+        }
+    };
 
     debug!("Converting program to SSA form");
     let program_ssa = ssa::convert(&program);
 
-    if print_ssa {
+    if options.print_ssa {
         println!("\n\n##### SSA Output Start #####");
         println!("{:#?}", program_ssa);
         println!("##### SSA Output End #####");
     }
 
-    let mut function_tracker = jit::FunctionTracker::new(program_ssa, print_ir, print_asm);
-    let mut main_function = function_tracker.get_main_function();
-    debug!("Executing main function");
-    let return_value = main_function.execute(args);
+    let return_value = if options.interpret {
+        let mut interpreter = interpreter::Interpreter::new(program_ssa, options.print_ir, options.optimize);
+        debug!("Interpreting main function");
+        interpreter.execute_main(args).map_err(|e| e.to_string())
+    }else{
+        let mut function_tracker = jit::FunctionTracker::new(program_ssa, options.print_ir, options.print_asm, options.tier_up_threshold, options.optimize);
+        let mut main_function = function_tracker.get_main_function();
+        debug!("Executing main function");
+        let result = main_function.execute(args).map_err(|e| e.to_string());
+        function_tracker.free_retired_code();
+        result
+    };
     match return_value {
         Ok(value) => {
             debug!("Return value:");
@@ -140,62 +188,293 @@ fn main() {
 
     debug!("Reading source file");
     let code = fs::read_to_string(opt.file).expect("Couldn't read source code file");
-    match execute_code(&code, opt.args, opt.print_parse, opt.print_ssa, opt.print_ir, opt.print_asm) {
-        Err(()) => (),
-        Ok(_) => ()
-    }
+    let options = ExecuteOptions {
+        print_parse: opt.print_parse,
+        print_ssa: opt.print_ssa,
+        print_ir: opt.print_ir,
+        print_asm: opt.print_asm,
+        tier_up_threshold: opt.tier_up_threshold,
+        interpret: opt.interpret,
+        optimize: opt.optimize
+    };
+    if let Err(()) = execute_code(&code, opt.args, &options) {  }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    //default, interpreter, optimizer, tier up and tier up with the optimizer
+    fn get_all_options() -> Vec<ExecuteOptions> {
+        vec![
+            ExecuteOptions::default(),
+            ExecuteOptions { interpret: true, ..Default::default() },
+            ExecuteOptions { optimize: true, ..Default::default() },
+            ExecuteOptions { tier_up_threshold: Some(2), ..Default::default() },
+            ExecuteOptions { tier_up_threshold: Some(2), optimize: true, ..Default::default() }
+        ]
+    }
+
+    //the program has to give the same result with every set of options
+    fn assert_code(code: &str, args: Vec<i64>, expected: Result<i64, ()>) {
+        for options in get_all_options() {
+            assert_eq!(execute_code(code, args.clone(), &options), expected, "{:?}", options);
+        }
+    }
+
+    fn assert_file(file: &str, args: Vec<i64>, expected: Result<i64, ()>) {
+        let code = fs::read_to_string(file).expect("Couldn't read source code file");
+        assert_code(&code, args, expected);
+    }
+
     #[test]
     fn basic_code_1() {
         let code = fs::read_to_string("test/test1.ji").expect("Couldn't read source code file");
-        assert_eq!(execute_code(&code, vec![1, 2], false, false, false, false).is_ok(), true);
+        assert!(execute_code(&code, vec![1, 2], &ExecuteOptions::default()).is_ok());
     }
 
     #[test]
     fn stack_arguments_1() {
         let code = fs::read_to_string("test/test2.ji").expect("Couldn't read source code file");
-        assert_eq!(execute_code(&code, vec![], false, false, false, false).is_ok(), true);
+        assert!(execute_code(&code, vec![], &ExecuteOptions::default()).is_ok());
     }
 
     #[test]
     fn stack_arguments_2() {
         let code = fs::read_to_string("test/test3.ji").expect("Couldn't read source code file");
-        assert_eq!(execute_code(&code, vec![], false, false, false, false).unwrap(), 45);
+        assert_eq!(execute_code(&code, vec![], &ExecuteOptions::default()).unwrap(), 45);
     }
 
     #[test]
     fn stack_arguments_3() {
         let code = fs::read_to_string("test/test4.ji").expect("Couldn't read source code file");
-        assert_eq!(execute_code(&code, vec![], false, false, false, false).unwrap(), 36);
+        assert_eq!(execute_code(&code, vec![], &ExecuteOptions::default()).unwrap(), 36);
     }
 
     #[test]
     fn stack_spilling_1() {
         let code = fs::read_to_string("test/test5.ji").expect("Couldn't read source code file");
-        assert_eq!(execute_code(&code, vec![], false, false, false, false).unwrap(), 120);
+        assert_eq!(execute_code(&code, vec![], &ExecuteOptions::default()).unwrap(), 120);
     }
 
     #[test]
     fn stack_spilling_2() {
         let code = fs::read_to_string("test/test6.ji").expect("Couldn't read source code file");
-        assert_eq!(execute_code(&code, vec![], false, false, false, false).unwrap(), 136);
+        assert_eq!(execute_code(&code, vec![], &ExecuteOptions::default()).unwrap(), 136);
+    }
+
+    #[test]
+    fn loop_carried_variables() {
+        let code = "fun main() { let step = 3; let a = 0; let i = 0; while(i < 10) { i = i + 1; let b = a + step; a = b + i; } return a; }";
+        assert_code(code, vec![], Ok(85));
+    }
+
+    #[test]
+    fn scratch_register_is_not_allocated() {
+        //g is the 7th variable, the divisor is moved into rbx
+        let code = "fun main() { let a = 1; let b = 2; let c = 3; let d = 4; let e = 5; let f = 6; let g = 7; let h = 100 / 5; return a + b + c + d + e + f + g + h; }";
+        assert_code(code, vec![], Ok(48));
+    }
+
+    #[test]
+    fn large_immediates() {
+        let code = "fun main() { let a = 1; let b = a + 5000000000; let c = b - 4000000000; if(c > 3000000000) { c = c + 1; } return c; }";
+        assert_code(code, vec![], Ok(1000000001));
+    }
+
+    #[test]
+    fn immediates_with_spilled_results() {
+        let code = "fun main() { let a = 1; let b = 2; let c = 3; let d = 4; let e = 5; let f = 6; let g = 7; let h = 8; let i = 9; let j = 10; let k = 11; let l = 12; let m = 13; let n = m + 1; let o = n - 1; return a + b + c + d + e + f + g + h + i + j + k + l + m + n + o; }";
+        assert_code(code, vec![], Ok(118));
+    }
+
+    #[test]
+    fn spilled_variables_in_branches() {
+        //t is spilled in a branch that isn't executed, u and v are spilled afterwards
+        let code = "
+            fun id(x) { return x; }
+            fun main() {
+                let a = 1; let b = 2; let c = 3; let d = 4; let e = 5; let f = 6; let g = 7; let h = 8; let i = 9; let j = 10; let k = 11; let l = 12; let m = 13;
+                if(a == 0) { let t = 5; a = t; }
+                let u = 7;
+                let v = id(a);
+                return a + b + c + d + e + f + g + h + i + j + k + l + m + u + v;
+            }
+        ";
+        assert_code(code, vec![], Ok(99));
+    }
+
+    #[test]
+    fn signed_division() {
+        let code = "fun main() { let a = 0 - 7; let b = 2; let c = a / b; let d = a % b; return c * 10 + d; }";
+        assert_code(code, vec![], Ok(-31));
+    }
+
+    #[test]
+    fn logic_operators_on_negative_values() {
+        let code = "fun main() { let a = 0 - 1; let b = 0 - 2; return (a && b) * 10 + (b || 0); }";
+        assert_code(code, vec![], Ok(11));
+    }
+
+    #[test]
+    fn swapped_arguments() {
+        let code = "fun f(a, b) { return a * 10 + b; } fun g(x, y) { return f(y, x); } fun main() { return g(1, 2); }";
+        assert_code(code, vec![], Ok(21));
     }
 
     #[test]
     fn var_assignment_in_loop() {
         let code = fs::read_to_string("test/test7.ji").expect("Couldn't read source code file");
-        assert_eq!(execute_code(&code, vec![], false, false, false, false).unwrap(), 9);
+        assert_eq!(execute_code(&code, vec![], &ExecuteOptions::default()).unwrap(), 9);
     }
 
     #[test]
     fn var_assignment_in_if() {
         let code = fs::read_to_string("test/test8.ji").expect("Couldn't read source code file");
-        assert_eq!(execute_code(&code, vec![], false, false, false, false).unwrap(), 9);
+        assert_eq!(execute_code(&code, vec![], &ExecuteOptions::default()).unwrap(), 9);
+    }
+
+    #[test]
+    fn var_assignment_in_else_if() {
+        assert_file("test/test10.ji", vec![], Ok(78));
+    }
+
+    #[test]
+    fn unary_operators() {
+        assert_file("test/test11.ji", vec![], Ok(-5000 + 400 + 100 - 5));
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        assert_file("test/test12.ji", vec![], Ok(-7814797372750222065));
+    }
+
+    #[test]
+    fn short_circuit() {
+        assert_file("test/test13.ji", vec![], Ok(1014));
+    }
+
+    #[test]
+    fn break_and_continue() {
+        assert_file("test/test14.ji", vec![], Ok(7211));
+    }
+
+    #[test]
+    fn for_loops() {
+        assert_file("test/test15.ji", vec![], Ok(75166));
+    }
+
+    #[test]
+    fn constants_and_globals() {
+        assert_file("test/test16.ji", vec![], Ok(907605));
+    }
+
+    #[test]
+    fn arrays() {
+        assert_file("test/test17.ji", vec![], Ok(83204065436));
+    }
+
+    #[test]
+    fn array_index_out_of_bounds() {
+        assert_file("test/test18.ji", vec![], Err(()));
+    }
+
+    #[test]
+    fn strings() {
+        assert_file("test/test19.ji", vec![], Ok(4711072));
+    }
+
+    #[test]
+    fn function_references() {
+        assert_file("test/test20.ji", vec![], Ok(14400167610));
+    }
+
+    #[test]
+    fn static_types() {
+        assert_file("test/test21.ji", vec![], Ok(421));
+    }
+
+    #[test]
+    fn floats() {
+        assert_file("test/test22.ji", vec![], Ok(814120));
+    }
+
+    #[test]
+    fn structs() {
+        assert_file("test/test23.ji", vec![0], Ok(1216914652));
+    }
+
+    #[test]
+    fn struct_invalid_field() {
+        assert_file("test/test23.ji", vec![1], Err(()));
+    }
+
+    #[test]
+    fn block_scopes() {
+        assert_file("test/test24.ji", vec![], Ok(1201794));
+    }
+
+    #[test]
+    fn compound_assignments() {
+        assert_file("test/test25.ji", vec![], Ok(554015804));
+    }
+
+    #[test]
+    fn scoped_function_variables() {
+        assert_file("test/test26.ji", vec![], Ok(111));
+    }
+
+    #[test]
+    fn recursive_calls() {
+        let code = fs::read_to_string("test/test9.ji").expect("Couldn't read source code file");
+        assert_eq!(execute_code(&code, vec![], &ExecuteOptions::default()).unwrap(), 610);
+    }
+
+    #[test]
+    fn interpret_recursive_calls() {
+        let code = fs::read_to_string("test/test9.ji").expect("Couldn't read source code file");
+        assert_eq!(execute_code(&code, vec![], &ExecuteOptions { interpret: true, ..Default::default() }).unwrap(), 610);
+    }
+
+    #[test]
+    fn interpret_stack_arguments() {
+        let code = fs::read_to_string("test/test3.ji").expect("Couldn't read source code file");
+        assert_eq!(execute_code(&code, vec![], &ExecuteOptions { interpret: true, ..Default::default() }).unwrap(), 45);
+    }
+
+    #[test]
+    fn tiered_recursive_calls() {
+        let code = fs::read_to_string("test/test9.ji").expect("Couldn't read source code file");
+        assert_eq!(execute_code(&code, vec![], &ExecuteOptions { tier_up_threshold: Some(10), ..Default::default() }).unwrap(), 610);
+    }
+
+    #[test]
+    fn tiered_loop() {
+        let code = fs::read_to_string("test/test7.ji").expect("Couldn't read source code file");
+        assert_eq!(execute_code(&code, vec![], &ExecuteOptions { tier_up_threshold: Some(2), ..Default::default() }).unwrap(), 9);
+    }
+
+    #[test]
+    fn optimized_if() {
+        let code = fs::read_to_string("test/test8.ji").expect("Couldn't read source code file");
+        assert_eq!(execute_code(&code, vec![], &ExecuteOptions { optimize: true, ..Default::default() }).unwrap(), 9);
+    }
+
+    #[test]
+    fn optimized_tiered_loop() {
+        let code = fs::read_to_string("test/test7.ji").expect("Couldn't read source code file");
+        assert_eq!(execute_code(&code, vec![], &ExecuteOptions { tier_up_threshold: Some(2), optimize: true, ..Default::default() }).unwrap(), 9);
+    }
+
+    #[test]
+    fn optimized_division_overflow() {
+        //the optimizer must not fold the overflow into a value, it's a runtime error without -O
+        for operator in ["/", "%"] {
+            let code = format!("fun main() {{ let a = 0 - 9223372036854775807 - 1; return a {} -1; }}", operator);
+            assert_eq!(execute_code(&code, vec![], &ExecuteOptions::default()), Err(()));
+            assert_eq!(execute_code(&code, vec![], &ExecuteOptions { optimize: true, ..Default::default() }), Err(()));
+            assert_code(&code, vec![], Err(()));
+        }
     }
 
 }
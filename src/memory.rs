@@ -1,11 +1,56 @@
 use std::ffi::c_void;
-use winapi;
 use std::ptr;
 
+#[cfg(windows)]
+mod windows;
+#[cfg(windows)]
+use self::windows::{alloc_memory, free_memory, get_page_size, protect_memory};
+
+#[cfg(unix)]
+mod unix;
+#[cfg(unix)]
+use self::unix::{alloc_memory, free_memory, get_page_size, protect_memory};
+
+mod arena;
+pub use self::arena::{CodeArena, CodeRange};
+
+mod strings;
+pub use self::strings::{read_string, StringTable};
+
+//memory pages are either writeable or executable, never both (W^X)
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[allow(clippy::enum_variant_names)]
+pub enum Protection {
+    ReadWrite,
+    ReadExecute,
+    ReadOnly //data that is never modified after it was written (eg. string literals)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MemoryError {
+    WriteToSealedMemory,
+    ExecuteUnsealedMemory,
+    OutOfBounds(usize, usize)
+}
+
+impl std::fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WriteToSealedMemory =>
+                write!(f, "Tried to write to sealed (executable) memory"),
+            Self::ExecuteUnsealedMemory =>
+                write!(f, "Tried to execute memory that has not been sealed"),
+            Self::OutOfBounds(size, length) =>
+                write!(f, "Tried to write {:?} bytes into memory of size {:?}", size, length),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ExecuteableMemory {
     address: *mut c_void,
     length: usize,
+    protection: Protection,
 }
 
 pub trait Memory {
@@ -14,37 +59,78 @@ pub trait Memory {
 }
 
 pub trait Writeable {
-    fn write(&mut self, bytes: &Vec<u8>);
+    #[allow(dead_code)]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), MemoryError>;
+    fn write_at(&mut self, offset: usize, bytes: &[u8]) -> Result<(), MemoryError>;
+    fn unseal(&mut self);
 }
 
 pub trait Executable {
-    fn as_function(&mut self) -> extern "C" fn() -> i64;
+    fn seal(&mut self);
+    fn as_function(&mut self) -> Result<extern "C" fn() -> i64, MemoryError>;
 }
 
 impl ExecuteableMemory {
     pub fn new(size: usize) -> Self {
         let page_size = get_page_size();
-        let size = size.max(page_size);
+        //round up to whole pages, the protection can only be changed per page
+        let size = size.max(1).div_ceil(page_size) * page_size;
         let address = unsafe {
             alloc_memory(size, 1)
         };
 
         ExecuteableMemory {
-            address: address,
+            address,
             length: size,
+            protection: Protection::ReadWrite,
         }
     }
 }
 
 impl Writeable for ExecuteableMemory {
-    fn write(&mut self, bytes: &Vec<u8>) {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), MemoryError> {
+        self.write_at(0, bytes)
+    }
+
+    fn write_at(&mut self, offset: usize, bytes: &[u8]) -> Result<(), MemoryError> {
+        if self.protection != Protection::ReadWrite {
+            return Err(MemoryError::WriteToSealedMemory);
+        }
+        if offset + bytes.len() > self.length {
+            return Err(MemoryError::OutOfBounds(offset + bytes.len(), self.length));
+        }
         let mut write_ptr = self.address() as *mut u8;
         unsafe{
+            write_ptr = write_ptr.add(offset);
             for byte in bytes {
                 ptr::write(write_ptr, *byte);
                 write_ptr = write_ptr.add(1);
             }
         }
+        Ok(())
+    }
+
+    fn unseal(&mut self) {
+        if self.protection == Protection::ReadWrite {
+            return;
+        }
+        unsafe {
+            protect_memory(self.address, self.length, Protection::ReadWrite);
+        }
+        self.protection = Protection::ReadWrite;
+    }
+}
+
+impl ExecuteableMemory {
+    //the memory can't be written or executed anymore (until it is unsealed)
+    pub fn freeze(&mut self) {
+        if self.protection == Protection::ReadOnly {
+            return;
+        }
+        unsafe {
+            protect_memory(self.address, self.length, Protection::ReadOnly);
+        }
+        self.protection = Protection::ReadOnly;
     }
 }
 
@@ -59,9 +145,22 @@ impl Memory for ExecuteableMemory {
 }
 
 impl Executable for ExecuteableMemory {
-    fn as_function(&mut self) -> extern "C" fn() -> i64 {
+    fn seal(&mut self) {
+        if self.protection == Protection::ReadExecute {
+            return;
+        }
         unsafe {
-            core::mem::transmute(self.address)
+            protect_memory(self.address, self.length, Protection::ReadExecute);
+        }
+        self.protection = Protection::ReadExecute;
+    }
+
+    fn as_function(&mut self) -> Result<extern "C" fn() -> i64, MemoryError> {
+        if self.protection != Protection::ReadExecute {
+            return Err(MemoryError::ExecuteUnsealedMemory);
+        }
+        unsafe {
+            Ok(core::mem::transmute::<*mut c_void, extern "C" fn() -> i64>(self.address))
         }
     }
 }
@@ -74,35 +173,33 @@ impl Drop for ExecuteableMemory {
     }
 }
 
-unsafe fn alloc_memory(page_size: usize, num_pages: usize) -> *mut c_void {
-    let size = page_size * num_pages;
-    let raw_addr: *mut winapi::ctypes::c_void;
-
-    raw_addr = winapi::um::memoryapi::VirtualAlloc(
-        ::core::ptr::null_mut(),
-        size,
-        winapi::um::winnt::MEM_RESERVE | winapi::um::winnt::MEM_COMMIT,
-        winapi::um::winnt::PAGE_EXECUTE_READWRITE
-    );
 
-    assert_ne!(
-        raw_addr, 0 as *mut winapi::ctypes::c_void,
-        "Could not allocate memory. Error Code: {:?}",
-        winapi::um::errhandlingapi::GetLastError()
-    );
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    core::mem::transmute(raw_addr)
+    // mov rax, 42; ret
+    const RETURN_42: [u8; 8] = [0x48, 0xC7, 0xC0, 0x2A, 0x00, 0x00, 0x00, 0xC3];
 
-}
+    #[test]
+    fn execute_sealed_memory() {
+        let mut memory = ExecuteableMemory::new(RETURN_42.len());
+        memory.write(RETURN_42.as_ref()).unwrap();
+        memory.seal();
+        assert_eq!(memory.as_function().unwrap()(), 42);
+    }
 
-unsafe fn free_memory(address: *mut c_void, _: usize) {
-    winapi::um::memoryapi::VirtualFree(address as *mut _, 0, winapi::um::winnt::MEM_RELEASE);
-}
+    #[test]
+    fn execute_unsealed_memory() {
+        let mut memory = ExecuteableMemory::new(RETURN_42.len());
+        memory.write(RETURN_42.as_ref()).unwrap();
+        assert_eq!(memory.as_function().err(), Some(MemoryError::ExecuteUnsealedMemory));
+    }
 
-fn get_page_size() -> usize {
-    unsafe {
-        let mut info:  winapi::um::sysinfoapi::SYSTEM_INFO = core::mem::zeroed();
-        winapi::um::sysinfoapi::GetSystemInfo(&mut info as  winapi::um::sysinfoapi::LPSYSTEM_INFO);
-        info.dwPageSize as usize
+    #[test]
+    fn write_sealed_memory() {
+        let mut memory = ExecuteableMemory::new(RETURN_42.len());
+        memory.seal();
+        assert_eq!(memory.write(RETURN_42.as_ref()), Err(MemoryError::WriteToSealedMemory));
     }
 }
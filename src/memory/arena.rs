@@ -0,0 +1,208 @@
+use super::{get_page_size, Executable, ExecuteableMemory, Memory, MemoryError, Writeable};
+
+//start of every function is aligned to this many bytes
+const ALIGNMENT: usize = 16;
+//minimal number of pages that get reserved when the arena grows
+const CHUNK_PAGES: usize = 4;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CodeRange {
+    pub chunk: usize,
+    pub offset: usize,
+    pub length: usize
+}
+
+struct Chunk {
+    memory: ExecuteableMemory,
+    top: usize, //everything above top was never handed out (bump allocation)
+    free_ranges: Vec<(usize, usize)> //(offset, length) sorted by offset
+}
+
+impl Chunk {
+    fn new(size: usize) -> Self {
+        let mut memory = ExecuteableMemory::new(size);
+        memory.seal();
+        Chunk {
+            memory,
+            top: 0,
+            free_ranges: vec![]
+        }
+    }
+
+    fn free(&mut self, offset: usize, length: usize) {
+        let idx = self.free_ranges.iter().position(|(o, _)| *o > offset).unwrap_or(self.free_ranges.len());
+        self.free_ranges.insert(idx, (offset, length));
+
+        //merge with the following range
+        if idx + 1 < self.free_ranges.len() {
+            let (next_offset, next_length) = self.free_ranges[idx + 1];
+            if offset + length == next_offset {
+                self.free_ranges[idx].1 += next_length;
+                self.free_ranges.remove(idx + 1);
+            }
+        }
+        //merge with the previous range
+        if idx > 0 {
+            let (prev_offset, prev_length) = self.free_ranges[idx - 1];
+            if prev_offset + prev_length == self.free_ranges[idx].0 {
+                self.free_ranges[idx - 1].1 += self.free_ranges[idx].1;
+                self.free_ranges.remove(idx);
+            }
+        }
+        //give the last range back to the bump allocator
+        if let Some((last_offset, last_length)) = self.free_ranges.last().copied() {
+            if last_offset + last_length == self.top {
+                self.top = last_offset;
+                self.free_ranges.pop();
+            }
+        }
+    }
+}
+
+// Packs multiple functions into shared pages. Freed ranges are reused (best fit),
+// otherwise the code is placed at the top of a chunk (bump allocation).
+pub struct CodeArena {
+    chunks: Vec<Chunk>
+}
+
+impl CodeArena {
+    pub fn new() -> Self {
+        CodeArena {
+            chunks: vec![]
+        }
+    }
+
+    fn find_free_range(&mut self, size: usize) -> Option<CodeRange> {
+        let mut best: Option<(usize, usize)> = None;
+        for (chunk_idx, chunk) in self.chunks.iter().enumerate() {
+            for (range_idx, (_, length)) in chunk.free_ranges.iter().enumerate() {
+                if *length < size {
+                    continue;
+                }
+                match best {
+                    Some((c, r)) if self.chunks[c].free_ranges[r].1 <= *length => (),
+                    _ => best = Some((chunk_idx, range_idx))
+                }
+            }
+        }
+
+        let (chunk_idx, range_idx) = best?;
+        let chunk = &mut self.chunks[chunk_idx];
+        let (offset, length) = chunk.free_ranges[range_idx];
+        if length == size {
+            chunk.free_ranges.remove(range_idx);
+        }else{
+            chunk.free_ranges[range_idx] = (offset + size, length - size);
+        }
+        Some(CodeRange { chunk: chunk_idx, offset, length: size })
+    }
+
+    fn bump(&mut self, size: usize) -> CodeRange {
+        let chunk_idx = match self.chunks.iter_mut().position(|c| c.memory.length() - c.top >= size) {
+            Some(idx) => idx,
+            None => {
+                self.chunks.push(Chunk::new(size.max(CHUNK_PAGES * get_page_size())));
+                self.chunks.len() - 1
+            }
+        };
+        let chunk = &mut self.chunks[chunk_idx];
+        let offset = chunk.top;
+        chunk.top += size;
+        CodeRange { chunk: chunk_idx, offset, length: size }
+    }
+
+    pub fn allocate(&mut self, bytes: &[u8]) -> Result<CodeRange, MemoryError> {
+        let size = bytes.len().max(1).div_ceil(ALIGNMENT) * ALIGNMENT;
+        let range = match self.find_free_range(size) {
+            Some(r) => r,
+            None => self.bump(size)
+        };
+
+        let memory = &mut self.chunks[range.chunk].memory;
+        memory.unseal();
+        let res = memory.write_at(range.offset, bytes);
+        memory.seal();
+        res?;
+        Ok(range)
+    }
+
+    pub fn free(&mut self, range: CodeRange) {
+        self.chunks[range.chunk].free(range.offset, range.length);
+    }
+
+    pub fn as_function(&mut self, range: &CodeRange) -> Result<extern "C" fn() -> i64, MemoryError> {
+        let memory = &mut self.chunks[range.chunk].memory;
+        //fails if the chunk isn't sealed
+        memory.as_function()?;
+        let address = memory.address() as usize + range.offset;
+        unsafe {
+            Ok(core::mem::transmute::<usize, extern "C" fn() -> i64>(address))
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn num_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // mov rax, <n>; ret
+    fn return_number(n: u8) -> Vec<u8> {
+        vec![0x48, 0xC7, 0xC0, n, 0x00, 0x00, 0x00, 0xC3]
+    }
+
+    #[test]
+    fn arena_shares_pages() {
+        let mut arena = CodeArena::new();
+        let r1 = arena.allocate(&return_number(1)).unwrap();
+        let r2 = arena.allocate(&return_number(2)).unwrap();
+        assert_eq!(arena.num_chunks(), 1);
+        assert_eq!(r1.chunk, r2.chunk);
+        assert_eq!(r2.offset, ALIGNMENT);
+        assert_eq!(arena.as_function(&r1).unwrap()(), 1);
+        assert_eq!(arena.as_function(&r2).unwrap()(), 2);
+    }
+
+    #[test]
+    fn arena_reuses_freed_range() {
+        let mut arena = CodeArena::new();
+        let r1 = arena.allocate(&return_number(1)).unwrap();
+        let _r2 = arena.allocate(&return_number(2)).unwrap();
+        arena.free(r1);
+        let r3 = arena.allocate(&return_number(3)).unwrap();
+        assert_eq!(r3.offset, r1.offset);
+        assert_eq!(arena.as_function(&r3).unwrap()(), 3);
+    }
+
+    #[test]
+    fn arena_coalesces_freed_ranges() {
+        let mut arena = CodeArena::new();
+        let r1 = arena.allocate(&return_number(1)).unwrap();
+        let r2 = arena.allocate(&return_number(2)).unwrap();
+        let _r3 = arena.allocate(&return_number(3)).unwrap();
+        arena.free(r1);
+        arena.free(r2);
+        let mut big = return_number(4);
+        big.resize(2 * ALIGNMENT, 0x90);
+        let r4 = arena.allocate(&big).unwrap();
+        assert_eq!(r4.offset, 0);
+        assert_eq!(arena.as_function(&r4).unwrap()(), 4);
+    }
+
+    #[test]
+    fn arena_grows() {
+        let mut arena = CodeArena::new();
+        let mut big = return_number(5);
+        big.resize(CHUNK_PAGES * get_page_size() + 1, 0x90);
+        let r1 = arena.allocate(&return_number(1)).unwrap();
+        let r2 = arena.allocate(&big).unwrap();
+        assert_eq!(arena.num_chunks(), 2);
+        assert_eq!(arena.as_function(&r1).unwrap()(), 1);
+        assert_eq!(arena.as_function(&r2).unwrap()(), 5);
+    }
+}
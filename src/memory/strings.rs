@@ -0,0 +1,117 @@
+use std::collections::{BTreeSet, HashMap};
+use std::slice;
+use std::sync::Mutex;
+use super::{ExecuteableMemory, Memory, Writeable};
+
+// Layout of a string: the length (in bytes) followed by the bytes and a 0 byte,
+// padded to whole words. Strings are passed around as the address of the length.
+fn encode_string(s: &str) -> Vec<i64> {
+    let mut bytes = s.as_bytes().to_vec();
+    bytes.push(0);
+    let mut words = vec![s.len() as i64];
+    words.extend(bytes.chunks(8).map(|c| {
+        let mut word = [0; 8];
+        word[..c.len()].copy_from_slice(c);
+        i64::from_le_bytes(word)
+    }));
+    words
+}
+
+//addresses of the strings of every string table that wasn't dropped yet
+static STRINGS: Mutex<BTreeSet<u64>> = Mutex::new(BTreeSet::new());
+
+//bytes of an encoded string, None if the address isn't the address of a string in a string table.
+//the string table must not be dropped while the bytes are used
+pub unsafe fn read_string<'a>(address: i64) -> Option<&'a [u8]> {
+    if !STRINGS.lock().unwrap().contains(&(address as u64)) {
+        return None;
+    }
+    let length = *(address as *const i64);
+    Some(slice::from_raw_parts((address as *const u8).add(8), length as usize))
+}
+
+//read-only data section that contains every string literal of a program once
+pub struct StringTable {
+    #[allow(dead_code)] //owns the memory the addresses point to
+    memory: ExecuteableMemory,
+    addresses: HashMap<String, u64>
+}
+
+impl StringTable {
+    pub fn new(strings: &[String]) -> Self {
+        let mut bytes = vec![];
+        let mut offsets = vec![];
+        for s in strings {
+            offsets.push(bytes.len());
+            bytes.extend(encode_string(s).iter().flat_map(|w| w.to_le_bytes()));
+        }
+        let mut memory = ExecuteableMemory::new(bytes.len());
+        memory.write(&bytes).unwrap();
+        memory.freeze();
+        let base = memory.address() as u64;
+        let addresses: HashMap<String, u64> = strings.iter().zip(offsets).map(|(s, o)| (s.to_owned(), base + o as u64)).collect();
+        STRINGS.lock().unwrap().extend(addresses.values());
+        StringTable {
+            memory,
+            addresses
+        }
+    }
+
+    pub fn get_address(&self, s: &str) -> Option<u64> {
+        self.addresses.get(s).copied()
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.addresses.len()
+    }
+}
+
+impl Drop for StringTable {
+    fn drop(&mut self) {
+        let mut strings = STRINGS.lock().unwrap();
+        for address in self.addresses.values() {
+            strings.remove(address);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryError;
+
+    #[test]
+    fn encode_string_pads_to_words() {
+        assert_eq!(encode_string(""), vec![0, 0]);
+        assert_eq!(encode_string("abcdefgh"), vec![8, i64::from_le_bytes(*b"abcdefgh"), 0]);
+    }
+
+    #[test]
+    fn string_table_addresses() {
+        let strings = vec!["hello".to_owned(), "".to_owned(), "world!\n".to_owned()];
+        let table = StringTable::new(&strings);
+        assert_eq!(table.len(), 3);
+        for s in &strings {
+            let address = table.get_address(s).unwrap();
+            assert_eq!(address % 8, 0);
+            assert_eq!(unsafe { read_string(address as i64) }, Some(s.as_bytes()));
+        }
+        assert_eq!(table.get_address("missing"), None);
+    }
+
+    #[test]
+    fn read_string_checks_the_address() {
+        let table = StringTable::new(&["hello".to_owned()]);
+        let address = table.get_address("hello").unwrap() as i64;
+        assert_eq!(unsafe { read_string(address + 8) }, None);
+        assert_eq!(unsafe { read_string(12345) }, None);
+    }
+
+    #[test]
+    fn string_table_is_read_only() {
+        let mut table = StringTable::new(&["a".to_owned()]);
+        assert_eq!(table.memory.write(&[1]), Err(MemoryError::WriteToSealedMemory));
+    }
+}
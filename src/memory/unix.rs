@@ -0,0 +1,49 @@
+use std::ffi::c_void;
+use std::io;
+use super::Protection;
+
+pub unsafe fn alloc_memory(page_size: usize, num_pages: usize) -> *mut c_void {
+    let size = page_size * num_pages;
+    let raw_addr = libc::mmap(
+        ::core::ptr::null_mut(),
+        size,
+        libc::PROT_READ | libc::PROT_WRITE,
+        libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+        -1,
+        0
+    );
+
+    assert_ne!(
+        raw_addr, libc::MAP_FAILED,
+        "Could not allocate memory. Error: {:?}",
+        io::Error::last_os_error()
+    );
+
+    raw_addr
+}
+
+pub unsafe fn protect_memory(address: *mut c_void, length: usize, protection: Protection) {
+    let prot = match protection {
+        Protection::ReadWrite => libc::PROT_READ | libc::PROT_WRITE,
+        Protection::ReadExecute => libc::PROT_READ | libc::PROT_EXEC,
+        Protection::ReadOnly => libc::PROT_READ
+    };
+
+    let res = libc::mprotect(address, length, prot);
+
+    assert_eq!(
+        res, 0,
+        "Could not change memory protection. Error: {:?}",
+        io::Error::last_os_error()
+    );
+}
+
+pub unsafe fn free_memory(address: *mut c_void, length: usize) {
+    libc::munmap(address as *mut _, length);
+}
+
+pub fn get_page_size() -> usize {
+    unsafe {
+        libc::sysconf(libc::_SC_PAGESIZE) as usize
+    }
+}
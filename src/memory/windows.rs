@@ -0,0 +1,58 @@
+use std::ffi::c_void;
+use winapi;
+use super::Protection;
+
+pub unsafe fn alloc_memory(page_size: usize, num_pages: usize) -> *mut c_void {
+    let size = page_size * num_pages;
+    let raw_addr: *mut winapi::ctypes::c_void;
+
+    raw_addr = winapi::um::memoryapi::VirtualAlloc(
+        ::core::ptr::null_mut(),
+        size,
+        winapi::um::winnt::MEM_RESERVE | winapi::um::winnt::MEM_COMMIT,
+        winapi::um::winnt::PAGE_READWRITE
+    );
+
+    assert_ne!(
+        raw_addr, 0 as *mut winapi::ctypes::c_void,
+        "Could not allocate memory. Error Code: {:?}",
+        winapi::um::errhandlingapi::GetLastError()
+    );
+
+    core::mem::transmute(raw_addr)
+
+}
+
+pub unsafe fn protect_memory(address: *mut c_void, length: usize, protection: Protection) {
+    let new_protect = match protection {
+        Protection::ReadWrite => winapi::um::winnt::PAGE_READWRITE,
+        Protection::ReadExecute => winapi::um::winnt::PAGE_EXECUTE_READ,
+        Protection::ReadOnly => winapi::um::winnt::PAGE_READONLY
+    };
+    let mut old_protect: winapi::shared::minwindef::DWORD = 0;
+
+    let res = winapi::um::memoryapi::VirtualProtect(
+        address as *mut _,
+        length,
+        new_protect,
+        &mut old_protect
+    );
+
+    assert_ne!(
+        res, 0,
+        "Could not change memory protection. Error Code: {:?}",
+        winapi::um::errhandlingapi::GetLastError()
+    );
+}
+
+pub unsafe fn free_memory(address: *mut c_void, _: usize) {
+    winapi::um::memoryapi::VirtualFree(address as *mut _, 0, winapi::um::winnt::MEM_RELEASE);
+}
+
+pub fn get_page_size() -> usize {
+    unsafe {
+        let mut info:  winapi::um::sysinfoapi::SYSTEM_INFO = core::mem::zeroed();
+        winapi::um::sysinfoapi::GetSystemInfo(&mut info as  winapi::um::sysinfoapi::LPSYSTEM_INFO);
+        info.dwPageSize as usize
+    }
+}
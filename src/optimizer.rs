@@ -0,0 +1,11 @@
+//optimization passes over the ir of a single function
+
+use crate::ir::IrInstruction;
+use crate::parser::Parameters;
+mod constant_folding;
+mod dead_code;
+
+pub fn optimize(instructions: Vec<IrInstruction>, parameters: &Parameters) -> Vec<IrInstruction> {
+    let instructions = constant_folding::fold_constants(instructions, parameters);
+    dead_code::eliminate_dead_code(instructions)
+}
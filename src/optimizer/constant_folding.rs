@@ -0,0 +1,358 @@
+//constant folding and constant propagation
+//the ir is not in strict ssa form: phi results (and loop variables) are assigned in multiple places.
+//a variable is only treated as a constant if every assignment assigns the same value.
+//parameters are assigned by the caller, so their value is never known (even if the function assigns a constant to them).
+
+use std::collections::HashMap;
+use crate::ir::{Data, IrInstruction, get_result_variable};
+use crate::parser::Parameters;
+use crate::types::float_to_int;
+use super::dead_code::remove_unreachable;
+
+type Constants = HashMap<String, i64>; //floats are stored as their bits
+
+fn get_constant(data: &Data, constants: &Constants) -> Option<i64> {
+    match data {
+        Data::Number(n) => Some(n.to_owned()),
+        Data::Float(f) => Some(f.to_bits() as i64),
+        Data::Variable(v) => constants.get(v).copied()
+    }
+}
+
+fn binary(d1: &Data, d2: &Data, constants: &Constants, f: fn(i64, i64) -> Option<i64>) -> Option<i64> {
+    f(get_constant(d1, constants)?, get_constant(d2, constants)?)
+}
+
+fn float_binary(d1: &Data, d2: &Data, constants: &Constants, f: fn(f64, f64) -> i64) -> Option<i64> {
+    let a = f64::from_bits(get_constant(d1, constants)? as u64);
+    let b = f64::from_bits(get_constant(d2, constants)? as u64);
+    Some(f(a, b))
+}
+
+fn float_unary(d: &Data, constants: &Constants) -> Option<f64> {
+    get_constant(d, constants).map(|a| f64::from_bits(a as u64))
+}
+
+//value that the instruction assigns to its result variable, None if it is unknown at compile time
+fn evaluate(inst: &IrInstruction, constants: &Constants) -> Option<i64> {
+    match inst {
+        IrInstruction::Assignment(_, d) => get_constant(d, constants),
+        IrInstruction::Addition(_, d1, d2) => binary(d1, d2, constants, |a, b| Some(a.wrapping_add(b))),
+        IrInstruction::Subtraction(_, d1, d2) => binary(d1, d2, constants, |a, b| Some(a.wrapping_sub(b))),
+        IrInstruction::Multiplication(_, d1, d2) => binary(d1, d2, constants, |a, b| Some(a.wrapping_mul(b))),
+        //division by zero and the overflow of i64::MIN / -1 have to happen at runtime (both are runtime errors)
+        IrInstruction::Division(_, d1, d2) => binary(d1, d2, constants, i64::checked_div),
+        IrInstruction::Modulo(_, d1, d2) => binary(d1, d2, constants, i64::checked_rem),
+        IrInstruction::Greater(_, d1, d2) => binary(d1, d2, constants, |a, b| Some((a > b) as i64)),
+        IrInstruction::GreaterEquals(_, d1, d2) => binary(d1, d2, constants, |a, b| Some((a >= b) as i64)),
+        IrInstruction::Less(_, d1, d2) => binary(d1, d2, constants, |a, b| Some((a < b) as i64)),
+        IrInstruction::LessEquals(_, d1, d2) => binary(d1, d2, constants, |a, b| Some((a <= b) as i64)),
+        IrInstruction::Equals(_, d1, d2) => binary(d1, d2, constants, |a, b| Some((a == b) as i64)),
+        IrInstruction::NotEquals(_, d1, d2) => binary(d1, d2, constants, |a, b| Some((a != b) as i64)),
+        IrInstruction::BitwiseAnd(_, d1, d2) => binary(d1, d2, constants, |a, b| Some(a & b)),
+        IrInstruction::BitwiseOr(_, d1, d2) => binary(d1, d2, constants, |a, b| Some(a | b)),
+        IrInstruction::BitwiseXor(_, d1, d2) => binary(d1, d2, constants, |a, b| Some(a ^ b)),
+        IrInstruction::ShiftLeft(_, d1, d2) => binary(d1, d2, constants, |a, b| Some(a.wrapping_shl(b as u32))),
+        IrInstruction::ShiftRight(_, d1, d2) => binary(d1, d2, constants, |a, b| Some(a.wrapping_shr(b as u32))),
+        IrInstruction::ShiftRightLogical(_, d1, d2) => binary(d1, d2, constants, |a, b| Some((a as u64).wrapping_shr(b as u32) as i64)),
+        IrInstruction::Negation(_, d) => get_constant(d, constants).map(i64::wrapping_neg),
+        IrInstruction::LogicNot(_, d) => get_constant(d, constants).map(|a| (a == 0) as i64),
+        IrInstruction::BitwiseNot(_, d) => get_constant(d, constants).map(|a| !a),
+        IrInstruction::FloatAddition(_, d1, d2) => float_binary(d1, d2, constants, |a, b| (a + b).to_bits() as i64),
+        IrInstruction::FloatSubtraction(_, d1, d2) => float_binary(d1, d2, constants, |a, b| (a - b).to_bits() as i64),
+        IrInstruction::FloatMultiplication(_, d1, d2) => float_binary(d1, d2, constants, |a, b| (a * b).to_bits() as i64),
+        IrInstruction::FloatDivision(_, d1, d2) => float_binary(d1, d2, constants, |a, b| (a / b).to_bits() as i64),
+        IrInstruction::FloatGreater(_, d1, d2) => float_binary(d1, d2, constants, |a, b| (a > b) as i64),
+        IrInstruction::FloatGreaterEquals(_, d1, d2) => float_binary(d1, d2, constants, |a, b| (a >= b) as i64),
+        IrInstruction::FloatLess(_, d1, d2) => float_binary(d1, d2, constants, |a, b| (a < b) as i64),
+        IrInstruction::FloatLessEquals(_, d1, d2) => float_binary(d1, d2, constants, |a, b| (a <= b) as i64),
+        IrInstruction::FloatEquals(_, d1, d2) => float_binary(d1, d2, constants, |a, b| (a == b) as i64),
+        IrInstruction::FloatNotEquals(_, d1, d2) => float_binary(d1, d2, constants, |a, b| (a != b) as i64),
+        IrInstruction::FloatNegation(_, d) => float_unary(d, constants).map(|a| (-a).to_bits() as i64),
+        IrInstruction::IntToFloat(_, d) => get_constant(d, constants).map(|a| (a as f64).to_bits() as i64),
+        IrInstruction::FloatToInt(_, d) => float_unary(d, constants).map(float_to_int),
+        _ => None
+    }
+}
+
+//iterates until no new constants are found, variables that depend on themselves (loops) stay unknown
+fn find_constants(instructions: &Vec<IrInstruction>, parameters: &Parameters) -> Constants {
+    let mut constants = Constants::new();
+    loop {
+        //None if the assignments don't agree or one of them is unknown
+        let mut values: HashMap<&String, Option<i64>> = parameters.iter().map(|p| (p, None)).collect();
+        for inst in instructions {
+            if let Some(var) = get_result_variable(inst) {
+                let value = evaluate(inst, &constants);
+                let entry = values.entry(var).or_insert(value);
+                if *entry != value {
+                    *entry = None;
+                }
+            }
+        }
+        let new_constants: Constants = values.into_iter().filter_map(|(var, value)| Some((var.to_owned(), value?))).collect();
+        if new_constants == constants {
+            return constants;
+        }
+        constants = new_constants;
+    }
+}
+
+fn propagate(data: Data, constants: &Constants) -> Data {
+    match get_constant(&data, constants) {
+        Some(n) => Data::Number(n),
+        None => data
+    }
+}
+
+fn propagate_instruction(inst: IrInstruction, constants: &Constants) -> IrInstruction {
+    let p = |d| propagate(d, constants);
+    match inst {
+        IrInstruction::JumpFalse(d, l) => IrInstruction::JumpFalse(p(d), l),
+        IrInstruction::FunctionCall(res, f, args) => IrInstruction::FunctionCall(res, f, args.into_iter().map(p).collect()),
+        IrInstruction::IndirectCall(res, f, args) => IrInstruction::IndirectCall(res, p(f), args.into_iter().map(p).collect()),
+        IrInstruction::Addition(res, d1, d2) => IrInstruction::Addition(res, p(d1), p(d2)),
+        IrInstruction::Subtraction(res, d1, d2) => IrInstruction::Subtraction(res, p(d1), p(d2)),
+        IrInstruction::Multiplication(res, d1, d2) => IrInstruction::Multiplication(res, p(d1), p(d2)),
+        IrInstruction::Division(res, d1, d2) => IrInstruction::Division(res, p(d1), p(d2)),
+        IrInstruction::Modulo(res, d1, d2) => IrInstruction::Modulo(res, p(d1), p(d2)),
+        IrInstruction::Greater(res, d1, d2) => IrInstruction::Greater(res, p(d1), p(d2)),
+        IrInstruction::GreaterEquals(res, d1, d2) => IrInstruction::GreaterEquals(res, p(d1), p(d2)),
+        IrInstruction::Less(res, d1, d2) => IrInstruction::Less(res, p(d1), p(d2)),
+        IrInstruction::LessEquals(res, d1, d2) => IrInstruction::LessEquals(res, p(d1), p(d2)),
+        IrInstruction::Equals(res, d1, d2) => IrInstruction::Equals(res, p(d1), p(d2)),
+        IrInstruction::NotEquals(res, d1, d2) => IrInstruction::NotEquals(res, p(d1), p(d2)),
+        IrInstruction::BitwiseAnd(res, d1, d2) => IrInstruction::BitwiseAnd(res, p(d1), p(d2)),
+        IrInstruction::BitwiseOr(res, d1, d2) => IrInstruction::BitwiseOr(res, p(d1), p(d2)),
+        IrInstruction::BitwiseXor(res, d1, d2) => IrInstruction::BitwiseXor(res, p(d1), p(d2)),
+        IrInstruction::ShiftLeft(res, d1, d2) => IrInstruction::ShiftLeft(res, p(d1), p(d2)),
+        IrInstruction::ShiftRight(res, d1, d2) => IrInstruction::ShiftRight(res, p(d1), p(d2)),
+        IrInstruction::ShiftRightLogical(res, d1, d2) => IrInstruction::ShiftRightLogical(res, p(d1), p(d2)),
+        IrInstruction::Negation(res, d) => IrInstruction::Negation(res, p(d)),
+        IrInstruction::LogicNot(res, d) => IrInstruction::LogicNot(res, p(d)),
+        IrInstruction::BitwiseNot(res, d) => IrInstruction::BitwiseNot(res, p(d)),
+        IrInstruction::FloatAddition(res, d1, d2) => IrInstruction::FloatAddition(res, p(d1), p(d2)),
+        IrInstruction::FloatSubtraction(res, d1, d2) => IrInstruction::FloatSubtraction(res, p(d1), p(d2)),
+        IrInstruction::FloatMultiplication(res, d1, d2) => IrInstruction::FloatMultiplication(res, p(d1), p(d2)),
+        IrInstruction::FloatDivision(res, d1, d2) => IrInstruction::FloatDivision(res, p(d1), p(d2)),
+        IrInstruction::FloatGreater(res, d1, d2) => IrInstruction::FloatGreater(res, p(d1), p(d2)),
+        IrInstruction::FloatGreaterEquals(res, d1, d2) => IrInstruction::FloatGreaterEquals(res, p(d1), p(d2)),
+        IrInstruction::FloatLess(res, d1, d2) => IrInstruction::FloatLess(res, p(d1), p(d2)),
+        IrInstruction::FloatLessEquals(res, d1, d2) => IrInstruction::FloatLessEquals(res, p(d1), p(d2)),
+        IrInstruction::FloatEquals(res, d1, d2) => IrInstruction::FloatEquals(res, p(d1), p(d2)),
+        IrInstruction::FloatNotEquals(res, d1, d2) => IrInstruction::FloatNotEquals(res, p(d1), p(d2)),
+        IrInstruction::FloatNegation(res, d) => IrInstruction::FloatNegation(res, p(d)),
+        IrInstruction::IntToFloat(res, d) => IrInstruction::IntToFloat(res, p(d)),
+        IrInstruction::FloatToInt(res, d) => IrInstruction::FloatToInt(res, p(d)),
+        IrInstruction::Assignment(res, d) => IrInstruction::Assignment(res, p(d)),
+        IrInstruction::StoreGlobal(g, d) => IrInstruction::StoreGlobal(g, p(d)),
+        IrInstruction::NewArray(res, d) => IrInstruction::NewArray(res, p(d)),
+        IrInstruction::ArrayLength(res, d) => IrInstruction::ArrayLength(res, p(d)),
+        IrInstruction::ArrayLoad(res, d1, d2) => IrInstruction::ArrayLoad(res, p(d1), p(d2)),
+        IrInstruction::ArrayStore(d1, d2, d3) => IrInstruction::ArrayStore(p(d1), p(d2), p(d3)),
+        IrInstruction::NewStruct(res, name, fields) => IrInstruction::NewStruct(res, name, fields.into_iter().map(|(f, d)| (f, p(d))).collect()),
+        IrInstruction::FieldLoad(res, d, field) => IrInstruction::FieldLoad(res, p(d), field),
+        IrInstruction::FieldStore(d1, field, d2) => IrInstruction::FieldStore(p(d1), field, p(d2)),
+        IrInstruction::Return(d) => IrInstruction::Return(p(d)),
+        IrInstruction::Jump(_) | IrInstruction::Label(_) | IrInstruction::KeepAlive(_) | IrInstruction::LoadGlobal(_, _) |
+        IrInstruction::LoadString(_, _) | IrInstruction::LoadFunction(_, _) => inst
+    }
+}
+
+fn fold(instructions: Vec<IrInstruction>, parameters: &Parameters) -> Vec<IrInstruction> {
+    let constants = find_constants(&instructions, parameters);
+    let mut new_instructions = vec![];
+    for inst in instructions {
+        //the result is known -> replace the calculation with an assignment
+        if let (Some(var), Some(value)) = (get_result_variable(&inst), evaluate(&inst, &constants)) {
+            new_instructions.push(IrInstruction::Assignment(var.to_owned(), Data::Number(value)));
+            continue;
+        }
+        match propagate_instruction(inst, &constants) {
+            //branches with a constant condition
+            IrInstruction::JumpFalse(Data::Number(0), label) => new_instructions.push(IrInstruction::Jump(label)),
+            IrInstruction::JumpFalse(Data::Number(_), _) => (),
+            inst => new_instructions.push(inst)
+        }
+    }
+    remove_unreachable(new_instructions)
+}
+
+//removing unreachable code can make more phi nodes constant -> repeat until nothing changes
+pub fn fold_constants(mut instructions: Vec<IrInstruction>, parameters: &Parameters) -> Vec<IrInstruction> {
+    loop {
+        let new_instructions = fold(instructions.clone(), parameters);
+        if new_instructions == instructions {
+            return new_instructions;
+        }
+        instructions = new_instructions;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ir, lexer, parser, ssa};
+
+    fn fold_code(code: &str) -> Vec<IrInstruction> {
+        let prog = ssa::convert(&parser::parse(&mut lexer::lex(code)).unwrap());
+        fold_constants(ir::transform(&prog.functions[0]), &prog.functions[0].parameters)
+    }
+
+    #[test]
+    fn fold_arithmetic() {
+        let code = "
+            fun test() {
+                let a = 2 * 3 + 4;
+                return a / 2;
+            }
+        ";
+        assert_eq!(fold_code(code).last(), Some(&IrInstruction::Return(Data::Number(5))));
+    }
+
+    #[test]
+    fn fold_unary_operators() {
+        let code = "
+            fun test() {
+                return -(2 - 7) + !0 + ~1;
+            }
+        ";
+        assert_eq!(fold_code(code).last(), Some(&IrInstruction::Return(Data::Number(4))));
+    }
+
+    #[test]
+    fn fold_bitwise_operators() {
+        let code = "
+            fun test() {
+                return (12 & 10 | 1 ^ 3) + (1 << 65) + (-16 >> 2) + (-1 >>> 60);
+            }
+        ";
+        assert_eq!(fold_code(code).last(), Some(&IrInstruction::Return(Data::Number(10 + 2 - 4 + 15))));
+    }
+
+    #[test]
+    fn fold_keeps_division_by_zero() {
+        let code = "
+            fun test() {
+                return 1 / 0;
+            }
+        ";
+        assert_eq!(fold_code(code), [IrInstruction::Division("#var_1".to_owned(), Data::Number(1), Data::Number(0)), IrInstruction::Return(Data::Variable("#var_1".to_owned()))]);
+    }
+
+    #[test]
+    fn fold_keeps_division_overflow() {
+        let code = "
+            fun test() {
+                let a = 0 - 9223372036854775807 - 1;
+                return a % -1;
+            }
+        ";
+        let instructions = fold_code(code);
+        assert!(matches!(instructions[instructions.len() - 2], IrInstruction::Modulo(_, Data::Number(i64::MIN), Data::Number(-1))), "{:?}", instructions);
+    }
+
+    #[test]
+    fn fold_parameters_are_unknown() {
+        let code = "
+            fun test(a) {
+                let b = a + 1;
+                return b;
+            }
+        ";
+        assert_eq!(fold_code(code).last(), Some(&IrInstruction::Return(Data::Variable("#var_b_#0".to_owned()))));
+    }
+
+    #[test]
+    fn fold_assigned_parameters_are_unknown() {
+        let code = "
+            fun test(a, b) {
+                while(b) {
+                    if(a == 0) {
+                        a = 15;
+                        continue;
+                    }
+                    return a;
+                }
+                return 0;
+            }
+        ";
+        assert!(!fold_code(code).contains(&IrInstruction::Return(Data::Number(15))));
+    }
+
+    #[test]
+    fn fold_phi_nodes_that_agree() {
+        let code = "
+            fun test(c) {
+                let a = 1;
+                if(c) {
+                    a = 3 - 2;
+                }
+                return a;
+            }
+        ";
+        assert_eq!(fold_code(code).last(), Some(&IrInstruction::Return(Data::Number(1))));
+    }
+
+    #[test]
+    fn fold_phi_nodes_that_disagree() {
+        let code = "
+            fun test(c) {
+                let a = 1;
+                if(c) {
+                    a = 2;
+                }
+                return a;
+            }
+        ";
+        assert_eq!(fold_code(code).last(), Some(&IrInstruction::Return(Data::Variable("#var_a_#2".to_owned()))));
+    }
+
+    #[test]
+    fn fold_loop_variables_are_unknown() {
+        let code = "
+            fun test() {
+                let i = 0;
+                while(i < 10) {
+                    i = i + 1;
+                }
+                return i;
+            }
+        ";
+        assert!(!fold_code(code).contains(&IrInstruction::Return(Data::Number(0))));
+    }
+
+    #[test]
+    fn fold_constant_branches() {
+        let code = "
+            fun test() {
+                if(1 < 2) {
+                    let a = 1;
+                }
+                if(0) {
+                    let b = 1;
+                }
+            }
+        ";
+        let ir = fold_code(code);
+        assert!(!ir.iter().any(|i| matches!(i, IrInstruction::JumpFalse(_, _))));
+        assert!(ir.contains(&IrInstruction::Assignment("#var_a_#0".to_owned(), Data::Number(1))));
+        assert!(!ir.contains(&IrInstruction::Assignment("#var_b_#0".to_owned(), Data::Number(1))));
+    }
+
+    #[test]
+    fn fold_phi_nodes_of_constant_branches() {
+        let code = "
+            fun test() {
+                let a = 5;
+                let d = 1;
+                if(a > 0) {
+                    d = 9;
+                }
+                return d;
+            }
+        ";
+        assert_eq!(fold_code(code).last(), Some(&IrInstruction::Return(Data::Number(9))));
+    }
+}
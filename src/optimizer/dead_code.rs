@@ -0,0 +1,254 @@
+//dead code elimination
+//removes unreachable instructions, computations whose result is never needed, jumps to the next instruction
+//and labels that are not the target of a jump
+
+use std::collections::{HashMap, HashSet};
+use crate::ir::{self, Data, IrInstruction};
+
+//removes the instructions that can't be reached from the start of the function
+pub(super) fn remove_unreachable(instructions: Vec<IrInstruction>) -> Vec<IrInstruction> {
+    let labels: HashMap<&String, usize> = instructions.iter().enumerate().filter_map(|(idx, inst)| match inst {
+        IrInstruction::Label(l) => Some((l, idx)),
+        _ => None
+    }).collect();
+    let mut reachable = vec![false; instructions.len()];
+    let mut worklist = vec![0];
+    while let Some(idx) = worklist.pop() {
+        if idx >= instructions.len() || reachable[idx] {
+            continue;
+        }
+        reachable[idx] = true;
+        match &instructions[idx] {
+            IrInstruction::Jump(l) => worklist.push(labels[l]),
+            IrInstruction::JumpFalse(_, l) => {
+                worklist.push(labels[l]);
+                worklist.push(idx + 1);
+            },
+            IrInstruction::Return(_) => (),
+            _ => worklist.push(idx + 1)
+        }
+    }
+    //lifetimes are based on the position in the code -> KeepAlive has to stay even if it can't be reached
+    //(e.g. the end of a loop body that is always left with continue)
+    instructions.into_iter().zip(reachable).filter_map(|(inst, r)| match inst {
+        IrInstruction::KeepAlive(_) => Some(inst),
+        _ if r => Some(inst),
+        _ => None
+    }).collect()
+}
+
+//instructions that have to be kept even if their result is never read
+fn has_side_effects(inst: &IrInstruction) -> bool {
+    match inst {
+        IrInstruction::Jump(_) | IrInstruction::JumpFalse(_, _) | IrInstruction::Label(_) |
+        IrInstruction::Return(_) | IrInstruction::FunctionCall(_, _, _) | IrInstruction::IndirectCall(_, _, _) | IrInstruction::StoreGlobal(_, _) => true,
+        //can fail at runtime (invalid size, invalid array or index out of bounds)
+        IrInstruction::NewArray(_, _) | IrInstruction::ArrayLength(_, _) | IrInstruction::ArrayLoad(_, _, _) | IrInstruction::ArrayStore(_, _, _) => true,
+        //can fail if the object is not a struct with the field
+        IrInstruction::FieldLoad(_, _, _) | IrInstruction::FieldStore(_, _, _) => true,
+        //can fail with a division by zero or overflow (i64::MIN / -1)
+        IrInstruction::Division(_, _, d) | IrInstruction::Modulo(_, _, d) => !matches!(d, Data::Number(n) if *n != 0 && *n != -1),
+        _ => false
+    }
+}
+
+fn get_variables(data: Vec<&Data>) -> impl Iterator<Item = &String> {
+    data.into_iter().filter_map(|d| match d {
+        Data::Variable(v) => Some(v),
+        Data::Number(_) | Data::Float(_) => None
+    })
+}
+
+//a variable is needed if an instruction with side effects reads it or if it is used to calculate a needed variable
+fn remove_dead_computations(instructions: Vec<IrInstruction>) -> Vec<IrInstruction> {
+    let mut definitions: HashMap<&String, Vec<&IrInstruction>> = HashMap::new();
+    let mut worklist: Vec<&String> = vec![];
+    for inst in &instructions {
+        if let Some(var) = ir::get_result_variable(inst) {
+            definitions.entry(var).or_default().push(inst);
+        }
+        if has_side_effects(inst) {
+            worklist.extend(get_variables(ir::get_operands(inst)));
+        }
+    }
+    let mut needed: HashSet<String> = HashSet::new();
+    while let Some(var) = worklist.pop() {
+        if !needed.insert(var.to_owned()) {
+            continue;
+        }
+        for inst in definitions.get(var).into_iter().flatten() {
+            worklist.extend(get_variables(ir::get_operands(inst)));
+        }
+    }
+
+    instructions.into_iter().filter(|inst| {
+        if has_side_effects(inst) {
+            return true;
+        }
+        match inst {
+            IrInstruction::KeepAlive(var) => needed.contains(var),
+            _ => ir::get_result_variable(inst).is_some_and(|var| needed.contains(var))
+        }
+    }).collect()
+}
+
+//jumps to a label that directly follows the jump
+fn remove_redundant_jumps(instructions: Vec<IrInstruction>) -> Vec<IrInstruction> {
+    let mut new_instructions = vec![];
+    for (idx, inst) in instructions.iter().enumerate() {
+        if let IrInstruction::Jump(target) = inst {
+            let following_labels = instructions[idx + 1..].iter().map_while(|i| match i {
+                IrInstruction::Label(l) => Some(l),
+                _ => None
+            });
+            if following_labels.into_iter().any(|l| l == target) {
+                continue;
+            }
+        }
+        new_instructions.push(inst.to_owned());
+    }
+    new_instructions
+}
+
+fn remove_unreferenced_labels(instructions: Vec<IrInstruction>) -> Vec<IrInstruction> {
+    let targets: HashSet<String> = instructions.iter().filter_map(|inst| match inst {
+        IrInstruction::Jump(l) | IrInstruction::JumpFalse(_, l) => Some(l.to_owned()),
+        _ => None
+    }).collect();
+    instructions.into_iter().filter(|inst| match inst {
+        IrInstruction::Label(l) => targets.contains(l),
+        _ => true
+    }).collect()
+}
+
+pub fn eliminate_dead_code(mut instructions: Vec<IrInstruction>) -> Vec<IrInstruction> {
+    loop {
+        let mut new_instructions = remove_unreachable(instructions.clone());
+        new_instructions = remove_dead_computations(new_instructions);
+        new_instructions = remove_redundant_jumps(new_instructions);
+        new_instructions = remove_unreferenced_labels(new_instructions);
+        if new_instructions == instructions {
+            return new_instructions;
+        }
+        instructions = new_instructions;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer, parser, ssa};
+
+    fn eliminate(code: &str) -> Vec<IrInstruction> {
+        let prog = ssa::convert(&parser::parse(&mut lexer::lex(code)).unwrap());
+        eliminate_dead_code(ir::transform(&prog.functions[0]))
+    }
+
+    #[test]
+    fn eliminate_unused_computations() {
+        let code = "
+            fun test(a) {
+                let b = a * 2;
+                let c = b + 1;
+                return a;
+            }
+        ";
+        assert_eq!(eliminate(code), [IrInstruction::Return(Data::Variable("#var_a_#0".to_owned()))]);
+    }
+
+    #[test]
+    fn eliminate_keeps_function_calls() {
+        let code = "
+            fun test(a) {
+                let b = abc(a);
+                return 0;
+            }
+        ";
+        assert_eq!(eliminate(code), [
+            IrInstruction::FunctionCall("#var_2".to_owned(), "abc".to_owned(), vec![Data::Variable("#var_a_#0".to_owned())]),
+            IrInstruction::Return(Data::Number(0))
+        ]);
+    }
+
+    #[test]
+    fn eliminate_keeps_possible_division_by_zero() {
+        let code = "
+            fun test(a) {
+                let b = 1 / a;
+                let c = a / 2;
+                return 0;
+            }
+        ";
+        assert_eq!(eliminate(code), [
+            IrInstruction::Division("#var_1".to_owned(), Data::Number(1), Data::Variable("#var_a_#0".to_owned())),
+            IrInstruction::Return(Data::Number(0))
+        ]);
+    }
+
+    #[test]
+    fn eliminate_keeps_possible_division_overflow() {
+        //the divisor is a constant after constant propagation
+        let instructions = vec![
+            IrInstruction::Division("#var_1".to_owned(), Data::Variable("#var_a_#0".to_owned()), Data::Number(-1)),
+            IrInstruction::Modulo("#var_2".to_owned(), Data::Variable("#var_a_#0".to_owned()), Data::Number(-1)),
+            IrInstruction::Division("#var_3".to_owned(), Data::Variable("#var_a_#0".to_owned()), Data::Number(-2)),
+            IrInstruction::Return(Data::Number(0))
+        ];
+        assert_eq!(eliminate_dead_code(instructions), [
+            IrInstruction::Division("#var_1".to_owned(), Data::Variable("#var_a_#0".to_owned()), Data::Number(-1)),
+            IrInstruction::Modulo("#var_2".to_owned(), Data::Variable("#var_a_#0".to_owned()), Data::Number(-1)),
+            IrInstruction::Return(Data::Number(0))
+        ]);
+    }
+
+    #[test]
+    fn eliminate_code_after_return() {
+        let code = "
+            fun test(a) {
+                if(a) {
+                    return 1;
+                    println_num(a);
+                }
+                return 2;
+            }
+        ";
+        let ir = eliminate(code);
+        assert!(!ir.iter().any(|i| matches!(i, IrInstruction::FunctionCall(_, _, _))));
+        assert_eq!(ir.iter().filter(|i| matches!(i, IrInstruction::Label(_))).count(), 1);
+    }
+
+    #[test]
+    fn eliminate_keeps_loop_lifetimes_after_continue() {
+        let code = "
+            fun test(n) {
+                let i = 0;
+                while(i < n) {
+                    i = i + 1;
+                    continue;
+                }
+                return i;
+            }
+        ";
+        let ir = eliminate(code);
+        assert!(ir.contains(&IrInstruction::KeepAlive("#var_i_#0".to_owned())));
+    }
+
+    #[test]
+    fn eliminate_unused_loop_variables() {
+        let code = "
+            fun test(n) {
+                let a = 0;
+                let i = 0;
+                while(i < n) {
+                    a = a + i;
+                    i = i + 1;
+                }
+                return i;
+            }
+        ";
+        let ir = eliminate(code);
+        assert!(!ir.iter().any(|i| ir::get_result_variable(i).is_some_and(|v| v.starts_with("#var_a_"))));
+        assert!(ir.iter().any(|i| ir::get_result_variable(i).is_some_and(|v| v.starts_with("#var_i_"))));
+    }
+}
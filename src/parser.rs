@@ -6,7 +6,8 @@ pub enum ParseError {
     UnexpectedToken(lexer::Token, lexer::Token),
     UnexpectedToken2(Vec<lexer::Token>, lexer::Token),
     TooFewTokens,
-    LexingError(lexer::LexingError)
+    LexingError(lexer::LexingError),
+    UnknownType(String)
 }
 
 impl std::fmt::Display for ParseError {
@@ -20,6 +21,8 @@ impl std::fmt::Display for ParseError {
                 write!(f, "Too few tokens"),
             Self::LexingError(e) =>
             write!(f, "Lexing error: {:?}", e),
+            Self::UnknownType(t) =>
+            write!(f, "Unknown type: {}", t),
         }
     }
 }
@@ -28,9 +31,9 @@ type ParseResult<T> = Result<T, ParseError>;
 
 fn get_token(lex_token: Option<Result<lexer::Token, lexer::LexingError>>) -> ParseResult<lexer::Token> {
     match lex_token {
-        None => return Err(ParseError::TooFewTokens),
+        None => Err(ParseError::TooFewTokens),
         Some(r) => match r {
-            Err(e) => return Err(ParseError::LexingError(e)),
+            Err(e) => Err(ParseError::LexingError(e)),
             Ok(v) => Ok(v)
         }
     }
@@ -38,9 +41,9 @@ fn get_token(lex_token: Option<Result<lexer::Token, lexer::LexingError>>) -> Par
 
 fn get_peak_token(lex_token: Option<&Result<lexer::Token, lexer::LexingError>>) -> ParseResult<lexer::Token> {
     match lex_token {
-        None => return Err(ParseError::TooFewTokens),
+        None => Err(ParseError::TooFewTokens),
         Some(r) => match r {
-            Err(e) => return Err(ParseError::LexingError(e.to_owned())),
+            Err(e) => Err(ParseError::LexingError(e.to_owned())),
             Ok(v) => Ok(v.to_owned())
         }
     }
@@ -53,17 +56,56 @@ pub type VariableName = String;
 pub type Arguments = Vec<Expression>;
 pub type Block = Vec<Statement>;
 
+//types of the optional annotations, values without a type are checked at runtime (or not at all)
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Type {
+    Int,
+    Bool,
+    Char,
+    Float
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Int => write!(f, "int"),
+            Self::Bool => write!(f, "bool"),
+            Self::Char => write!(f, "char"),
+            Self::Float => write!(f, "float"),
+        }
+    }
+}
+
 
 #[derive(Debug, PartialEq)]
 pub struct Assignment {
     pub variable_name: VariableName,
+    pub variable_type: Option<Type>, //let <name>: <type> = <expression>;
+    pub declaration: bool, //let <name> = <expression>; (constants and globals are declarations as well)
+    pub expression: Expression
+}
+
+//<array>[<index>] = <expression>;
+#[derive(Debug, PartialEq)]
+pub struct ArrayAssignment {
+    pub array: Expression,
+    pub index: Expression,
+    pub expression: Expression
+}
+
+//<object>.<field> = <expression>;
+#[derive(Debug, PartialEq)]
+pub struct FieldAssignment {
+    pub object: Expression,
+    pub field: String,
     pub expression: Expression
 }
 
 #[derive(Debug, PartialEq)]
 pub struct IfStatement {
     pub condition: Expression,
-    pub block: Block
+    pub block: Block,
+    pub else_block: Option<Block> //else if is an if statement inside the else block
 }
 
 #[derive(Debug, PartialEq)]
@@ -72,17 +114,39 @@ pub struct WhileLoop {
     pub block: Block
 }
 
+#[derive(Debug, PartialEq)]
+pub struct ForLoop {
+    pub init: Option<Assignment>,
+    pub condition: Expression,
+    pub step: Option<Assignment>, //executed after every iteration and on continue
+    pub block: Block
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct FunctionCall {
     pub name: FunctionIdentifier,
     pub arguments: Arguments
 }
 
+//call of the function reference that the expression evaluates to
+#[derive(Debug, PartialEq, Clone)]
+pub struct IndirectCall {
+    pub function: Box<Expression>,
+    pub arguments: Arguments
+}
+
+//<struct> { <field>: <expression>, ... }, the fields are evaluated in the written order
+#[derive(Debug, PartialEq, Clone)]
+pub struct NewStruct {
+    pub name: String,
+    pub fields: Vec<(String, Expression)>
+}
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Expression {
     Number(i64),
     Variable(VariableName),
+    Global(VariableName), //only created by the ssa conversion, the parser creates variables
     Addition(Box<(Expression, Expression)>),
     Subtraction(Box<(Expression, Expression)>),
     Multiplication(Box<(Expression, Expression)>),
@@ -96,16 +160,54 @@ pub enum Expression {
     NotEquals(Box<(Expression, Expression)>),
     LogicAnd(Box<(Expression, Expression)>),
     LogicOr(Box<(Expression, Expression)>),
+    BitwiseAnd(Box<(Expression, Expression)>),
+    BitwiseOr(Box<(Expression, Expression)>),
+    BitwiseXor(Box<(Expression, Expression)>),
+    ShiftLeft(Box<(Expression, Expression)>),
+    ShiftRight(Box<(Expression, Expression)>), //arithmetic
+    ShiftRightLogical(Box<(Expression, Expression)>),
+    Negation(Box<Expression>),
+    LogicNot(Box<Expression>),
+    BitwiseNot(Box<Expression>),
     FunctionCall(FunctionCall),
+    NewArray(Box<Expression>), //size
+    ArrayLength(Box<Expression>),
+    ArrayAccess(Box<(Expression, Expression)>), //array, index
+    String(String), //address of the string in the read-only data section
+    FunctionReference(FunctionIdentifier), //&<function>
+    IndirectCall(IndirectCall), //only created by the ssa conversion, calls through variables are parsed as function calls
+    Cast(Box<Expression>, Type), //<expression> as <type>, only changes the type not the value
+    Float(f64), //stored as its bits wherever a number can be stored
+    //the float operations are only created by the type check, the parser creates the integer operations
+    FloatAddition(Box<(Expression, Expression)>),
+    FloatSubtraction(Box<(Expression, Expression)>),
+    FloatMultiplication(Box<(Expression, Expression)>),
+    FloatDivision(Box<(Expression, Expression)>),
+    FloatGreater(Box<(Expression, Expression)>),
+    FloatGreaterEquals(Box<(Expression, Expression)>),
+    FloatLess(Box<(Expression, Expression)>),
+    FloatLessEquals(Box<(Expression, Expression)>),
+    FloatEquals(Box<(Expression, Expression)>),
+    FloatNotEquals(Box<(Expression, Expression)>),
+    FloatNegation(Box<Expression>),
+    IntToFloat(Box<Expression>),
+    FloatToInt(Box<Expression>), //truncates, NaN and values out of range become i64::MIN
+    NewStruct(NewStruct),
+    FieldAccess(Box<Expression>, String), //<object>.<field>
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Statement {
     Assignment(Assignment),
+    ArrayAssignment(ArrayAssignment),
+    FieldAssignment(FieldAssignment),
     IfStatement(IfStatement),
     WhileLoop(WhileLoop),
+    ForLoop(ForLoop),
     FunctionCall(FunctionCall),
-    Return(Expression)
+    Return(Expression),
+    Break,
+    Continue
 }
 
 #[derive(Debug, PartialEq)]
@@ -118,12 +220,24 @@ pub enum Function {
 pub struct InternalFunction {
     pub name: FunctionIdentifier,
     pub parameters: Parameters,
+    pub parameter_types: Vec<Option<Type>>,
+    pub return_type: Option<Type>,
     pub block: Block
 }
 
+//struct <name> { <field>, ... }, the fields are untyped like variables
+#[derive(Debug, PartialEq, Clone)]
+pub struct Struct {
+    pub name: String,
+    pub fields: Vec<String>
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Program {
-    pub functions: Vec<Function>
+    pub functions: Vec<Function>,
+    pub constants: Vec<Assignment>, //evaluated at compile time
+    pub globals: Vec<Assignment>, //initialized in declaration order before main is called
+    pub structs: Vec<Struct>
 }
 
 fn get_identifier(token: lexer::Token) -> ParseResult<String>{
@@ -140,22 +254,40 @@ fn check_token(token: lexer::Token, expected_token: lexer::Token)-> ParseResult<
     Ok(())
 }
 
-fn parse_argument(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Parameters>{
+fn parse_type(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Type>{
+    match get_identifier(get_token(lex.next())?)?.as_str() {
+        "int" => Ok(Type::Int),
+        "bool" => Ok(Type::Bool),
+        "char" => Ok(Type::Char),
+        "float" => Ok(Type::Float),
+        t => Err(ParseError::UnknownType(t.to_owned()))
+    }
+}
+
+//optional `<prefix> <type>`, eg. `: int` after a parameter or `-> bool` after the parameter list
+fn parse_type_annotation(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>, prefix: Token) -> ParseResult<Option<Type>>{
+    if get_peak_token(lex.peek())? != prefix {
+        return Ok(None);
+    }
+    lex.next();
+    Ok(Some(parse_type(lex)?))
+}
+
+fn parse_argument(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<(Parameters, Vec<Option<Type>>)>{
 
     let mut parameters: Parameters = Vec::new();
+    let mut parameter_types = Vec::new();
     loop {
         let token = get_peak_token(lex.peek())?;
         match token {
             Token::Identifier(v) => parameters.push(v),
-            _ => return Ok(parameters),
+            _ => return Ok((parameters, parameter_types)),
 
         };
         lex.next();
+        parameter_types.push(parse_type_annotation(lex, Token::Colon)?);
         let res = check_token(get_peak_token(lex.peek())?, Token::Comma);
-        match res {
-            Err(_) => return Ok(parameters),
-            Ok(_) => ()
-        };
+        if res.is_err() { return Ok((parameters, parameter_types)) };
         lex.next();
     }
 }
@@ -167,49 +299,152 @@ fn parse_expression_p1(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Tok
             let mut tmp_lex = lex.clone();
             tmp_lex.next();
             let next_token = get_token(tmp_lex.next())?;
-            return match next_token {
+            match next_token {
                 Token::OpeningRoundBracket => Ok(Expression::FunctionCall(parse_function_call(lex)?)),
+                Token::OpeningCurlyBracket => Ok(Expression::NewStruct(parse_new_struct(lex)?)),
                 _ => {
                     lex.next();
                     Ok(Expression::Variable(id))
                 }
-            };
+            }
         },
         Token::Number(n) => {
             lex.next();
             Ok(Expression::Number(n))
         },
+        //character literals are numbers of type char
+        Token::Character(c) => {
+            lex.next();
+            Ok(Expression::Cast(Box::new(Expression::Number(c)), Type::Char))
+        },
         Token::OpeningRoundBracket => {
             lex.next();
-            let expr = parse_expression_p3(lex)?;
+            let expr = parse_expression(lex)?;
             let closing_token = get_token(lex.next())?;
             if closing_token != Token::ClosingRoundBracket {
                 return Err(ParseError::UnexpectedToken(Token::ClosingRoundBracket, closing_token));
             }
-            return Ok(expr);
+            Ok(expr)
+        },
+        Token::Float(f) => {
+            lex.next();
+            Ok(Expression::Float(f))
+        },
+        Token::String(s) => {
+            lex.next();
+            Ok(Expression::String(s))
         },
-        t => Err(ParseError::UnexpectedToken2(vec![Token::Identifier("".to_owned()), Token::Number(0), Token::OpeningRoundBracket], t))
+        Token::NewArray => {
+            lex.next();
+            Ok(Expression::NewArray(Box::new(parse_builtin_argument(lex)?)))
+        },
+        Token::ArrayLength => {
+            lex.next();
+            Ok(Expression::ArrayLength(Box::new(parse_builtin_argument(lex)?)))
+        },
+        t => Err(ParseError::UnexpectedToken2(vec![Token::Identifier("".to_owned()), Token::Number(0), Token::Float(0.0), Token::String("".to_owned()), Token::OpeningRoundBracket, Token::NewArray, Token::ArrayLength], t))
+    }
+}
+
+//(<expr>) after new_array and len
+fn parse_builtin_argument(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Expression>{
+    check_token(get_token(lex.next())?, Token::OpeningRoundBracket)?;
+    let expr = parse_expression(lex)?;
+    check_token(get_token(lex.next())?, Token::ClosingRoundBracket)?;
+    Ok(expr)
+}
+
+//<struct> { <field>: <expression>, ... }, a trailing comma is allowed
+fn parse_new_struct(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<NewStruct>{
+    let name = get_identifier(get_token(lex.next())?)?;
+    check_token(get_token(lex.next())?, Token::OpeningCurlyBracket)?;
+    let mut fields = Vec::new();
+    while get_peak_token(lex.peek())? != Token::ClosingCurlyBracket {
+        let field = get_identifier(get_token(lex.next())?)?;
+        check_token(get_token(lex.next())?, Token::Colon)?;
+        fields.push((field, parse_expression(lex)?));
+        match get_peak_token(lex.peek())? {
+            Token::Comma => { lex.next(); },
+            Token::ClosingCurlyBracket => (),
+            t => return Err(ParseError::UnexpectedToken2(vec![Token::Comma, Token::ClosingCurlyBracket], t))
+        }
+    }
+    lex.next();
+    Ok(NewStruct { name, fields })
+}
+
+//array indexing and field accesses, eg. a[i][j] or a[i].x
+fn parse_expression_index(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Expression>{
+    let mut expr = parse_expression_p1(lex)?;
+    loop {
+        match get_peak_token(lex.peek())? {
+            Token::OpeningSquareBracket => {
+                lex.next();
+                let index = parse_expression(lex)?;
+                check_token(get_token(lex.next())?, Token::ClosingSquareBracket)?;
+                expr = Expression::ArrayAccess(Box::new((expr, index)));
+            },
+            Token::Dot => {
+                lex.next();
+                expr = Expression::FieldAccess(Box::new(expr), get_identifier(get_token(lex.next())?)?);
+            },
+            _ => return Ok(expr)
+        }
     }
 }
 
+//unary operators and function references
+fn parse_expression_unary(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Expression>{
+    let token = get_peak_token(lex.peek())?;
+    match token {
+        Token::Subtraction => {
+            lex.next();
+            Ok(Expression::Negation(Box::new(parse_expression_unary(lex)?)))
+        },
+        Token::LogicNot => {
+            lex.next();
+            Ok(Expression::LogicNot(Box::new(parse_expression_unary(lex)?)))
+        },
+        Token::BitwiseNot => {
+            lex.next();
+            Ok(Expression::BitwiseNot(Box::new(parse_expression_unary(lex)?)))
+        },
+        Token::BitwiseAnd => {
+            lex.next();
+            Ok(Expression::FunctionReference(get_identifier(get_token(lex.next())?)?))
+        },
+        _ => parse_expression_index(lex)
+    }
+}
+
+//casts bind weaker than unary operators, eg. -a as char is (-a) as char
+fn parse_expression_cast(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Expression>{
+    let mut expr = parse_expression_unary(lex)?;
+    while get_peak_token(lex.peek())? == Token::As {
+        lex.next();
+        expr = Expression::Cast(Box::new(expr), parse_type(lex)?);
+    }
+    Ok(expr)
+}
+
 fn parse_expression_p2(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Expression>{
-    let mut left_side = parse_expression_p1(lex)?;
+    let mut left_side = parse_expression_cast(lex)?;
     loop {
         let token = get_peak_token(lex.peek())?;
         match token {
             Token::Multiplication => {
                 lex.next();
-                let right_side = parse_expression_p1(lex)?;
+                let right_side = parse_expression_cast(lex)?;
                 left_side = Expression::Multiplication(Box::new((left_side, right_side)));
             },
             Token::Division => {
                 lex.next();
-                let right_side = parse_expression_p1(lex)?;
+                let right_side = parse_expression_cast(lex)?;
                 left_side = Expression::Division(Box::new((left_side, right_side)));
             },
             Token::Modulo => {
                 lex.next();
-                let right_side = parse_expression_p1(lex)?;
+                let right_side = parse_expression_cast(lex)?;
                 left_side = Expression::Modulo(Box::new((left_side, right_side)));
             },
             _ => return Ok(left_side)
@@ -237,29 +472,54 @@ fn parse_expression_p3(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Tok
     }
 }
 
-fn parse_expression_p4(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Expression>{
+fn parse_expression_shift(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Expression>{
     let mut left_side = parse_expression_p3(lex)?;
     loop {
         let token = get_peak_token(lex.peek())?;
         match token {
-            Token::Greater => {
+            Token::ShiftLeft => {
+                lex.next();
+                let right_side = parse_expression_p3(lex)?;
+                left_side = Expression::ShiftLeft(Box::new((left_side, right_side)));
+            },
+            Token::ShiftRight => {
                 lex.next();
                 let right_side = parse_expression_p3(lex)?;
+                left_side = Expression::ShiftRight(Box::new((left_side, right_side)));
+            },
+            Token::ShiftRightLogical => {
+                lex.next();
+                let right_side = parse_expression_p3(lex)?;
+                left_side = Expression::ShiftRightLogical(Box::new((left_side, right_side)));
+            },
+            _ => return Ok(left_side)
+        }
+    }
+}
+
+fn parse_expression_p4(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Expression>{
+    let mut left_side = parse_expression_shift(lex)?;
+    loop {
+        let token = get_peak_token(lex.peek())?;
+        match token {
+            Token::Greater => {
+                lex.next();
+                let right_side = parse_expression_shift(lex)?;
                 left_side = Expression::Greater(Box::new((left_side, right_side)));
             },
             Token::GreaterEquals => {
                 lex.next();
-                let right_side = parse_expression_p3(lex)?;
+                let right_side = parse_expression_shift(lex)?;
                 left_side = Expression::GreaterEquals(Box::new((left_side, right_side)));
             },
             Token::Less => {
                 lex.next();
-                let right_side = parse_expression_p3(lex)?;
+                let right_side = parse_expression_shift(lex)?;
                 left_side = Expression::Less(Box::new((left_side, right_side)));
             },
             Token::LessEquals => {
                 lex.next();
-                let right_side = parse_expression_p3(lex)?;
+                let right_side = parse_expression_shift(lex)?;
                 left_side = Expression::LessEquals(Box::new((left_side, right_side)));
             },
             _ => return Ok(left_side)
@@ -288,13 +548,43 @@ fn parse_expression_p5(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Tok
     }
 }
 
+fn parse_expression_bitwise_and(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Expression>{
+    let mut left_side = parse_expression_p5(lex)?;
+    while get_peak_token(lex.peek())? == Token::BitwiseAnd {
+        lex.next();
+        let right_side = parse_expression_p5(lex)?;
+        left_side = Expression::BitwiseAnd(Box::new((left_side, right_side)));
+    }
+    Ok(left_side)
+}
+
+fn parse_expression_bitwise_xor(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Expression>{
+    let mut left_side = parse_expression_bitwise_and(lex)?;
+    while get_peak_token(lex.peek())? == Token::BitwiseXor {
+        lex.next();
+        let right_side = parse_expression_bitwise_and(lex)?;
+        left_side = Expression::BitwiseXor(Box::new((left_side, right_side)));
+    }
+    Ok(left_side)
+}
+
+fn parse_expression_bitwise_or(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Expression>{
+    let mut left_side = parse_expression_bitwise_xor(lex)?;
+    while get_peak_token(lex.peek())? == Token::BitwiseOr {
+        lex.next();
+        let right_side = parse_expression_bitwise_xor(lex)?;
+        left_side = Expression::BitwiseOr(Box::new((left_side, right_side)));
+    }
+    Ok(left_side)
+}
+
 fn parse_expression_p6(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Expression>{
-    let left_side = parse_expression_p5(lex)?;
+    let left_side = parse_expression_bitwise_or(lex)?;
     let token = get_peak_token(lex.peek())?;
     match token {
         Token::LogicAnd => {
             lex.next();
-            let right_side = parse_expression_p5(lex)?;
+            let right_side = parse_expression_bitwise_or(lex)?;
             Ok(Expression::LogicAnd(Box::new((left_side, right_side))))
         },
         _ => Ok(left_side)
@@ -326,7 +616,26 @@ fn parse_if_statement(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Toke
     check_token(get_token(lex.next())?, Token::OpeningCurlyBracket)?;
     let block = parse_block(lex)?;
     check_token(get_token(lex.next())?, Token::ClosingCurlyBracket)?;
-    Ok(Statement::IfStatement(IfStatement { condition, block }))
+    let else_block = parse_else(lex)?;
+    Ok(Statement::IfStatement(IfStatement { condition, block, else_block }))
+}
+
+fn parse_else(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Option<Block>>{
+    if lex.peek() != Some(&Ok(Token::Else)) {
+        return Ok(None);
+    }
+    lex.next();
+    let token = get_peak_token(lex.peek())?;
+    match token {
+        Token::IfStatement => Ok(Some(vec![parse_if_statement(lex)?])),
+        Token::OpeningCurlyBracket => {
+            lex.next();
+            let block = parse_block(lex)?;
+            check_token(get_token(lex.next())?, Token::ClosingCurlyBracket)?;
+            Ok(Some(block))
+        },
+        _ => Err(ParseError::UnexpectedToken2(vec![Token::IfStatement, Token::OpeningCurlyBracket], token))
+    }
 }
 
 fn parse_while_loop(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Statement>{
@@ -340,6 +649,28 @@ fn parse_while_loop(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>
     Ok(Statement::WhileLoop(WhileLoop { condition, block }))
 }
 
+//for(init; condition; step) { block }, init and step are optional assignments, init can be a declaration
+fn parse_for_loop(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Statement>{
+    check_token(get_token(lex.next())?, Token::ForLoop)?;
+    check_token(get_token(lex.next())?, Token::OpeningRoundBracket)?;
+    let init = match get_peak_token(lex.peek())? {
+        Token::Semicolon => None,
+        _ => Some(parse_assignment_or_declaration(lex)?)
+    };
+    check_token(get_token(lex.next())?, Token::Semicolon)?;
+    let condition = parse_expression(lex)?;
+    check_token(get_token(lex.next())?, Token::Semicolon)?;
+    let step = match get_peak_token(lex.peek())? {
+        Token::ClosingRoundBracket => None,
+        _ => Some(parse_assignment_expression(lex)?)
+    };
+    check_token(get_token(lex.next())?, Token::ClosingRoundBracket)?;
+    check_token(get_token(lex.next())?, Token::OpeningCurlyBracket)?;
+    let block = parse_block(lex)?;
+    check_token(get_token(lex.next())?, Token::ClosingCurlyBracket)?;
+    Ok(Statement::ForLoop(ForLoop { init, condition, step, block }))
+}
+
 fn parse_return(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Statement>{
     check_token(get_token(lex.next())?, Token::Return)?;
     let expression = parse_expression(lex)?;
@@ -347,6 +678,17 @@ fn parse_return(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -
     Ok(Statement::Return(expression))
 }
 
+//break; and continue;
+fn parse_loop_control(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Statement>{
+    let statement = match get_token(lex.next())? {
+        Token::Break => Statement::Break,
+        Token::Continue => Statement::Continue,
+        t => return Err(ParseError::UnexpectedToken2(vec![Token::Break, Token::Continue], t))
+    };
+    check_token(get_token(lex.next())?, lexer::Token::Semicolon)?;
+    Ok(statement)
+}
+
 fn parse_arguments(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Arguments>{
     let mut arguments:Arguments = Vec::new();
     loop {
@@ -395,16 +737,21 @@ fn parse_statement(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>
             tmp_lex.next();
             let next_token = get_token(tmp_lex.next())?;
             match next_token {
-                Token::Assignment => return parse_assignment(lex),
+                Token::Assignment | Token::Increment | Token::Decrement => parse_assignment(lex),
+                t if get_compound_operation(&t).is_some() => parse_assignment(lex),
+                Token::OpeningSquareBracket | Token::Dot => parse_element_assignment(lex),
                 Token::OpeningRoundBracket => parse_function_call_statement(lex),
                 //expect assignment or function call
-                _ => return Err(ParseError::UnexpectedToken2(vec![Token::Assignment, Token::OpeningRoundBracket], next_token))
+                _ => Err(ParseError::UnexpectedToken2(vec![Token::Assignment, Token::OpeningSquareBracket, Token::Dot, Token::OpeningRoundBracket], next_token))
             }
         },
+        Token::Let => parse_assignment(lex),
         Token::IfStatement => parse_if_statement(lex),
         Token::WhileLoop => parse_while_loop(lex),
+        Token::ForLoop => parse_for_loop(lex),
         Token::Return => parse_return(lex),
-        _ => return Err(ParseError::UnexpectedToken2(vec![Token::Identifier("".to_owned()), Token::IfStatement, Token::WhileLoop], token))
+        Token::Break | Token::Continue => parse_loop_control(lex),
+        _ => Err(ParseError::UnexpectedToken2(vec![Token::Identifier("".to_owned()), Token::Let, Token::IfStatement, Token::WhileLoop, Token::ForLoop], token))
     }
 }
 
@@ -423,34 +770,139 @@ fn parse_block(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) ->
 }
 
 fn parse_assignment(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Statement>{
-    let variable_name = get_identifier(get_token(lex.next())?)?;
+    let assignment = parse_assignment_or_declaration(lex)?;
+    check_token(get_token(lex.next())?, lexer::Token::Semicolon)?;
+    Ok(Statement::Assignment(assignment))
+}
+
+//assignment to an array element or a field, the last index or field access is the target
+fn parse_element_assignment(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Statement>{
+    let target = parse_expression_index(lex)?;
     check_token(get_token(lex.next())?, lexer::Token::Assignment)?;
     let expression = parse_expression(lex)?;
     check_token(get_token(lex.next())?, lexer::Token::Semicolon)?;
-    Ok(Statement::Assignment(Assignment { variable_name, expression }))
+    match target {
+        Expression::ArrayAccess(b) => {
+            let (array, index) = *b;
+            Ok(Statement::ArrayAssignment(ArrayAssignment { array, index, expression }))
+        },
+        Expression::FieldAccess(object, field) => Ok(Statement::FieldAssignment(FieldAssignment { object: *object, field, expression })),
+        _ => unreachable!("the statement starts with an identifier followed by [ or .")
+    }
+}
+
+type BinaryOperation = fn(Box<(Expression, Expression)>) -> Expression;
+
+//operation of the compound assignment <name> <op>= <expr>
+fn get_compound_operation(token: &Token) -> Option<BinaryOperation> {
+    match token {
+        Token::AdditionAssignment => Some(Expression::Addition),
+        Token::SubtractionAssignment => Some(Expression::Subtraction),
+        Token::MultiplicationAssignment => Some(Expression::Multiplication),
+        Token::DivisionAssignment => Some(Expression::Division),
+        Token::ModuloAssignment => Some(Expression::Modulo),
+        Token::BitwiseAndAssignment => Some(Expression::BitwiseAnd),
+        Token::BitwiseOrAssignment => Some(Expression::BitwiseOr),
+        Token::BitwiseXorAssignment => Some(Expression::BitwiseXor),
+        Token::ShiftLeftAssignment => Some(Expression::ShiftLeft),
+        Token::ShiftRightAssignment => Some(Expression::ShiftRight),
+        Token::ShiftRightLogicalAssignment => Some(Expression::ShiftRightLogical),
+        _ => None
+    }
+}
+
+//assignment without the trailing semicolon, used by for loops.
+//compound assignments and increments are desugared, eg. `a += b` to `a = a + (b)` and `a--` to `a = a - 1`
+fn parse_assignment_expression(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Assignment>{
+    let variable_name = get_identifier(get_token(lex.next())?)?;
+    let variable = Expression::Variable(variable_name.to_owned());
+    let expression = match get_token(lex.next())? {
+        Token::Assignment => parse_expression(lex)?,
+        Token::Increment => Expression::Addition(Box::new((variable, Expression::Number(1)))),
+        Token::Decrement => Expression::Subtraction(Box::new((variable, Expression::Number(1)))),
+        t => match get_compound_operation(&t) {
+            Some(operation) => operation(Box::new((variable, parse_expression(lex)?))),
+            None => return Err(ParseError::UnexpectedToken(Token::Assignment, t))
+        }
+    };
+    Ok(Assignment { variable_name, variable_type: None, declaration: false, expression })
+}
+
+//<name>: <type> = <expr> after let, const or global (the type is optional)
+fn parse_declaration(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Assignment>{
+    let variable_name = get_identifier(get_token(lex.next())?)?;
+    let variable_type = parse_type_annotation(lex, Token::Colon)?;
+    check_token(get_token(lex.next())?, lexer::Token::Assignment)?;
+    let expression = parse_expression(lex)?;
+    Ok(Assignment { variable_name, variable_type, declaration: true, expression })
+}
+
+fn parse_assignment_or_declaration(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Assignment>{
+    match get_peak_token(lex.peek())? {
+        Token::Let => {
+            lex.next();
+            parse_declaration(lex)
+        },
+        _ => parse_assignment_expression(lex)
+    }
 }
 
 fn parse_function(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Function>{
     check_token(get_token(lex.next())?, lexer::Token::Function)?;
     let name = get_identifier(get_token(lex.next())?)?;
     check_token(get_token(lex.next())?, lexer::Token::OpeningRoundBracket)?;
-    let parameters = parse_argument(lex)?;
+    let (parameters, parameter_types) = parse_argument(lex)?;
     check_token(get_token(lex.next())?, lexer::Token::ClosingRoundBracket)?;
+    let return_type = parse_type_annotation(lex, Token::Arrow)?;
     check_token(get_token(lex.next())?, lexer::Token::OpeningCurlyBracket)?;
     let block = parse_block(lex)?;
     check_token(get_token(lex.next())?, lexer::Token::ClosingCurlyBracket)?;
-    Ok(Function::Internal(InternalFunction { name, parameters, block}))
+    Ok(Function::Internal(InternalFunction { name, parameters, parameter_types, return_type, block}))
 }
 
 
 
+//struct <name> { <field>, ... }, a trailing comma is allowed
+fn parse_struct(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Struct>{
+    check_token(get_token(lex.next())?, lexer::Token::Struct)?;
+    let name = get_identifier(get_token(lex.next())?)?;
+    check_token(get_token(lex.next())?, lexer::Token::OpeningCurlyBracket)?;
+    let mut fields = Vec::new();
+    while get_peak_token(lex.peek())? != Token::ClosingCurlyBracket {
+        fields.push(get_identifier(get_token(lex.next())?)?);
+        match get_peak_token(lex.peek())? {
+            Token::Comma => { lex.next(); },
+            Token::ClosingCurlyBracket => (),
+            t => return Err(ParseError::UnexpectedToken2(vec![Token::Comma, Token::ClosingCurlyBracket], t))
+        }
+    }
+    lex.next();
+    Ok(Struct { name, fields })
+}
+
+//const <name> = <expr>; and global <name> = <expr>;
+fn parse_global_declaration(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Assignment>{
+    lex.next();
+    let assignment = parse_declaration(lex)?;
+    check_token(get_token(lex.next())?, lexer::Token::Semicolon)?;
+    Ok(assignment)
+}
+
 pub fn parse(lex: &mut std::iter::Peekable<logos::Lexer<'_, lexer::Token>>) -> ParseResult<Program> {
     let mut functions: Vec<Function> = Vec::new();
+    let mut constants: Vec<Assignment> = Vec::new();
+    let mut globals: Vec<Assignment> = Vec::new();
+    let mut structs: Vec<Struct> = Vec::new();
     loop {
         if lex.peek().is_none() {
-            return Ok(Program {functions: functions});
+            return Ok(Program {functions, constants, globals, structs});
+        }
+        match get_peak_token(lex.peek())? {
+            Token::Constant => constants.push(parse_global_declaration(lex)?),
+            Token::Global => globals.push(parse_global_declaration(lex)?),
+            Token::Struct => structs.push(parse_struct(lex)?),
+            _ => functions.push(parse_function(lex)?)
         }
-        functions.push(parse_function(lex)?);
     }
 }
 
@@ -515,6 +967,13 @@ mod tests {
         assert_eq!(parse_expression(&mut lex), Ok(Expression::Less(Box::new((Expression::Number(1), Expression::Number(2))))))
     }
 
+    #[test]
+    fn parser_expression_parenthesized_comparison() {
+        let code = "(1 < 2) && 3;";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        assert_eq!(parse_expression(&mut lex), Ok(Expression::LogicAnd(Box::new((Expression::Less(Box::new((Expression::Number(1), Expression::Number(2)))), Expression::Number(3))))))
+    }
+
 
     #[test]
     fn parser_prog_1() {
@@ -531,18 +990,118 @@ mod tests {
         assert!(parse(&mut lex).is_ok())
     }
 
+    #[test]
+    fn parser_constants_and_globals() {
+        let code = "
+        const SIZE = 4;
+        fun main(){}
+        global counter = SIZE * 2;
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parse(&mut lex).unwrap();
+        assert_eq!(program.constants, vec![Assignment { variable_name: "SIZE".to_owned(), variable_type: None, declaration: true, expression: Expression::Number(4) }]);
+        let expression = Expression::Multiplication(Box::new((Expression::Variable("SIZE".to_owned()), Expression::Number(2))));
+        assert_eq!(program.globals, vec![Assignment { variable_name: "counter".to_owned(), variable_type: None, declaration: true, expression }]);
+        assert_eq!(program.functions.len(), 1);
+    }
+
+    #[test]
+    fn parser_array_expressions() {
+        let code = "-a[i][len(b)] + new_array(3);";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let inner = Expression::ArrayAccess(Box::new((Expression::Variable("a".to_owned()), Expression::Variable("i".to_owned()))));
+        let access = Expression::ArrayAccess(Box::new((inner, Expression::ArrayLength(Box::new(Expression::Variable("b".to_owned()))))));
+        assert_eq!(parse_expression(&mut lex), Ok(Expression::Addition(Box::new((Expression::Negation(Box::new(access)), Expression::NewArray(Box::new(Expression::Number(3))))))))
+    }
+
+    #[test]
+    fn parser_array_assignment() {
+        let code = "a[i + 1] = 5;";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let index = Expression::Addition(Box::new((Expression::Variable("i".to_owned()), Expression::Number(1))));
+        assert_eq!(parse_statement(&mut lex), Ok(Statement::ArrayAssignment(ArrayAssignment { array: Expression::Variable("a".to_owned()), index, expression: Expression::Number(5) })))
+    }
+
+    #[test]
+    fn parser_structs() {
+        let code = "
+        struct Point { x, y, }
+        fun main(){ let p = Point { x: 1, y: a[0].y }; }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parse(&mut lex).unwrap();
+        assert_eq!(program.structs, vec![Struct { name: "Point".to_owned(), fields: vec!["x".to_owned(), "y".to_owned()] }]);
+        let element = Expression::ArrayAccess(Box::new((Expression::Variable("a".to_owned()), Expression::Number(0))));
+        let fields = vec![("x".to_owned(), Expression::Number(1)), ("y".to_owned(), Expression::FieldAccess(Box::new(element), "y".to_owned()))];
+        let assignment = Statement::Assignment(Assignment { variable_name: "p".to_owned(), variable_type: None, declaration: true, expression: Expression::NewStruct(NewStruct { name: "Point".to_owned(), fields }) });
+        match &program.functions[0] {
+            Function::Internal(f) => assert_eq!(f.block, vec![assignment]),
+            Function::External(_) => unreachable!()
+        }
+    }
+
+    #[test]
+    fn parser_field_assignment() {
+        let code = "p.a[1].b = 5;";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let array = Expression::FieldAccess(Box::new(Expression::Variable("p".to_owned())), "a".to_owned());
+        let object = Expression::ArrayAccess(Box::new((array, Expression::Number(1))));
+        assert_eq!(parse_statement(&mut lex), Ok(Statement::FieldAssignment(FieldAssignment { object, field: "b".to_owned(), expression: Expression::Number(5) })))
+    }
+
+    #[test]
+    fn parser_string_literal() {
+        let code = "print_str(\"a\\n\");";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        assert_eq!(parse_statement(&mut lex), Ok(Statement::FunctionCall(FunctionCall { name: "print_str".to_owned(), arguments: vec![Expression::String("a\n".to_owned())] })))
+    }
+
     #[test]
     fn parser_function_with_args() {
         let code = "fun test(a, b){}";
         let mut lex = lexer::Token::lexer(code).peekable();
-        assert_eq!(parse_function(&mut lex), Ok(Function::Internal(InternalFunction{ name: "test".to_owned(), parameters: vec!["a".to_owned(), "b".to_owned()], block: vec![] })))
+        assert_eq!(parse_function(&mut lex), Ok(Function::Internal(InternalFunction{ name: "test".to_owned(), parameters: vec!["a".to_owned(), "b".to_owned()], parameter_types: vec![None, None], return_type: None, block: vec![] })))
     }
 
     #[test]
     fn parser_function() {
         let code = "fun test(){}";
         let mut lex = lexer::Token::lexer(code).peekable();
-        assert_eq!(parse_function(&mut lex), Ok(Function::Internal(InternalFunction { name: "test".to_owned(), parameters: vec![], block: vec![] })))
+        assert_eq!(parse_function(&mut lex), Ok(Function::Internal(InternalFunction { name: "test".to_owned(), parameters: vec![], parameter_types: vec![], return_type: None, block: vec![] })))
+    }
+
+    #[test]
+    fn parser_type_annotations() {
+        let code = "fun test(a: int, b, c: char) -> bool { let d: bool = a as bool; }";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let expression = Expression::Cast(Box::new(Expression::Variable("a".to_owned())), Type::Bool);
+        let block = vec![Statement::Assignment(Assignment { variable_name: "d".to_owned(), variable_type: Some(Type::Bool), declaration: true, expression })];
+        let parameters = vec!["a".to_owned(), "b".to_owned(), "c".to_owned()];
+        let parameter_types = vec![Some(Type::Int), None, Some(Type::Char)];
+        assert_eq!(parse_function(&mut lex), Ok(Function::Internal(InternalFunction { name: "test".to_owned(), parameters, parameter_types, return_type: Some(Type::Bool), block })))
+    }
+
+    #[test]
+    fn parser_unknown_type() {
+        let code = "fun test(a: string) {}";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        assert_eq!(parse_function(&mut lex), Err(ParseError::UnknownType("string".to_owned())))
+    }
+
+    #[test]
+    fn parser_cast_precedence() {
+        let code = "-a as char + 1;";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let cast = Expression::Cast(Box::new(Expression::Negation(Box::new(Expression::Variable("a".to_owned())))), Type::Char);
+        assert_eq!(parse_expression(&mut lex), Ok(Expression::Addition(Box::new((cast, Expression::Number(1))))))
+    }
+
+    #[test]
+    fn parser_float_literal() {
+        let code = "let x: float = 1.5 * -2.0;";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let expression = Expression::Multiplication(Box::new((Expression::Float(1.5), Expression::Negation(Box::new(Expression::Float(2.0))))));
+        assert_eq!(parse_statement(&mut lex), Ok(Statement::Assignment(Assignment { variable_name: "x".to_owned(), variable_type: Some(Type::Float), declaration: true, expression })))
     }
 
     #[test]
@@ -571,7 +1130,30 @@ mod tests {
     fn parser_if_statement_simple() {
         let code = "if(1){}";
         let mut lex = lexer::Token::lexer(code).peekable();
-        assert_eq!(parse_if_statement(&mut lex), Ok(Statement::IfStatement(IfStatement { condition: Expression::Number(1), block: vec![] })))
+        assert_eq!(parse_if_statement(&mut lex), Ok(Statement::IfStatement(IfStatement { condition: Expression::Number(1), block: vec![], else_block: None })))
+    }
+
+    #[test]
+    fn parser_if_else() {
+        let code = "if(1){} else {a = 2;}";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let else_block = vec![Statement::Assignment(Assignment { variable_name: "a".to_owned(), variable_type: None, declaration: false, expression: Expression::Number(2) })];
+        assert_eq!(parse_if_statement(&mut lex), Ok(Statement::IfStatement(IfStatement { condition: Expression::Number(1), block: vec![], else_block: Some(else_block) })))
+    }
+
+    #[test]
+    fn parser_else_if() {
+        let code = "if(1){} else if(2){} else {}";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let else_if = Statement::IfStatement(IfStatement { condition: Expression::Number(2), block: vec![], else_block: Some(vec![]) });
+        assert_eq!(parse_if_statement(&mut lex), Ok(Statement::IfStatement(IfStatement { condition: Expression::Number(1), block: vec![], else_block: Some(vec![else_if]) })))
+    }
+
+    #[test]
+    fn parser_else_without_block() {
+        let code = "if(1){} else a = 2;";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        assert!(parse_if_statement(&mut lex).is_err())
     }
 
     #[test]
@@ -581,6 +1163,67 @@ mod tests {
         assert_eq!(parse_while_loop(&mut lex), Ok(Statement::WhileLoop(WhileLoop { condition: Expression::Number(1), block: vec![] })))
     }
 
+    #[test]
+    fn parser_break_and_continue() {
+        let code = "while(1){ break; continue; }";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        assert_eq!(parse_while_loop(&mut lex), Ok(Statement::WhileLoop(WhileLoop { condition: Expression::Number(1), block: vec![Statement::Break, Statement::Continue] })))
+    }
+
+    #[test]
+    fn parser_for_loop() {
+        let code = "for(let i = 0; i < 3; i = i + 1){ break; }";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let init = Assignment { variable_name: "i".to_owned(), variable_type: None, declaration: true, expression: Expression::Number(0) };
+        let condition = Expression::Less(Box::new((Expression::Variable("i".to_owned()), Expression::Number(3))));
+        let step = Assignment { variable_name: "i".to_owned(), variable_type: None, declaration: false, expression: Expression::Addition(Box::new((Expression::Variable("i".to_owned()), Expression::Number(1)))) };
+        assert_eq!(parse_for_loop(&mut lex), Ok(Statement::ForLoop(ForLoop { init: Some(init), condition, step: Some(step), block: vec![Statement::Break] })))
+    }
+
+    #[test]
+    fn parser_declarations() {
+        let code = "let a = 1; a = 2; b: int = 3;";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        assert_eq!(parse_statement(&mut lex), Ok(Statement::Assignment(Assignment { variable_name: "a".to_owned(), variable_type: None, declaration: true, expression: Expression::Number(1) })));
+        assert_eq!(parse_statement(&mut lex), Ok(Statement::Assignment(Assignment { variable_name: "a".to_owned(), variable_type: None, declaration: false, expression: Expression::Number(2) })));
+        //only declarations have a type
+        assert_eq!(parse_statement(&mut lex), Err(ParseError::UnexpectedToken2(vec![Token::Assignment, Token::OpeningSquareBracket, Token::Dot, Token::OpeningRoundBracket], Token::Colon)));
+        //the step of a for loop can't declare a variable
+        let code = "for(let i = 0; i < 3; let j = i){}";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        assert!(parse_for_loop(&mut lex).is_err());
+    }
+
+    #[test]
+    fn parser_compound_assignments() {
+        let code = "a *= b + 1; a--; a >>>= 2;";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let a = || Expression::Variable("a".to_owned());
+        let sum = Expression::Addition(Box::new((Expression::Variable("b".to_owned()), Expression::Number(1))));
+        let expected = [
+            Expression::Multiplication(Box::new((a(), sum))),
+            Expression::Subtraction(Box::new((a(), Expression::Number(1)))),
+            Expression::ShiftRightLogical(Box::new((a(), Expression::Number(2))))
+        ];
+        for expression in expected {
+            assert_eq!(parse_statement(&mut lex), Ok(Statement::Assignment(Assignment { variable_name: "a".to_owned(), variable_type: None, declaration: false, expression })));
+        }
+        //increments can be used as step of a for loop, but not in expressions
+        let code = "for(let i = 0; i < 3; i++){}";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        assert!(parse_for_loop(&mut lex).is_ok());
+        let code = "a = i++;";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        assert!(parse_statement(&mut lex).is_err());
+    }
+
+    #[test]
+    fn parser_for_loop_without_init_and_step() {
+        let code = "for(; 1;){}";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        assert_eq!(parse_for_loop(&mut lex), Ok(Statement::ForLoop(ForLoop { init: None, condition: Expression::Number(1), step: None, block: vec![] })))
+    }
+
     #[test]
     fn parser_block() {
         let st1 = "a = 5;";
@@ -588,7 +1231,7 @@ mod tests {
         let mut code = "".to_owned();
         code.push_str(st1);
         code.push_str(st2);
-        code.push_str("}");
+        code.push('}');
         let mut lex = lexer::Token::lexer(&code).peekable();
         let mut st1_lex = lexer::Token::lexer(st1).peekable();
         let mut st2_lex = lexer::Token::lexer(st2).peekable();
@@ -600,9 +1243,9 @@ mod tests {
     #[test]
     fn parser_statement() {
         let code = "b = 6 - 4;";
-        let mut lex = lexer::Token::lexer(&code).peekable();
+        let mut lex = lexer::Token::lexer(code).peekable();
         assert_eq!( parse_statement(&mut lex),
-            Ok(Statement::Assignment(Assignment { variable_name: "b".to_owned(), expression: Expression::Subtraction(Box::new((Expression::Number(6), Expression::Number(4)))) })))
+            Ok(Statement::Assignment(Assignment { variable_name: "b".to_owned(), variable_type: None, declaration: false, expression: Expression::Subtraction(Box::new((Expression::Number(6), Expression::Number(4)))) })))
     }
 
     #[test]
@@ -613,7 +1256,7 @@ mod tests {
         let mut lex = lexer::Token::lexer(&code).peekable();
         let mut expr_lex = lexer::Token::lexer(expr).peekable();
         let expr = parse_expression(&mut expr_lex).unwrap();
-        assert_eq!(parse_assignment(&mut lex), Ok(Statement::Assignment(Assignment { variable_name: "abc".to_owned(), expression: expr })))
+        assert_eq!(parse_assignment(&mut lex), Ok(Statement::Assignment(Assignment { variable_name: "abc".to_owned(), variable_type: None, declaration: false, expression: expr })))
     }
 
     #[test]
@@ -651,6 +1294,53 @@ mod tests {
         assert_eq!(parse_expression(&mut lex), Ok(Expression::Modulo(Box::new((Expression::Number(911), Expression::Number(10))))))
     }
 
+    #[test]
+    fn parser_expression_subtraction_without_spaces() {
+        let code = "a-1;";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        assert_eq!(parse_expression(&mut lex), Ok(Expression::Subtraction(Box::new((Expression::Variable("a".to_owned()), Expression::Number(1))))))
+    }
+
+    #[test]
+    fn parser_expression_bitwise_precedence() {
+        let code = "1 | 2 ^ 3 & 4 == 5;";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let equals = Expression::Equals(Box::new((Expression::Number(4), Expression::Number(5))));
+        let and = Expression::BitwiseAnd(Box::new((Expression::Number(3), equals)));
+        let xor = Expression::BitwiseXor(Box::new((Expression::Number(2), and)));
+        assert_eq!(parse_expression(&mut lex), Ok(Expression::BitwiseOr(Box::new((Expression::Number(1), xor)))))
+    }
+
+    #[test]
+    fn parser_expression_shift_precedence() {
+        let code = "1 << 2 + 3 < 4 >>> 5 >> 6;";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let left = Expression::ShiftLeft(Box::new((Expression::Number(1), Expression::Addition(Box::new((Expression::Number(2), Expression::Number(3)))))));
+        let logical = Expression::ShiftRightLogical(Box::new((Expression::Number(4), Expression::Number(5))));
+        let right = Expression::ShiftRight(Box::new((logical, Expression::Number(6))));
+        assert_eq!(parse_expression(&mut lex), Ok(Expression::Less(Box::new((left, right)))))
+    }
+
+    #[test]
+    fn parser_expression_unary() {
+        let code = "-a * !~2;";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        assert_eq!(parse_expression(&mut lex), Ok(Expression::Multiplication(Box::new((
+            Expression::Negation(Box::new(Expression::Variable("a".to_owned()))),
+            Expression::LogicNot(Box::new(Expression::BitwiseNot(Box::new(Expression::Number(2)))))
+        )))))
+    }
+
+    #[test]
+    fn parser_function_reference() {
+        let code = "f = &add;";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        assert_eq!(parse_statement(&mut lex), Ok(Statement::Assignment(Assignment { variable_name: "f".to_owned(), variable_type: None, declaration: false, expression: Expression::FunctionReference("add".to_owned()) })));
+        let code = "&1;";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        assert_eq!(parse_expression(&mut lex), Err(ParseError::UnexpectedToken(Token::Identifier("".to_owned()), Token::Number(1))))
+    }
+
     #[test]
     fn parser_expression_1() {
         let code = "7 - 5 + 1;";
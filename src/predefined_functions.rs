@@ -1,59 +1,242 @@
+use std::cell::{Cell, RefCell};
 use std::char::from_u32;
+use std::collections::VecDeque;
 use std::io::{self, Write};
+use std::mem;
+use crate::memory::read_string;
 use crate::parser;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct ExternalFunction {
     pub name: String,
     pub parameters: parser::Parameters,
+    pub parameter_types: Vec<Option<parser::Type>>,
+    pub return_type: Option<parser::Type>,
     pub address: u64
 }
 
+type FloatExternal<R> = extern "C" fn(i64, i64, i64, i64, i64, i64, f64, f64, f64, f64, f64, f64, f64, f64) -> R;
+
+impl ExternalFunction {
+    pub fn uses_floats(&self) -> bool {
+        self.return_type == Some(parser::Type::Float) || self.parameter_types.contains(&Some(parser::Type::Float))
+    }
+
+    //float arguments go to xmm0 - xmm7 and the other ones to the general purpose registers (System V),
+    //so every combination of parameters can be passed through the same signature (unused registers are ignored).
+    //None if there are too many arguments of one kind
+    pub fn call_with_floats(&self, args: &[i64]) -> Option<i64> {
+        let mut ints = [0; 6];
+        let mut floats = [0.0; 8];
+        let (mut num_ints, mut num_floats) = (0, 0);
+        for (arg, t) in args.iter().zip(&self.parameter_types) {
+            if *t == Some(parser::Type::Float) {
+                *floats.get_mut(num_floats)? = f64::from_bits(*arg as u64);
+                num_floats += 1;
+            } else {
+                *ints.get_mut(num_ints)? = *arg;
+                num_ints += 1;
+            }
+        }
+        let [i1, i2, i3, i4, i5, i6] = ints;
+        let [f1, f2, f3, f4, f5, f6, f7, f8] = floats;
+        unsafe {
+            let address = self.address as usize;
+            if self.return_type == Some(parser::Type::Float) {
+                let res = mem::transmute::<usize, FloatExternal<f64>>(address)(i1, i2, i3, i4, i5, i6, f1, f2, f3, f4, f5, f6, f7, f8);
+                Some(res.to_bits() as i64)
+            } else {
+                Some(mem::transmute::<usize, FloatExternal<i64>>(address)(i1, i2, i3, i4, i5, i6, f1, f2, f3, f4, f5, f6, f7, f8))
+            }
+        }
+    }
+}
+
+//redirected input / output (used to compare the output of different execution engines)
+struct CapturedIo {
+    input: VecDeque<String>,
+    output: String
+}
+
+thread_local! {
+    static CAPTURED_IO: RefCell<Option<CapturedIo>> = const { RefCell::new(None) };
+}
+
+// Set by the string builtins if their argument isn't a string (they return 0 in this case).
+// The execution engines check it after every call of an external function and stop the program.
+#[repr(C)]
+pub struct InvalidString {
+    pub occured: Cell<i64>,
+    pub value: Cell<i64>
+}
+
+thread_local! {
+    static INVALID_STRING: InvalidString = const { InvalidString { occured: Cell::new(0), value: Cell::new(0) } };
+}
+
+fn invalid_string(s: i64) -> i64 {
+    INVALID_STRING.with(|i| {
+        i.occured.set(1);
+        i.value.set(s);
+    });
+    0
+}
+
+//the value that was passed to a string builtin instead of a string, resets the error
+pub fn take_invalid_string() -> Option<i64> {
+    INVALID_STRING.with(|i| (i.occured.replace(0) != 0).then(|| i.value.get()))
+}
+
+//the generated code reads the error of the thread that compiled it
+pub fn get_invalid_string_address() -> u64 {
+    INVALID_STRING.with(|i| i as *const InvalidString as u64)
+}
+
+#[allow(dead_code)]
+pub fn capture_io<R>(input: &str, f: impl FnOnce() -> R) -> (R, String) {
+    let lines = input.lines().map(|l| l.to_owned()).collect();
+    CAPTURED_IO.with(|c| *c.borrow_mut() = Some(CapturedIo { input: lines, output: String::new() }));
+    let res = f();
+    let captured = CAPTURED_IO.with(|c| c.borrow_mut().take()).unwrap();
+    (res, captured.output)
+}
+
+fn write_output(text: &str) {
+    let captured = CAPTURED_IO.with(|c| {
+        match c.borrow_mut().as_mut() {
+            Some(io) => {
+                io.output.push_str(text);
+                true
+            },
+            None => false
+        }
+    });
+    if !captured {
+        print!("{}", text);
+        io::stdout().flush().unwrap();
+    }
+}
+
+fn read_line() -> String {
+    let captured = CAPTURED_IO.with(|c| c.borrow_mut().as_mut().map(|io| io.input.pop_front().unwrap_or_default()));
+    match captured {
+        Some(line) => line,
+        None => {
+            let mut input_line = String::new();
+            std::io::stdin()
+                .read_line(&mut input_line)
+                .expect("Failed to read line");
+            input_line
+        }
+    }
+}
+
 extern "C" fn cool() -> i64 {
-    println!("cool!");
-    io::stdout().flush().unwrap();
-    return 0;
+    write_output("cool!\n");
+    0
 }
 
 extern "C" fn print_num(i: i64) -> i64 {
-    print!("{}", i);
-    io::stdout().flush().unwrap();
-    return 0;
+    write_output(&format!("{}", i));
+    0
 }
 
 extern "C" fn print_char(c: u32) -> i64 {
-    let _c = from_u32(c).unwrap();
-    print!("{}", _c);
-    io::stdout().flush().unwrap();
-    return 0;
+    let _c = from_u32(c).unwrap_or(char::REPLACEMENT_CHARACTER);
+    write_output(&format!("{}", _c));
+    0
 }
 
 extern "C" fn println_num(i: i64) -> i64 {
-    println!("{}", i);
-    io::stdout().flush().unwrap();
-    return 0;
+    write_output(&format!("{}\n", i));
+    0
 }
 
 extern "C" fn println_char(c: u32) -> i64 {
-    let _c = from_u32(c).unwrap();
-    println!("{}", _c);
-    io::stdout().flush().unwrap();
-    return 0;
+    let _c = from_u32(c).unwrap_or(char::REPLACEMENT_CHARACTER);
+    write_output(&format!("{}\n", _c));
+    0
+}
+
+//strings are passed as the address of an encoded string (see memory::StringTable), other values are reported
+//as invalid strings
+extern "C" fn print_str(s: i64) -> i64 {
+    let bytes = match unsafe { read_string(s) } {
+        Some(bytes) => bytes,
+        None => return invalid_string(s)
+    };
+    write_output(&String::from_utf8_lossy(bytes));
+    0
+}
+
+extern "C" fn println_str(s: i64) -> i64 {
+    let bytes = match unsafe { read_string(s) } {
+        Some(bytes) => bytes,
+        None => return invalid_string(s)
+    };
+    write_output(&format!("{}\n", String::from_utf8_lossy(bytes)));
+    0
+}
+
+extern "C" fn str_len(s: i64) -> i64 {
+    let bytes = match unsafe { read_string(s) } {
+        Some(bytes) => bytes,
+        None => return invalid_string(s)
+    };
+    bytes.len() as i64
+}
+
+//-1 if the index is out of bounds
+extern "C" fn str_char_at(s: i64, index: i64) -> i64 {
+    let bytes = match unsafe { read_string(s) } {
+        Some(bytes) => bytes,
+        None => return invalid_string(s)
+    };
+    match usize::try_from(index).ok().and_then(|i| bytes.get(i)) {
+        Some(c) => *c as i64,
+        None => -1
+    }
+}
+
+extern "C" fn print_float(f: f64) -> i64 {
+    write_output(&format!("{}", f));
+    0
+}
+
+extern "C" fn println_float(f: f64) -> i64 {
+    write_output(&format!("{}\n", f));
+    0
+}
+
+extern "C" fn sqrt(f: f64) -> f64 {
+    f.sqrt()
+}
+
+//the value is reinterpreted (`as float` converts it)
+extern "C" fn float_from_bits(bits: i64) -> f64 {
+    f64::from_bits(bits as u64)
 }
 
 extern "C" fn read_num() -> i64 {
-    let mut input_line = String::new();
-    std::io::stdin()
-        .read_line(&mut input_line)
-        .expect("Failed to read line");
-    return input_line.trim().parse().expect("Input not an integer");
+    let input_line = read_line();
+    input_line.trim().parse().expect("Input not an integer")
 }
 
 pub fn add(program: &mut parser::Program) {
-    program.functions.push(parser::Function::External(ExternalFunction {name: "cool".to_owned(), parameters: vec![], address: cool as u64}));
-    program.functions.push(parser::Function::External(ExternalFunction {name: "print_num".to_owned(), parameters: vec!["num".to_owned()], address: print_num as u64}));
-    program.functions.push(parser::Function::External(ExternalFunction {name: "print_char".to_owned(), parameters: vec!["char".to_owned()], address: print_char as u64}));
-    program.functions.push(parser::Function::External(ExternalFunction {name: "println_num".to_owned(), parameters: vec!["num".to_owned()], address: println_num as u64}));
-    program.functions.push(parser::Function::External(ExternalFunction {name: "println_char".to_owned(), parameters: vec!["char".to_owned()], address: println_char as u64}));
-    program.functions.push(parser::Function::External(ExternalFunction {name: "read_num".to_owned(), parameters: vec![], address: read_num as u64}));
+    use parser::Type::{Char, Float, Int};
+    program.functions.push(parser::Function::External(ExternalFunction {name: "cool".to_owned(), parameters: vec![], parameter_types: vec![], return_type: None, address: cool as *const () as u64}));
+    program.functions.push(parser::Function::External(ExternalFunction {name: "print_num".to_owned(), parameters: vec!["num".to_owned()], parameter_types: vec![Some(Int)], return_type: None, address: print_num as *const () as u64}));
+    program.functions.push(parser::Function::External(ExternalFunction {name: "print_char".to_owned(), parameters: vec!["char".to_owned()], parameter_types: vec![Some(Char)], return_type: None, address: print_char as *const () as u64}));
+    program.functions.push(parser::Function::External(ExternalFunction {name: "println_num".to_owned(), parameters: vec!["num".to_owned()], parameter_types: vec![Some(Int)], return_type: None, address: println_num as *const () as u64}));
+    program.functions.push(parser::Function::External(ExternalFunction {name: "println_char".to_owned(), parameters: vec!["char".to_owned()], parameter_types: vec![Some(Char)], return_type: None, address: println_char as *const () as u64}));
+    program.functions.push(parser::Function::External(ExternalFunction {name: "read_num".to_owned(), parameters: vec![], parameter_types: vec![], return_type: Some(Int), address: read_num as *const () as u64}));
+    program.functions.push(parser::Function::External(ExternalFunction {name: "print_float".to_owned(), parameters: vec!["num".to_owned()], parameter_types: vec![Some(Float)], return_type: None, address: print_float as *const () as u64}));
+    program.functions.push(parser::Function::External(ExternalFunction {name: "println_float".to_owned(), parameters: vec!["num".to_owned()], parameter_types: vec![Some(Float)], return_type: None, address: println_float as *const () as u64}));
+    program.functions.push(parser::Function::External(ExternalFunction {name: "sqrt".to_owned(), parameters: vec!["num".to_owned()], parameter_types: vec![Some(Float)], return_type: Some(Float), address: sqrt as *const () as u64}));
+    program.functions.push(parser::Function::External(ExternalFunction {name: "float_from_bits".to_owned(), parameters: vec!["bits".to_owned()], parameter_types: vec![Some(Int)], return_type: Some(Float), address: float_from_bits as *const () as u64}));
+    //strings (and the bytes read from them) are untyped
+    program.functions.push(parser::Function::External(ExternalFunction {name: "print_str".to_owned(), parameters: vec!["str".to_owned()], parameter_types: vec![None], return_type: None, address: print_str as *const () as u64}));
+    program.functions.push(parser::Function::External(ExternalFunction {name: "println_str".to_owned(), parameters: vec!["str".to_owned()], parameter_types: vec![None], return_type: None, address: println_str as *const () as u64}));
+    program.functions.push(parser::Function::External(ExternalFunction {name: "str_len".to_owned(), parameters: vec!["str".to_owned()], parameter_types: vec![None], return_type: None, address: str_len as *const () as u64}));
+    program.functions.push(parser::Function::External(ExternalFunction {name: "str_char_at".to_owned(), parameters: vec!["str".to_owned(), "index".to_owned()], parameter_types: vec![None, Some(Int)], return_type: None, address: str_char_at as *const () as u64}));
 }
@@ -1,4 +1,6 @@
+use std::collections::HashMap;
 use crate::parser::{self, Expression, FunctionCall, Statement};
+use crate::types;
 
 #[derive(Debug, PartialEq)]
 pub enum SemanticError {
@@ -7,6 +9,19 @@ pub enum SemanticError {
     FunctionArgumentCountMissmatch(String),
     VariableUsedBeforeInit,
     FunctionDoesNotExist(String),
+    BreakOutsideLoop,
+    ContinueOutsideLoop,
+    DuplicateGlobal(String),
+    ShadowedGlobal(String),
+    AssignmentToConstant(String),
+    InvalidConstantExpression(String),
+    DuplicateStruct(String),
+    DuplicateField(String),
+    StructDoesNotExist(String),
+    FieldDoesNotExist(String),
+    MissingField(String),
+    VariableNotDeclared(String),
+    DuplicateVariable(String),
 }
 
 type SemanticResult = Result<(), SemanticError>;
@@ -24,13 +39,39 @@ impl std::fmt::Display for SemanticError {
             write!(f, "function does not exist: {}", fun),
             Self::FunctionArgumentCountMissmatch(fun) =>
             write!(f, "function call has invalid number of arguments: {}", fun),
+            Self::BreakOutsideLoop =>
+            write!(f, "break outside of a loop"),
+            Self::ContinueOutsideLoop =>
+            write!(f, "continue outside of a loop"),
+            Self::DuplicateGlobal(name) =>
+            write!(f, "duplicated constant or global: {}", name),
+            Self::ShadowedGlobal(name) =>
+            write!(f, "parameter or variable shadows a constant or global: {}", name),
+            Self::AssignmentToConstant(name) =>
+            write!(f, "assignment to constant: {}", name),
+            Self::InvalidConstantExpression(name) =>
+            write!(f, "constant can't be evaluated at compile time: {}", name),
+            Self::DuplicateStruct(name) =>
+            write!(f, "duplicated struct: {}", name),
+            Self::DuplicateField(name) =>
+            write!(f, "duplicated field: {}", name),
+            Self::StructDoesNotExist(name) =>
+            write!(f, "struct does not exist: {}", name),
+            Self::FieldDoesNotExist(name) =>
+            write!(f, "field does not exist: {}", name),
+            Self::MissingField(name) =>
+            write!(f, "field is not initialized: {}", name),
+            Self::VariableNotDeclared(name) =>
+            write!(f, "assignment to undeclared variable: {}", name),
+            Self::DuplicateVariable(name) =>
+            write!(f, "variable is already declared: {}", name),
         }
     }
 }
 
 fn check_duplicate_parameters(function: &parser::InternalFunction) -> SemanticResult {
     let mut uniq = std::collections::HashSet::new();
-    if (&function.parameters).into_iter().all(move |x| uniq.insert(x)) {
+    if function.parameters.iter().all(move |x| uniq.insert(x)) {
         Ok(())
     }else{
         Err(SemanticError::DuplicateParameter(function.name.to_owned()))
@@ -42,12 +83,12 @@ fn check_duplicate_functions(program: &parser::Program) -> SemanticResult {
     for function in &program.functions {
         match function {
             parser::Function::Internal(f) => {
-                if uniq.insert(f.name.to_owned()) == false {
+                if !uniq.insert(f.name.to_owned()) {
                     return Err(SemanticError::DuplicateFunction(f.name.to_owned()))
                 }
             }
             parser::Function::External(f) => {
-                if uniq.insert(f.name.to_owned()) == false {
+                if !uniq.insert(f.name.to_owned()) {
                     return Err(SemanticError::DuplicateFunction(f.name.to_owned()))
                 }
             }
@@ -56,10 +97,62 @@ fn check_duplicate_functions(program: &parser::Program) -> SemanticResult {
     Ok(())
 }
 
-fn contains_var(vars: &Vec<Vec<String>>, var: String) -> bool{
+// Names that can hold a function reference (globals, parameters and assigned variables).
+// Calls of globals and of parameters and variables that are visible at the call are indirect calls, other calls are direct calls.
+// The number of parameters is known if every assignment to a variable with the name references functions with the same number of parameters.
+struct FunctionVariables {
+    globals: Vec<String>,
+    num_parameters: HashMap<String, Option<u64>>
+}
+
+impl FunctionVariables {
+    fn is_indirect_call(&self, vars: &[Vec<String>], name: &str) -> bool {
+        self.globals.iter().any(|g| g == name) || (self.num_parameters.contains_key(name) && contains_var(vars, name.to_owned()))
+    }
+}
+
+fn get_assignments<'a>(block: &'a parser::Block, assignments: &mut Vec<&'a parser::Assignment>) {
+    for statement in block {
+        match statement {
+            Statement::Assignment(a) => assignments.push(a),
+            Statement::IfStatement(s) => {
+                get_assignments(&s.block, assignments);
+                if let Some(else_block) = &s.else_block {
+                    get_assignments(else_block, assignments);
+                }
+            },
+            Statement::WhileLoop(l) => get_assignments(&l.block, assignments),
+            Statement::ForLoop(l) => {
+                assignments.extend(l.init.iter().chain(l.step.iter()));
+                get_assignments(&l.block, assignments);
+            },
+            _ => ()
+        }
+    }
+}
+
+fn get_function_variables(declared_function_names_and_arg_count: &[(String, u64)], global_variables: &[String], function: &parser::InternalFunction) -> FunctionVariables {
+    let mut num_parameters: HashMap<String, Option<u64>> = global_variables.iter().chain(function.parameters.iter()).map(|v| (v.to_owned(), None)).collect();
+    let mut assignments = vec![];
+    get_assignments(&function.block, &mut assignments);
+    for assignment in assignments {
+        let n_args = match &assignment.expression {
+            Expression::FunctionReference(name) => declared_function_names_and_arg_count.iter().find(|(n, _)| n == name).map(|(_, n_args)| n_args.to_owned()),
+            _ => None
+        };
+        match num_parameters.get(&assignment.variable_name) {
+            None => { num_parameters.insert(assignment.variable_name.to_owned(), n_args); },
+            Some(known) if *known != n_args => { num_parameters.insert(assignment.variable_name.to_owned(), None); },
+            Some(_) => ()
+        }
+    }
+    FunctionVariables { globals: global_variables.to_vec(), num_parameters }
+}
+
+fn contains_var(vars: &[Vec<String>], var: String) -> bool{
     for stack in vars {
         for v in stack {
-            if v.to_owned() == var {
+            if *v == var {
                 return true;
             }
         }
@@ -67,9 +160,20 @@ fn contains_var(vars: &Vec<Vec<String>>, var: String) -> bool{
     false
 }
 
-fn check_vars_in_expression(vars: &mut Vec<Vec<String>>, expression: &Expression) -> SemanticResult{
+//calls through a global read the global (global initializers can only use the globals that were declared before them)
+fn check_vars_in_function_call(vars: &mut Vec<Vec<String>>, function_variables: &FunctionVariables, function_call: &FunctionCall) -> SemanticResult {
+    if function_variables.globals.contains(&function_call.name) && !contains_var(vars, function_call.name.to_owned()) {
+        return Err(SemanticError::VariableUsedBeforeInit);
+    }
+    for arg in &function_call.arguments {
+        check_vars_in_expression(vars, function_variables, arg)?;
+    }
+    Ok(())
+}
+
+fn check_vars_in_expression(vars: &mut Vec<Vec<String>>, function_variables: &FunctionVariables, expression: &Expression) -> SemanticResult{
     match expression {
-        Expression::Number(_) => Ok(()),
+        Expression::Number(_) | Expression::Float(_) | Expression::Global(_) | Expression::String(_) | Expression::FunctionReference(_) => Ok(()),
         Expression::Variable(v) => {
             if contains_var(vars, v.to_owned()){
                 Ok(())
@@ -77,229 +181,680 @@ fn check_vars_in_expression(vars: &mut Vec<Vec<String>>, expression: &Expression
                 Err(SemanticError::VariableUsedBeforeInit)
             }
         },
-        Expression::FunctionCall(fc) => {
-            for arg in &fc.arguments {
-                check_vars_in_expression(vars, arg)?;
-            }
-            Ok(())
-        },
+        Expression::FunctionCall(fc) => check_vars_in_function_call(vars, function_variables, fc),
         Expression::Addition(b) => {
-            check_vars_in_expression(vars, &b.0)?;
-            check_vars_in_expression(vars, &b.1)?;
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
             Ok(())
         },
         Expression::Subtraction(b) => {
-            check_vars_in_expression(vars, &b.0)?;
-            check_vars_in_expression(vars, &b.1)?;
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
             Ok(())
         },
         Expression::Multiplication(b) => {
-            check_vars_in_expression(vars, &b.0)?;
-            check_vars_in_expression(vars, &b.1)?;
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
             Ok(())
         },
         Expression::Division(b) => {
-            check_vars_in_expression(vars, &b.0)?;
-            check_vars_in_expression(vars, &b.1)?;
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
             Ok(())
         },
         Expression::Modulo(b) => {
-            check_vars_in_expression(vars, &b.0)?;
-            check_vars_in_expression(vars, &b.1)?;
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
             Ok(())
         },
         Expression::Greater(b) => {
-            check_vars_in_expression(vars, &b.0)?;
-            check_vars_in_expression(vars, &b.1)?;
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
             Ok(())
         },
         Expression::GreaterEquals(b) => {
-            check_vars_in_expression(vars, &b.0)?;
-            check_vars_in_expression(vars, &b.1)?;
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
             Ok(())
         },
         Expression::Less(b) => {
-            check_vars_in_expression(vars, &b.0)?;
-            check_vars_in_expression(vars, &b.1)?;
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
             Ok(())
         },
         Expression::LessEquals(b) => {
-            check_vars_in_expression(vars, &b.0)?;
-            check_vars_in_expression(vars, &b.1)?;
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
             Ok(())
         },
         Expression::Equals(b) => {
-            check_vars_in_expression(vars, &b.0)?;
-            check_vars_in_expression(vars, &b.1)?;
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
             Ok(())
         },
         Expression::NotEquals(b) => {
-            check_vars_in_expression(vars, &b.0)?;
-            check_vars_in_expression(vars, &b.1)?;
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
             Ok(())
         },
         Expression::LogicAnd(b) => {
-            check_vars_in_expression(vars, &b.0)?;
-            check_vars_in_expression(vars, &b.1)?;
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
             Ok(())
         },
         Expression::LogicOr(b) => {
-            check_vars_in_expression(vars, &b.0)?;
-            check_vars_in_expression(vars, &b.1)?;
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
+            Ok(())
+        },
+        Expression::BitwiseAnd(b) | Expression::BitwiseOr(b) | Expression::BitwiseXor(b) | Expression::ShiftLeft(b) | Expression::ShiftRight(b) | Expression::ShiftRightLogical(b) => {
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
+            Ok(())
+        },
+        Expression::FloatAddition(b) | Expression::FloatSubtraction(b) | Expression::FloatMultiplication(b) | Expression::FloatDivision(b) |
+        Expression::FloatGreater(b) | Expression::FloatGreaterEquals(b) | Expression::FloatLess(b) | Expression::FloatLessEquals(b) |
+        Expression::FloatEquals(b) | Expression::FloatNotEquals(b) => {
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
+            Ok(())
+        },
+        Expression::Negation(e) | Expression::LogicNot(e) | Expression::BitwiseNot(e) => check_vars_in_expression(vars, function_variables, e),
+        Expression::FloatNegation(e) | Expression::IntToFloat(e) | Expression::FloatToInt(e) => check_vars_in_expression(vars, function_variables, e),
+        Expression::NewArray(e) | Expression::ArrayLength(e) | Expression::Cast(e, _) => check_vars_in_expression(vars, function_variables, e),
+        Expression::ArrayAccess(b) => {
+            check_vars_in_expression(vars, function_variables, &b.0)?;
+            check_vars_in_expression(vars, function_variables, &b.1)?;
+            Ok(())
+        },
+        Expression::IndirectCall(c) => {
+            check_vars_in_expression(vars, function_variables, &c.function)?;
+            for arg in &c.arguments {
+                check_vars_in_expression(vars, function_variables, arg)?;
+            }
+            Ok(())
+        },
+        Expression::NewStruct(n) => {
+            for (_, field) in &n.fields {
+                check_vars_in_expression(vars, function_variables, field)?;
+            }
             Ok(())
         },
+        Expression::FieldAccess(e, _) => check_vars_in_expression(vars, function_variables, e),
+    }
+}
+
+//declarations add the variable to the innermost scope, they can't shadow a visible variable.
+//assignments need a visible variable (the first scope contains the constants and globals)
+fn check_assignment_target(known_vars: &mut [Vec<String>], assignment: &parser::Assignment) -> SemanticResult {
+    let name = &assignment.variable_name;
+    if !assignment.declaration {
+        if contains_var(known_vars, name.to_owned()) {
+            return Ok(());
+        }
+        return Err(SemanticError::VariableNotDeclared(name.to_owned()));
     }
+    if known_vars[0].contains(name) {
+        return Err(SemanticError::ShadowedGlobal(name.to_owned()));
+    }
+    if contains_var(known_vars, name.to_owned()) {
+        return Err(SemanticError::DuplicateVariable(name.to_owned()));
+    }
+    known_vars.last_mut().unwrap().push(name.to_owned());
+    Ok(())
 }
 
-fn check_variable_use_before_init(known_vars: &mut Vec<Vec<String>>, block: &parser::Block) -> SemanticResult {
+fn check_variable_use_before_init(known_vars: &mut Vec<Vec<String>>, function_variables: &FunctionVariables, block: &parser::Block) -> SemanticResult {
     for statement in block {
         match statement {
             parser::Statement::Assignment(v) => {
-                check_vars_in_expression(known_vars, &v.expression)?;
-                let mut vars = known_vars.pop().unwrap();
-                vars.push(v.variable_name.to_owned());
-                known_vars.push(vars);
+                check_vars_in_expression(known_vars, function_variables, &v.expression)?;
+                check_assignment_target(known_vars, v)?;
+            }
+            parser::Statement::ArrayAssignment(a) => {
+                check_vars_in_expression(known_vars, function_variables, &a.array)?;
+                check_vars_in_expression(known_vars, function_variables, &a.index)?;
+                check_vars_in_expression(known_vars, function_variables, &a.expression)?;
+            }
+            parser::Statement::FieldAssignment(a) => {
+                check_vars_in_expression(known_vars, function_variables, &a.object)?;
+                check_vars_in_expression(known_vars, function_variables, &a.expression)?;
             }
             parser::Statement::FunctionCall(f) => {
-                for arg in &f.arguments {
-                    check_vars_in_expression(known_vars, arg)?;
-                }
+                check_vars_in_function_call(known_vars, function_variables, f)?;
             }
             parser::Statement::IfStatement(s) => {
-                check_vars_in_expression(known_vars, &s.condition)?;
+                check_vars_in_expression(known_vars, function_variables, &s.condition)?;
                 known_vars.push(vec![]);
-                check_variable_use_before_init(known_vars, &s.block)?;
+                check_variable_use_before_init(known_vars, function_variables, &s.block)?;
                 known_vars.pop();
+                if let Some(else_block) = &s.else_block {
+                    known_vars.push(vec![]);
+                    check_variable_use_before_init(known_vars, function_variables, else_block)?;
+                    known_vars.pop();
+                }
             }
             parser::Statement::WhileLoop(l) => {
-                check_vars_in_expression(known_vars, &l.condition)?;
+                check_vars_in_expression(known_vars, function_variables, &l.condition)?;
+                known_vars.push(vec![]);
+                check_variable_use_before_init(known_vars, function_variables, &l.block)?;
+                known_vars.pop();
+            }
+            //variables that are declared by the init assignment are only visible in the loop
+            parser::Statement::ForLoop(l) => {
+                known_vars.push(vec![]);
+                if let Some(init) = &l.init {
+                    check_vars_in_expression(known_vars, function_variables, &init.expression)?;
+                    check_assignment_target(known_vars, init)?;
+                }
+                check_vars_in_expression(known_vars, function_variables, &l.condition)?;
                 known_vars.push(vec![]);
-                check_variable_use_before_init(known_vars, &l.block)?;
+                check_variable_use_before_init(known_vars, function_variables, &l.block)?;
+                known_vars.pop();
+                if let Some(step) = &l.step {
+                    check_vars_in_expression(known_vars, function_variables, &step.expression)?;
+                    check_assignment_target(known_vars, step)?;
+                }
                 known_vars.pop();
             }
             parser::Statement::Return(e) => {
-                check_vars_in_expression(known_vars, e)?;
+                check_vars_in_expression(known_vars, function_variables, e)?;
             }
+            parser::Statement::Break | parser::Statement::Continue => ()
         }
     }
     Ok(())
 }
 
-fn check_if_function_exist_in_expression(declared_function_names_and_arg_count: &Vec<(String, u64)>, expression: &Expression) -> SemanticResult {
+fn check_if_function_exist_in_expression(declared_function_names_and_arg_count: &Vec<(String, u64)>, function_variables: &FunctionVariables, vars: &[Vec<String>], expression: &Expression) -> SemanticResult {
     match expression {
-        Expression::Number(_) => Ok(()),
-        Expression::Variable(_) => Ok(()),
+        Expression::Number(_) | Expression::Float(_) | Expression::String(_) => Ok(()),
+        Expression::Variable(_) | Expression::Global(_) => Ok(()),
+        Expression::FunctionReference(name) => {
+            if !declared_function_names_and_arg_count.iter().any(|(n, _)| n == name) {
+                return Err(SemanticError::FunctionDoesNotExist(name.to_owned()));
+            }
+            Ok(())
+        },
         Expression::FunctionCall(fc) => {
-            check_function(declared_function_names_and_arg_count, fc)?;
+            check_function(declared_function_names_and_arg_count, function_variables, vars, fc)?;
             for arg in &fc.arguments {
-                check_if_function_exist_in_expression(declared_function_names_and_arg_count, arg)?;
+                check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, arg)?;
             }
             Ok(())
         },
         Expression::Addition(b) => {
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.0)?;
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.1)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
             Ok(())
         },
         Expression::Subtraction(b) => {
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.0)?;
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.1)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
             Ok(())
         },
         Expression::Multiplication(b) => {
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.0)?;
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.1)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
             Ok(())
         },
         Expression::Division(b) => {
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.0)?;
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.1)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
             Ok(())
         },
         Expression::Modulo(b) => {
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.0)?;
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.1)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
             Ok(())
         },
         Expression::Greater(b) => {
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.0)?;
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.1)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
             Ok(())
         },
         Expression::GreaterEquals(b) => {
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.0)?;
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.1)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
             Ok(())
         },
         Expression::Less(b) => {
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.0)?;
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.1)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
             Ok(())
         },
         Expression::LessEquals(b) => {
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.0)?;
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.1)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
             Ok(())
         },
         Expression::Equals(b) => {
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.0)?;
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.1)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
             Ok(())
         },
         Expression::NotEquals(b) => {
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.0)?;
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.1)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
             Ok(())
         },
         Expression::LogicAnd(b) => {
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.0)?;
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.1)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
             Ok(())
         },
         Expression::LogicOr(b) => {
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.0)?;
-            check_if_function_exist_in_expression(declared_function_names_and_arg_count, &b.1)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
+            Ok(())
+        },
+        Expression::BitwiseAnd(b) | Expression::BitwiseOr(b) | Expression::BitwiseXor(b) | Expression::ShiftLeft(b) | Expression::ShiftRight(b) | Expression::ShiftRightLogical(b) => {
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
+            Ok(())
+        },
+        Expression::FloatAddition(b) | Expression::FloatSubtraction(b) | Expression::FloatMultiplication(b) | Expression::FloatDivision(b) |
+        Expression::FloatGreater(b) | Expression::FloatGreaterEquals(b) | Expression::FloatLess(b) | Expression::FloatLessEquals(b) |
+        Expression::FloatEquals(b) | Expression::FloatNotEquals(b) => {
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
+            Ok(())
+        },
+        Expression::Negation(e) | Expression::LogicNot(e) | Expression::BitwiseNot(e) => {
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, e)
+        },
+        Expression::FloatNegation(e) | Expression::IntToFloat(e) | Expression::FloatToInt(e) => {
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, e)
+        },
+        Expression::NewArray(e) | Expression::ArrayLength(e) | Expression::Cast(e, _) => {
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, e)
+        },
+        Expression::ArrayAccess(b) => {
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.0)?;
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &b.1)?;
             Ok(())
         },
+        Expression::IndirectCall(c) => {
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &c.function)?;
+            for arg in &c.arguments {
+                check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, arg)?;
+            }
+            Ok(())
+        },
+        Expression::NewStruct(n) => {
+            for (_, field) in &n.fields {
+                check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, field)?;
+            }
+            Ok(())
+        },
+        Expression::FieldAccess(e, _) => {
+            check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, e)
+        },
     }
 }
 
 
 
-fn check_if_function_exist_on_call(declared_function_names_and_arg_count: &Vec<(String, u64)>, block: &parser::Block) -> SemanticResult {
+//the visible variables are tracked like in the use before init check
+fn check_if_function_exist_on_call(declared_function_names_and_arg_count: &Vec<(String, u64)>, function_variables: &FunctionVariables, vars: &mut Vec<Vec<String>>, block: &parser::Block) -> SemanticResult {
     for statement in block {
         match statement {
             Statement::FunctionCall(f) => {
-                check_function(declared_function_names_and_arg_count, f)?;
+                check_function(declared_function_names_and_arg_count, function_variables, vars, f)?;
                 for arg in &f.arguments {
-                    check_if_function_exist_in_expression(declared_function_names_and_arg_count, arg)?;
+                    check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, arg)?;
                 }
             },
             Statement::Assignment(a) => {
-                check_if_function_exist_in_expression(declared_function_names_and_arg_count, &a.expression)?;
+                check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &a.expression)?;
+                if a.declaration {
+                    vars.last_mut().unwrap().push(a.variable_name.to_owned());
+                }
+            },
+            Statement::ArrayAssignment(a) => {
+                check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &a.array)?;
+                check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &a.index)?;
+                check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &a.expression)?;
+            },
+            Statement::FieldAssignment(a) => {
+                check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &a.object)?;
+                check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &a.expression)?;
             },
             Statement::IfStatement(s) => {
-                check_if_function_exist_in_expression(declared_function_names_and_arg_count, &s.condition)?;
-                check_if_function_exist_on_call(declared_function_names_and_arg_count, &s.block)?
+                check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &s.condition)?;
+                vars.push(vec![]);
+                check_if_function_exist_on_call(declared_function_names_and_arg_count, function_variables, vars, &s.block)?;
+                vars.pop();
+                if let Some(else_block) = &s.else_block {
+                    vars.push(vec![]);
+                    check_if_function_exist_on_call(declared_function_names_and_arg_count, function_variables, vars, else_block)?;
+                    vars.pop();
+                }
             },
             Statement::WhileLoop(l) => {
-                check_if_function_exist_in_expression(declared_function_names_and_arg_count, &l.condition)?;
-                check_if_function_exist_on_call(declared_function_names_and_arg_count, &l.block)?
+                check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &l.condition)?;
+                vars.push(vec![]);
+                check_if_function_exist_on_call(declared_function_names_and_arg_count, function_variables, vars, &l.block)?;
+                vars.pop();
+            },
+            Statement::ForLoop(l) => {
+                vars.push(vec![]);
+                if let Some(init) = &l.init {
+                    check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &init.expression)?;
+                    if init.declaration {
+                        vars.last_mut().unwrap().push(init.variable_name.to_owned());
+                    }
+                }
+                check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &l.condition)?;
+                vars.push(vec![]);
+                check_if_function_exist_on_call(declared_function_names_and_arg_count, function_variables, vars, &l.block)?;
+                vars.pop();
+                if let Some(step) = &l.step {
+                    check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, &step.expression)?;
+                }
+                vars.pop();
             },
             Statement::Return(e) => {
-                check_if_function_exist_in_expression(declared_function_names_and_arg_count, e)?;
+                check_if_function_exist_in_expression(declared_function_names_and_arg_count, function_variables, vars, e)?;
+            }
+            Statement::Break | Statement::Continue => ()
+        }
+    }
+    Ok(())
+}
+
+fn check_struct_declarations(structs: &Vec<parser::Struct>) -> SemanticResult {
+    let mut uniq = std::collections::HashSet::new();
+    for s in structs {
+        if !uniq.insert(s.name.to_owned()) {
+            return Err(SemanticError::DuplicateStruct(s.name.to_owned()));
+        }
+        let mut fields = std::collections::HashSet::new();
+        if let Some(field) = s.fields.iter().find(|f| !fields.insert(f.to_owned())) {
+            return Err(SemanticError::DuplicateField(field.to_owned()));
+        }
+    }
+    Ok(())
+}
+
+//struct constructions have to initialize every field of the struct exactly once,
+//the type of an object is only known at runtime so accessed fields only have to exist in any struct
+fn check_fields_in_expression(structs: &Vec<parser::Struct>, expression: &Expression) -> SemanticResult {
+    match expression {
+        Expression::Number(_) | Expression::Float(_) | Expression::String(_) => Ok(()),
+        Expression::Variable(_) | Expression::Global(_) | Expression::FunctionReference(_) => Ok(()),
+        Expression::FunctionCall(fc) => {
+            for arg in &fc.arguments {
+                check_fields_in_expression(structs, arg)?;
+            }
+            Ok(())
+        },
+        Expression::Addition(b) | Expression::Subtraction(b) | Expression::Multiplication(b) | Expression::Division(b) |
+        Expression::Modulo(b) | Expression::Greater(b) | Expression::GreaterEquals(b) | Expression::Less(b) |
+        Expression::LessEquals(b) | Expression::Equals(b) | Expression::NotEquals(b) | Expression::LogicAnd(b) |
+        Expression::LogicOr(b) | Expression::BitwiseAnd(b) | Expression::BitwiseOr(b) | Expression::BitwiseXor(b) |
+        Expression::ShiftLeft(b) | Expression::ShiftRight(b) | Expression::ShiftRightLogical(b) | Expression::ArrayAccess(b) |
+        Expression::FloatAddition(b) | Expression::FloatSubtraction(b) | Expression::FloatMultiplication(b) |
+        Expression::FloatDivision(b) | Expression::FloatGreater(b) | Expression::FloatGreaterEquals(b) |
+        Expression::FloatLess(b) | Expression::FloatLessEquals(b) | Expression::FloatEquals(b) | Expression::FloatNotEquals(b) => {
+            check_fields_in_expression(structs, &b.0)?;
+            check_fields_in_expression(structs, &b.1)?;
+            Ok(())
+        },
+        Expression::Negation(e) | Expression::LogicNot(e) | Expression::BitwiseNot(e) => check_fields_in_expression(structs, e),
+        Expression::FloatNegation(e) | Expression::IntToFloat(e) | Expression::FloatToInt(e) => check_fields_in_expression(structs, e),
+        Expression::NewArray(e) | Expression::ArrayLength(e) | Expression::Cast(e, _) => check_fields_in_expression(structs, e),
+        Expression::IndirectCall(c) => {
+            check_fields_in_expression(structs, &c.function)?;
+            for arg in &c.arguments {
+                check_fields_in_expression(structs, arg)?;
             }
+            Ok(())
+        },
+        Expression::NewStruct(n) => {
+            let declaration = match structs.iter().find(|s| s.name == n.name) {
+                Some(s) => s,
+                None => return Err(SemanticError::StructDoesNotExist(n.name.to_owned()))
+            };
+            let mut initialized = std::collections::HashSet::new();
+            for (name, field) in &n.fields {
+                if !declaration.fields.contains(name) {
+                    return Err(SemanticError::FieldDoesNotExist(name.to_owned()));
+                }
+                if !initialized.insert(name.to_owned()) {
+                    return Err(SemanticError::DuplicateField(name.to_owned()));
+                }
+                check_fields_in_expression(structs, field)?;
+            }
+            if let Some(missing) = declaration.fields.iter().find(|f| !initialized.contains(*f)) {
+                return Err(SemanticError::MissingField(missing.to_owned()));
+            }
+            Ok(())
+        },
+        Expression::FieldAccess(e, field) => {
+            if !structs.iter().any(|s| s.fields.contains(field)) {
+                return Err(SemanticError::FieldDoesNotExist(field.to_owned()));
+            }
+            check_fields_in_expression(structs, e)
+        },
+    }
+}
+
+fn check_fields(structs: &Vec<parser::Struct>, block: &parser::Block) -> SemanticResult {
+    for statement in block {
+        match statement {
+            Statement::Assignment(a) => check_fields_in_expression(structs, &a.expression)?,
+            Statement::ArrayAssignment(a) => {
+                check_fields_in_expression(structs, &a.array)?;
+                check_fields_in_expression(structs, &a.index)?;
+                check_fields_in_expression(structs, &a.expression)?;
+            },
+            Statement::FieldAssignment(a) => {
+                if !structs.iter().any(|s| s.fields.contains(&a.field)) {
+                    return Err(SemanticError::FieldDoesNotExist(a.field.to_owned()));
+                }
+                check_fields_in_expression(structs, &a.object)?;
+                check_fields_in_expression(structs, &a.expression)?;
+            },
+            Statement::FunctionCall(f) => {
+                for arg in &f.arguments {
+                    check_fields_in_expression(structs, arg)?;
+                }
+            },
+            Statement::IfStatement(s) => {
+                check_fields_in_expression(structs, &s.condition)?;
+                check_fields(structs, &s.block)?;
+                if let Some(else_block) = &s.else_block {
+                    check_fields(structs, else_block)?;
+                }
+            },
+            Statement::WhileLoop(l) => {
+                check_fields_in_expression(structs, &l.condition)?;
+                check_fields(structs, &l.block)?;
+            },
+            Statement::ForLoop(l) => {
+                for assignment in l.init.iter().chain(l.step.iter()) {
+                    check_fields_in_expression(structs, &assignment.expression)?;
+                }
+                check_fields_in_expression(structs, &l.condition)?;
+                check_fields(structs, &l.block)?;
+            },
+            Statement::Return(e) => check_fields_in_expression(structs, e)?,
+            Statement::Break | Statement::Continue => ()
         }
     }
     Ok(())
 }
 
-fn check_function(declared_function_names_and_arg_count: &Vec<(String, u64)>, function_call: &FunctionCall) -> SemanticResult  {
-    let function = declared_function_names_and_arg_count.iter().find(|(n, _)| n.to_owned() == function_call.name);
+fn check_loop_control(block: &parser::Block, in_loop: bool) -> SemanticResult {
+    for statement in block {
+        match statement {
+            Statement::Break if !in_loop => return Err(SemanticError::BreakOutsideLoop),
+            Statement::Continue if !in_loop => return Err(SemanticError::ContinueOutsideLoop),
+            Statement::IfStatement(s) => {
+                check_loop_control(&s.block, in_loop)?;
+                if let Some(else_block) = &s.else_block {
+                    check_loop_control(else_block, in_loop)?;
+                }
+            },
+            Statement::WhileLoop(l) => check_loop_control(&l.block, true)?,
+            Statement::ForLoop(l) => check_loop_control(&l.block, true)?,
+            _ => ()
+        }
+    }
+    Ok(())
+}
+
+fn check_constant_assignment(constants: &Vec<String>, block: &parser::Block) -> SemanticResult {
+    //declarations with the name of a constant are reported as shadowed globals
+    let check = |assignment: &parser::Assignment| {
+        if !assignment.declaration && constants.contains(&assignment.variable_name) {
+            return Err(SemanticError::AssignmentToConstant(assignment.variable_name.to_owned()));
+        }
+        Ok(())
+    };
+    for statement in block {
+        match statement {
+            Statement::Assignment(a) => check(a)?,
+            Statement::IfStatement(s) => {
+                check_constant_assignment(constants, &s.block)?;
+                if let Some(else_block) = &s.else_block {
+                    check_constant_assignment(constants, else_block)?;
+                }
+            },
+            Statement::WhileLoop(l) => check_constant_assignment(constants, &l.block)?,
+            Statement::ForLoop(l) => {
+                for assignment in l.init.iter().chain(l.step.iter()) {
+                    check(assignment)?;
+                }
+                check_constant_assignment(constants, &l.block)?
+            },
+            _ => ()
+        }
+    }
+    Ok(())
+}
+
+pub type ConstantValues = HashMap<String, i64>;
+
+fn evaluate_constant_binary(constants: &ConstantValues, b: &(Expression, Expression), f: fn(i64, i64) -> Option<i64>) -> Option<i64> {
+    f(evaluate_constant(constants, &b.0)?, evaluate_constant(constants, &b.1)?)
+}
+
+//floats are stored as their bits
+fn evaluate_constant_float(constants: &ConstantValues, b: &(Expression, Expression), f: fn(f64, f64) -> i64) -> Option<i64> {
+    let left = f64::from_bits(evaluate_constant(constants, &b.0)? as u64);
+    let right = f64::from_bits(evaluate_constant(constants, &b.1)? as u64);
+    Some(f(left, right))
+}
+
+//None if the expression can't be evaluated at compile time (variables, function calls, arrays, division by zero)
+fn evaluate_constant(constants: &ConstantValues, expression: &Expression) -> Option<i64> {
+    match expression {
+        Expression::Number(n) => Some(n.to_owned()),
+        Expression::Variable(v) => constants.get(v).copied(),
+        //the address of a string is only known at runtime
+        Expression::Global(_) | Expression::FunctionCall(_) | Expression::String(_) => None,
+        //functions are referenced by their index in the function table
+        Expression::FunctionReference(_) | Expression::IndirectCall(_) => None,
+        //arrays and structs are allocated at runtime
+        Expression::NewArray(_) | Expression::ArrayLength(_) | Expression::ArrayAccess(_) => None,
+        Expression::NewStruct(_) | Expression::FieldAccess(_, _) => None,
+        Expression::Addition(b) => evaluate_constant_binary(constants, b, |a, b| Some(a.wrapping_add(b))),
+        Expression::Subtraction(b) => evaluate_constant_binary(constants, b, |a, b| Some(a.wrapping_sub(b))),
+        Expression::Multiplication(b) => evaluate_constant_binary(constants, b, |a, b| Some(a.wrapping_mul(b))),
+        Expression::Division(b) => evaluate_constant_binary(constants, b, |a, b| if b == 0 { None } else { Some(a.wrapping_div(b)) }),
+        Expression::Modulo(b) => evaluate_constant_binary(constants, b, |a, b| if b == 0 { None } else { Some(a.wrapping_rem(b)) }),
+        Expression::Greater(b) => evaluate_constant_binary(constants, b, |a, b| Some((a > b) as i64)),
+        Expression::GreaterEquals(b) => evaluate_constant_binary(constants, b, |a, b| Some((a >= b) as i64)),
+        Expression::Less(b) => evaluate_constant_binary(constants, b, |a, b| Some((a < b) as i64)),
+        Expression::LessEquals(b) => evaluate_constant_binary(constants, b, |a, b| Some((a <= b) as i64)),
+        Expression::Equals(b) => evaluate_constant_binary(constants, b, |a, b| Some((a == b) as i64)),
+        Expression::NotEquals(b) => evaluate_constant_binary(constants, b, |a, b| Some((a != b) as i64)),
+        //short circuit: the right side doesn't have to be constant if it is never evaluated
+        Expression::LogicAnd(b) => match evaluate_constant(constants, &b.0)? {
+            0 => Some(0),
+            _ => Some((evaluate_constant(constants, &b.1)? != 0) as i64)
+        },
+        Expression::LogicOr(b) => match evaluate_constant(constants, &b.0)? {
+            0 => Some((evaluate_constant(constants, &b.1)? != 0) as i64),
+            _ => Some(1)
+        },
+        Expression::BitwiseAnd(b) => evaluate_constant_binary(constants, b, |a, b| Some(a & b)),
+        Expression::BitwiseOr(b) => evaluate_constant_binary(constants, b, |a, b| Some(a | b)),
+        Expression::BitwiseXor(b) => evaluate_constant_binary(constants, b, |a, b| Some(a ^ b)),
+        Expression::ShiftLeft(b) => evaluate_constant_binary(constants, b, |a, b| Some(a.wrapping_shl(b as u32))),
+        Expression::ShiftRight(b) => evaluate_constant_binary(constants, b, |a, b| Some(a.wrapping_shr(b as u32))),
+        Expression::ShiftRightLogical(b) => evaluate_constant_binary(constants, b, |a, b| Some((a as u64).wrapping_shr(b as u32) as i64)),
+        Expression::Negation(e) => evaluate_constant(constants, e).map(i64::wrapping_neg),
+        Expression::LogicNot(e) => evaluate_constant(constants, e).map(|a| (a == 0) as i64),
+        Expression::BitwiseNot(e) => evaluate_constant(constants, e).map(|a| !a),
+        Expression::Cast(e, _) => evaluate_constant(constants, e),
+        Expression::Float(f) => Some(f.to_bits() as i64),
+        Expression::FloatAddition(b) => evaluate_constant_float(constants, b, |a, b| (a + b).to_bits() as i64),
+        Expression::FloatSubtraction(b) => evaluate_constant_float(constants, b, |a, b| (a - b).to_bits() as i64),
+        Expression::FloatMultiplication(b) => evaluate_constant_float(constants, b, |a, b| (a * b).to_bits() as i64),
+        Expression::FloatDivision(b) => evaluate_constant_float(constants, b, |a, b| (a / b).to_bits() as i64),
+        Expression::FloatGreater(b) => evaluate_constant_float(constants, b, |a, b| (a > b) as i64),
+        Expression::FloatGreaterEquals(b) => evaluate_constant_float(constants, b, |a, b| (a >= b) as i64),
+        Expression::FloatLess(b) => evaluate_constant_float(constants, b, |a, b| (a < b) as i64),
+        Expression::FloatLessEquals(b) => evaluate_constant_float(constants, b, |a, b| (a <= b) as i64),
+        Expression::FloatEquals(b) => evaluate_constant_float(constants, b, |a, b| (a == b) as i64),
+        Expression::FloatNotEquals(b) => evaluate_constant_float(constants, b, |a, b| (a != b) as i64),
+        Expression::FloatNegation(e) => evaluate_constant(constants, e).map(|a| (-f64::from_bits(a as u64)).to_bits() as i64),
+        Expression::IntToFloat(e) => evaluate_constant(constants, e).map(|a| (a as f64).to_bits() as i64),
+        Expression::FloatToInt(e) => evaluate_constant(constants, e).map(|a| types::float_to_int(f64::from_bits(a as u64))),
+    }
+}
+
+//constants can only use constants that were declared before them
+pub fn get_constant_values(program: &parser::Program) -> Result<ConstantValues, SemanticError> {
+    let mut constants = ConstantValues::new();
+    for constant in &program.constants {
+        match evaluate_constant(&constants, &constant.expression) {
+            Some(value) => constants.insert(constant.variable_name.to_owned(), value),
+            None => return Err(SemanticError::InvalidConstantExpression(constant.variable_name.to_owned()))
+        };
+    }
+    Ok(constants)
+}
+
+//global initializers can use every constant and the globals that were declared before them
+fn check_globals(program: &parser::Program, declared_function_names_and_arg_count: &Vec<(String, u64)>) -> SemanticResult {
+    let mut uniq = std::collections::HashSet::new();
+    for global in program.constants.iter().chain(program.globals.iter()) {
+        if !uniq.insert(global.variable_name.to_owned()) {
+            return Err(SemanticError::DuplicateGlobal(global.variable_name.to_owned()))
+        }
+    }
+    get_constant_values(program)?;
+    let global_variables: Vec<String> = program.globals.iter().map(|g| g.variable_name.to_owned()).collect();
+    let function_variables = FunctionVariables { num_parameters: global_variables.iter().map(|g| (g.to_owned(), None)).collect(), globals: global_variables };
+    let mut known_vars = vec![program.constants.iter().map(|c| c.variable_name.to_owned()).collect::<Vec<String>>()];
+    for global in &program.globals {
+        check_vars_in_expression(&mut known_vars, &function_variables, &global.expression)?;
+        check_if_function_exist_in_expression(declared_function_names_and_arg_count, &function_variables, &known_vars, &global.expression)?;
+        check_fields_in_expression(&program.structs, &global.expression)?;
+        known_vars[0].push(global.variable_name.to_owned());
+    }
+    Ok(())
+}
+
+fn check_function(declared_function_names_and_arg_count: &[(String, u64)], function_variables: &FunctionVariables, vars: &[Vec<String>], function_call: &FunctionCall) -> SemanticResult  {
+    //calls through variables are only checked if the number of parameters is known
+    if function_variables.is_indirect_call(vars, &function_call.name) {
+        return match function_variables.num_parameters.get(&function_call.name) {
+            Some(Some(n_args)) if *n_args != function_call.arguments.len() as u64 => {
+                Err(SemanticError::FunctionArgumentCountMissmatch(function_call.name.to_owned()))
+            },
+            _ => Ok(())
+        };
+    }
+    let function = declared_function_names_and_arg_count.iter().find(|(n, _)| *n == function_call.name);
     match function {
         None => Err(SemanticError::FunctionDoesNotExist(function_call.name.to_owned())),
         Some((_, n_args)) => {
-            if n_args.to_owned() != function_call.arguments.len() as u64 {
+            if *n_args != function_call.arguments.len() as u64 {
                 return Err(SemanticError::FunctionArgumentCountMissmatch(function_call.name.to_owned()))
             }
             Ok(())
@@ -315,13 +870,27 @@ pub fn check(program: &parser::Program) -> SemanticResult {
             parser::Function::External(f) => (f.name.to_owned(), f.parameters.len() as u64)
         }
     ).collect();
+    check_struct_declarations(&program.structs)?;
+    check_globals(program, &declared_function_names_and_arg_count)?;
+    let constant_names: Vec<String> = program.constants.iter().map(|c| c.variable_name.to_owned()).collect();
+    let global_names: Vec<String> = program.constants.iter().chain(program.globals.iter()).map(|g| g.variable_name.to_owned()).collect();
+    let global_variables: Vec<String> = program.globals.iter().map(|g| g.variable_name.to_owned()).collect();
     for function in &program.functions {
         match function {
             parser::Function::Internal(f) => {
                 check_duplicate_parameters(f)?;
-                let mut vars = vec![f.parameters.clone()];
-                check_variable_use_before_init(&mut vars, &f.block)?;
-                check_if_function_exist_on_call(&declared_function_names_and_arg_count, &f.block)?;
+                if let Some(p) = f.parameters.iter().find(|p| global_names.contains(p)) {
+                    return Err(SemanticError::ShadowedGlobal(p.to_owned()));
+                }
+                check_constant_assignment(&constant_names, &f.block)?;
+                //assignments to the name of a global change the global
+                let mut vars = vec![global_names.clone(), f.parameters.clone()];
+                let function_variables = get_function_variables(&declared_function_names_and_arg_count, &global_variables, f);
+                check_variable_use_before_init(&mut vars, &function_variables, &f.block)?;
+                let mut vars = vec![global_names.clone(), f.parameters.clone()];
+                check_if_function_exist_on_call(&declared_function_names_and_arg_count, &function_variables, &mut vars, &f.block)?;
+                check_loop_control(&f.block, false)?;
+                check_fields(&program.structs, &f.block)?;
             }
             parser::Function::External(_) => ()
         }
@@ -340,7 +909,7 @@ mod tests {
     fn check_non_existant_function_expression() {
         let code = "
         fun main() {
-            a = 1 && test();
+            let a = 1 && test();
         }
         ";
         let mut lex = lexer::Token::lexer(code).peekable();
@@ -364,7 +933,7 @@ mod tests {
     fn check_use_before_init() {
         let code = "
         fun main() {
-            a = b;
+            let a = b;
         }
         ";
         let mut lex = lexer::Token::lexer(code).peekable();
@@ -372,6 +941,165 @@ mod tests {
         assert!(check(&program).is_err_and(|e| e == SemanticError::VariableUsedBeforeInit))
     }
 
+    #[test]
+    fn check_use_of_if_variable_in_else() {
+        let code = "
+        fun main(a) {
+            if(a) {
+                let b = 1;
+            } else {
+                a = b;
+            }
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert!(check(&program).is_err_and(|e| e == SemanticError::VariableUsedBeforeInit))
+    }
+
+    #[test]
+    fn check_use_of_for_body_variable_in_step() {
+        let code = "
+        fun main(a) {
+            for(let i = 0; i < a; i = b) {
+                let b = i + 1;
+            }
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert!(check(&program).is_err_and(|e| e == SemanticError::VariableUsedBeforeInit))
+    }
+
+    #[test]
+    fn check_array_assignment_to_undefined_array() {
+        let code = "
+        fun main() {
+            a[0] = 1;
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert!(check(&program).is_err_and(|e| e == SemanticError::VariableUsedBeforeInit))
+    }
+
+    #[test]
+    fn check_constant_array() {
+        let code = "
+        const A = new_array(2);
+        fun main() {}
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert!(check(&program).is_err_and(|e| e == SemanticError::InvalidConstantExpression("A".to_owned())))
+    }
+
+    #[test]
+    fn check_assignment_to_constant() {
+        let code = "
+        const A = 1;
+        fun main() {
+            for(A = 0; A < 2; A = A + 1) {}
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert!(check(&program).is_err_and(|e| e == SemanticError::AssignmentToConstant("A".to_owned())))
+    }
+
+    #[test]
+    fn check_parameter_shadows_global() {
+        let code = "
+        global a = 1;
+        fun main(a) {
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert!(check(&program).is_err_and(|e| e == SemanticError::ShadowedGlobal("a".to_owned())))
+    }
+
+    #[test]
+    fn check_duplicate_global() {
+        let code = "
+        const a = 1;
+        global a = 2;
+        fun main() {
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert!(check(&program).is_err_and(|e| e == SemanticError::DuplicateGlobal("a".to_owned())))
+    }
+
+    #[test]
+    fn check_constant_uses_global() {
+        let code = "
+        global a = 1;
+        const B = a + 1;
+        fun main() {
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert!(check(&program).is_err_and(|e| e == SemanticError::InvalidConstantExpression("B".to_owned())))
+    }
+
+    #[test]
+    fn check_constant_values() {
+        let code = "
+        const A = 6;
+        const B = A * 7 + (0 && 1 / 0);
+        fun main() {
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert_eq!(get_constant_values(&program), Ok(ConstantValues::from([("A".to_owned(), 6), ("B".to_owned(), 42)])))
+    }
+
+    #[test]
+    fn check_global_used_before_declaration() {
+        let code = "
+        global a = b;
+        global b = 1;
+        fun main() {
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert!(check(&program).is_err_and(|e| e == SemanticError::VariableUsedBeforeInit))
+    }
+
+    #[test]
+    fn check_break_outside_loop() {
+        let code = "
+        fun main(a) {
+            while(a) {
+                break;
+            }
+            if(a) {
+                break;
+            }
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert!(check(&program).is_err_and(|e| e == SemanticError::BreakOutsideLoop))
+    }
+
+    #[test]
+    fn check_continue_outside_loop() {
+        let code = "
+        fun main(a) {
+            continue;
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert!(check(&program).is_err_and(|e| e == SemanticError::ContinueOutsideLoop))
+    }
+
     #[test]
     fn check_duplicate_parameter() {
         let code = "
@@ -394,6 +1122,96 @@ mod tests {
         assert!(check(&program).is_err_and(|e| e == SemanticError::DuplicateFunction("test1".to_owned())))
     }
 
+    #[test]
+    fn check_function_reference_arguments() {
+        let code = "
+        fun add(a, b) {}
+        fun main(g) {
+            let f = &add;
+            g(1);
+            f(1);
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert!(check(&program).is_err_and(|e| e == SemanticError::FunctionArgumentCountMissmatch("f".to_owned())))
+    }
+
+    #[test]
+    fn check_function_reference_unknown_arguments() {
+        let code = "
+        fun add(a, b) {}
+        fun neg(a) {}
+        fun main(a) {
+            let f = &add;
+            if(a) {
+                f = &neg;
+            }
+            return f(1);
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert_eq!(check(&program), Ok(()))
+    }
+
+    #[test]
+    fn check_function_variable_used_before_init() {
+        let code = "
+        fun add(a, b) {}
+        global f = g(1, 2);
+        global g = &add;
+        fun main() {}
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert!(check(&program).is_err_and(|e| e == SemanticError::VariableUsedBeforeInit))
+    }
+
+    #[test]
+    fn check_calls_of_variables_that_are_not_visible() {
+        let code = "
+        fun add(a, b) {}
+        fun main() {
+            add(1, 2);
+            if(1) {
+                let add = &add;
+                add(1, 2);
+            }
+            return add(1, 2);
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert_eq!(check(&program), Ok(()));
+
+        let code = "
+        fun f(x) { return x + 1; }
+        fun main() {
+            let r = f(1);
+            if (r > 0) {
+                let f = 2;
+                r = r + f;
+            }
+            return r;
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert_eq!(check(&program), Ok(()));
+    }
+
+    #[test]
+    fn check_reference_to_non_existant_function() {
+        let code = "
+        global f = &test;
+        fun main() {}
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert!(check(&program).is_err_and(|e| e == SemanticError::FunctionDoesNotExist("test".to_owned())))
+    }
+
     #[test]
     fn check_wrong_number_arguments_function() {
         let code = "
@@ -407,4 +1225,86 @@ mod tests {
         assert!(check(&program).is_err_and(|e| e == SemanticError::FunctionArgumentCountMissmatch("test1".to_owned())))
     }
 
+
+    #[test]
+    fn check_struct_fields() {
+        let code = "
+        struct Point { x, y }
+        global origin = Point { y: 0, x: 0 };
+        fun main() {
+            let p = Point { x: 1, y: origin.y };
+            p.x = p.y;
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert_eq!(check(&program), Ok(()));
+    }
+
+    #[test]
+    fn check_invalid_struct_fields() {
+        let cases = [
+            ("struct A { x } struct A { y } fun main() {}", SemanticError::DuplicateStruct("A".to_owned())),
+            ("struct A { x, x } fun main() {}", SemanticError::DuplicateField("x".to_owned())),
+            ("fun main() { let a = A { x: 1 }; }", SemanticError::StructDoesNotExist("A".to_owned())),
+            ("struct A { x } fun main() { let a = A { x: 1, y: 2 }; }", SemanticError::FieldDoesNotExist("y".to_owned())),
+            ("struct A { x } fun main() { let a = A { x: 1, x: 2 }; }", SemanticError::DuplicateField("x".to_owned())),
+            ("struct A { x, y } fun main() { let a = A { x: 1 }; }", SemanticError::MissingField("y".to_owned())),
+            ("struct A { x } fun main() { let a = A { x: 1 }; return a.y; }", SemanticError::FieldDoesNotExist("y".to_owned())),
+            ("struct A { x } fun main() { let a = A { x: 1 }; a.y = 2; }", SemanticError::FieldDoesNotExist("y".to_owned())),
+            ("struct A { x } fun main() { let a = A { x: b }; }", SemanticError::VariableUsedBeforeInit),
+        ];
+        for (code, error) in cases {
+            let mut lex = lexer::Token::lexer(code).peekable();
+            let program = parser::parse(&mut lex).unwrap();
+            assert_eq!(check(&program), Err(error), "{}", code);
+        }
+    }
+
+    #[test]
+    fn check_block_scoped_declarations() {
+        //variables of finished blocks and for loops can be declared again
+        let code = "
+        global g = 0;
+        fun main(a) {
+            if(a) {
+                let b = 1;
+                a = b;
+            } else {
+                let b = 2;
+                g = b;
+            }
+            for(let i = 0; i < a; i = i + 1) {
+                let b = i;
+            }
+            let i = 0;
+            let b = i;
+            return b;
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let program = parser::parse(&mut lex).unwrap();
+        assert_eq!(check(&program), Ok(()));
+    }
+
+    #[test]
+    fn check_invalid_declarations() {
+        let cases = [
+            ("fun main() { a = 1; }", SemanticError::VariableNotDeclared("a".to_owned())),
+            ("fun main() { if(1) { let a = 1; } a = 2; }", SemanticError::VariableNotDeclared("a".to_owned())),
+            ("fun main() { for(let i = 0; i < 2; i = i + 1) {} i = 2; }", SemanticError::VariableNotDeclared("i".to_owned())),
+            ("fun main() { let a = 1; let a = 2; }", SemanticError::DuplicateVariable("a".to_owned())),
+            ("fun main() { let a = 1; while(a) { let a = 2; } }", SemanticError::DuplicateVariable("a".to_owned())),
+            ("fun main(a) { let a = 2; }", SemanticError::DuplicateVariable("a".to_owned())),
+            ("global g = 1; fun main() { let g = 2; }", SemanticError::ShadowedGlobal("g".to_owned())),
+            ("const C = 1; fun main() { for(let C = 0; C < 2;) {} }", SemanticError::ShadowedGlobal("C".to_owned())),
+            ("fun main() { let a = a; }", SemanticError::VariableUsedBeforeInit),
+        ];
+        for (code, error) in cases {
+            let mut lex = lexer::Token::lexer(code).peekable();
+            let program = parser::parse(&mut lex).unwrap();
+            assert_eq!(check(&program), Err(error), "{}", code);
+        }
+    }
+
 }
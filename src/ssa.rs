@@ -1,20 +1,60 @@
 //single static assignment
 
 use std::collections::HashMap;
-use itertools::{self, Itertools};
-use crate::{parser::{self, VariableName}, predefined_functions::ExternalFunction};
+use itertools::Itertools;
+use crate::{parser::{self, VariableName}, predefined_functions::ExternalFunction, semantic};
+
+//function that assigns the initial values of the globals, it is called before main
+pub const GLOBALS_INIT_FUNCTION: &str = "#init_globals";
 
 struct VariableTracker {
-    vars: HashMap<String, u64>
+    vars: HashMap<String, u64>, //current version
+    counters: HashMap<String, u64>, //last version that was created
+    scopes: Vec<Vec<String>>, //variables that are visible in the current block (same rules as the semantic check)
+    constants: semantic::ConstantValues, //replaced by their value
+    globals: Vec<VariableName>, //not versioned, every access goes to memory
+    strings: Vec<String> //string literals of the program (without duplicates)
 }
 
 impl VariableTracker {
-    pub fn new() -> Self {
+    pub fn new(constants: semantic::ConstantValues, globals: Vec<VariableName>) -> Self {
         VariableTracker {
-            vars: std::collections::HashMap::new()
+            vars: std::collections::HashMap::new(),
+            counters: std::collections::HashMap::new(),
+            scopes: vec![],
+            constants,
+            globals,
+            strings: vec![]
+        }
+    }
+
+    pub fn is_global(&self, name: &str) -> bool {
+        self.globals.iter().any(|g| g == name)
+    }
+
+    //calls of variables (same rules as the semantic check) are indirect calls
+    pub fn is_function_variable(&self, name: &str) -> bool {
+        self.is_visible(name) || self.is_global(name)
+    }
+
+    pub fn add_string(&mut self, s: &String) {
+        if !self.strings.contains(s) {
+            self.strings.push(s.to_owned());
         }
     }
 
+    pub fn push_scope(&mut self) {
+        self.scopes.push(vec![]);
+    }
+
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn is_visible(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.iter().any(|v| v == name))
+    }
+
     pub fn get_current(&mut self, name: &str) -> String {
         let num = match self.vars.get(name) {
             Some(n) => {
@@ -22,13 +62,18 @@ impl VariableTracker {
             },
             None => {
                 self.vars.insert(name.to_owned(), 0);
+                self.counters.insert(name.to_owned(), 0);
                 0
             },
         };
         format!("#var_{}_#{}",name, num)
     }
 
+    //only returns variables that are visible in the current block
     pub fn get_current_optinal(&mut self, name: &str) -> Option<String> {
+        if !self.is_visible(name) {
+            return None;
+        }
         let num = match self.vars.get(name) {
             Some(n) => {
                 n.to_owned()
@@ -38,8 +83,17 @@ impl VariableTracker {
         Some(format!("#var_{}_#{}",name, num))
     }
 
+    //the else block starts with the versions from before the if statement
+    pub fn get_versions(&self) -> HashMap<String, u64> {
+        self.vars.clone()
+    }
+
+    pub fn set_versions(&mut self, versions: HashMap<String, u64>) {
+        self.vars = versions;
+    }
+
     pub fn get_new(&mut self, name: &str) -> String {
-        let num = match self.vars.get(name) {
+        let num = match self.counters.get(name) {
             Some(n) => {
                 n.to_owned() + 1
             },
@@ -48,6 +102,12 @@ impl VariableTracker {
             },
         };
         self.vars.insert(name.to_owned(), num);
+        self.counters.insert(name.to_owned(), num);
+        if !self.is_visible(name) {
+            if let Some(scope) = self.scopes.last_mut() {
+                scope.push(name.to_owned());
+            }
+        }
         format!("#var_{}_#{}", name, num)
     }
 }
@@ -55,11 +115,28 @@ impl VariableTracker {
 fn convert_expression(expression: &parser::Expression, var_tracker: &mut VariableTracker) -> parser::Expression {
     match expression {
         parser::Expression::Number(n) => parser::Expression::Number(n.to_owned()),
-        parser::Expression::Variable(v) => parser::Expression::Variable(var_tracker.get_current(v)),
+        parser::Expression::Variable(v) => match var_tracker.constants.get(v) {
+            Some(value) => parser::Expression::Number(value.to_owned()),
+            None if var_tracker.is_global(v) => parser::Expression::Global(v.to_owned()),
+            None => parser::Expression::Variable(var_tracker.get_current(v))
+        },
+        parser::Expression::Global(v) => parser::Expression::Global(v.to_owned()),
+        parser::Expression::String(s) => {
+            var_tracker.add_string(s);
+            parser::Expression::String(s.to_owned())
+        },
+        parser::Expression::FunctionCall(fc) if var_tracker.is_function_variable(&fc.name) => {
+            parser::Expression::IndirectCall(convert_indirect_call(fc, var_tracker))
+        },
         parser::Expression::FunctionCall(fc) => {
             let new_arguments = fc.arguments.iter().map(|arg| convert_expression(arg, var_tracker)).collect();
             parser::Expression::FunctionCall(parser::FunctionCall{name: fc.name.to_owned(), arguments: new_arguments})
         },
+        parser::Expression::FunctionReference(f) => parser::Expression::FunctionReference(f.to_owned()),
+        parser::Expression::IndirectCall(c) => {
+            let new_arguments = c.arguments.iter().map(|arg| convert_expression(arg, var_tracker)).collect();
+            parser::Expression::IndirectCall(parser::IndirectCall{function: Box::new(convert_expression(&c.function, var_tracker)), arguments: new_arguments})
+        },
         parser::Expression::Addition(b) => {
             parser::Expression::Addition(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
         },
@@ -99,112 +176,203 @@ fn convert_expression(expression: &parser::Expression, var_tracker: &mut Variabl
         parser::Expression::LogicOr(b) => {
             parser::Expression::LogicOr(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
         },
+        parser::Expression::BitwiseAnd(b) => {
+            parser::Expression::BitwiseAnd(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::BitwiseOr(b) => {
+            parser::Expression::BitwiseOr(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::BitwiseXor(b) => {
+            parser::Expression::BitwiseXor(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::ShiftLeft(b) => {
+            parser::Expression::ShiftLeft(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::ShiftRight(b) => {
+            parser::Expression::ShiftRight(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::ShiftRightLogical(b) => {
+            parser::Expression::ShiftRightLogical(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::Negation(e) => parser::Expression::Negation(Box::new(convert_expression(e, var_tracker))),
+        parser::Expression::LogicNot(e) => parser::Expression::LogicNot(Box::new(convert_expression(e, var_tracker))),
+        parser::Expression::BitwiseNot(e) => parser::Expression::BitwiseNot(Box::new(convert_expression(e, var_tracker))),
+        parser::Expression::NewArray(e) => parser::Expression::NewArray(Box::new(convert_expression(e, var_tracker))),
+        parser::Expression::ArrayLength(e) => parser::Expression::ArrayLength(Box::new(convert_expression(e, var_tracker))),
+        parser::Expression::ArrayAccess(b) => {
+            parser::Expression::ArrayAccess(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        //casts don't change the value, the types are only used by the type checker
+        parser::Expression::Cast(e, _) => convert_expression(e, var_tracker),
+        parser::Expression::Float(f) => parser::Expression::Float(f.to_owned()),
+        parser::Expression::FloatAddition(b) => {
+            parser::Expression::FloatAddition(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::FloatSubtraction(b) => {
+            parser::Expression::FloatSubtraction(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::FloatMultiplication(b) => {
+            parser::Expression::FloatMultiplication(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::FloatDivision(b) => {
+            parser::Expression::FloatDivision(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::FloatGreater(b) => {
+            parser::Expression::FloatGreater(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::FloatGreaterEquals(b) => {
+            parser::Expression::FloatGreaterEquals(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::FloatLess(b) => {
+            parser::Expression::FloatLess(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::FloatLessEquals(b) => {
+            parser::Expression::FloatLessEquals(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::FloatEquals(b) => {
+            parser::Expression::FloatEquals(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::FloatNotEquals(b) => {
+            parser::Expression::FloatNotEquals(Box::new((convert_expression(&b.0, var_tracker), convert_expression(&b.1, var_tracker))))
+        },
+        parser::Expression::FloatNegation(e) => parser::Expression::FloatNegation(Box::new(convert_expression(e, var_tracker))),
+        parser::Expression::IntToFloat(e) => parser::Expression::IntToFloat(Box::new(convert_expression(e, var_tracker))),
+        parser::Expression::FloatToInt(e) => parser::Expression::FloatToInt(Box::new(convert_expression(e, var_tracker))),
+        parser::Expression::NewStruct(n) => {
+            let fields = n.fields.iter().map(|(field, e)| (field.to_owned(), convert_expression(e, var_tracker))).collect();
+            parser::Expression::NewStruct(parser::NewStruct { name: n.name.to_owned(), fields })
+        },
+        parser::Expression::FieldAccess(e, field) => parser::Expression::FieldAccess(Box::new(convert_expression(e, var_tracker)), field.to_owned()),
     }
 }
 
+//the variable is read before the arguments are evaluated
+fn convert_indirect_call(function_call: &parser::FunctionCall, var_tracker: &mut VariableTracker) -> parser::IndirectCall {
+    let function = convert_expression(&parser::Expression::Variable(function_call.name.to_owned()), var_tracker);
+    let new_arguments = function_call.arguments.iter().map(|arg| convert_expression(arg, var_tracker)).collect();
+    parser::IndirectCall { function: Box::new(function), arguments: new_arguments }
+}
+
 fn get_assigned_variables_in_block(block: &parser::Block, var_tracker: &mut VariableTracker) -> Vec<VariableName> {
     let mut vars = vec![];
     for statement in block {
         match statement {
             parser::Statement::Assignment(a) => {
-                match var_tracker.get_current_optinal(&a.variable_name) {
-                    Some(_) => vars.push(a.variable_name.to_owned()),
-                    None => ()
-                }
+                if var_tracker.get_current_optinal(&a.variable_name).is_some() { vars.push(a.variable_name.to_owned()) }
 
             },
-            parser::Statement::FunctionCall(_) => (),
+            //only changes the array or struct, the variable still references the same object
+            parser::Statement::FunctionCall(_) | parser::Statement::ArrayAssignment(_) | parser::Statement::FieldAssignment(_) => (),
             parser::Statement::IfStatement(s) => {
                 vars.append(&mut get_assigned_variables_in_block(&s.block, var_tracker));
+                if let Some(else_block) = &s.else_block {
+                    vars.append(&mut get_assigned_variables_in_block(else_block, var_tracker));
+                }
             },
             parser::Statement::WhileLoop(l) => {
                 vars.append(&mut get_assigned_variables_in_block(&l.block, var_tracker));
             },
-            parser::Statement::Return(_) => ()
+            parser::Statement::ForLoop(l) => {
+                for assignment in l.init.iter().chain(l.step.iter()) {
+                    if var_tracker.get_current_optinal(&assignment.variable_name).is_some() {
+                        vars.push(assignment.variable_name.to_owned());
+                    }
+                }
+                vars.append(&mut get_assigned_variables_in_block(&l.block, var_tracker));
+            },
+            parser::Statement::Return(_) | parser::Statement::Break | parser::Statement::Continue => ()
 
         }
     }
     vars
 }
 
-fn get_ref_vars_expression(expression: &parser::Expression) -> Vec<String>{
+//variables of the innermost loop that are assigned in the loop and their version that is read by the condition
+type LoopVariables = Vec<(VariableName, VariableName)>;
 
-    let mut ref_vars = vec![];
+//break and continue leave the block with the current versions -> update the versions that are read by the condition
+fn get_loop_exit_nodes(loop_vars: Option<&LoopVariables>, var_tracker: &mut VariableTracker) -> LoopPhiNodes {
+    loop_vars.into_iter().flatten().map(|(var, condition_var)| LoopPhiNode {
+        condition_var: condition_var.to_owned(),
+        inner_var: var_tracker.get_current(var)
+    }).collect()
+}
 
-    match expression {
-        parser::Expression::Number(_) => (),
-        parser::Expression::Variable(v) => {
-            ref_vars.push(v.to_owned());
-        },
-        parser::Expression::FunctionCall(fc) => {
-            for arg in &fc.arguments {
-                ref_vars.append(&mut get_ref_vars_expression(arg));
-            }
-        },
-        parser::Expression::Addition(b) => {
-            ref_vars.append(&mut get_ref_vars_expression(&b.0));
-            ref_vars.append(&mut get_ref_vars_expression(&b.1));
-        },
-        parser::Expression::Subtraction(b) => {
-            ref_vars.append(&mut get_ref_vars_expression(&b.0));
-            ref_vars.append(&mut get_ref_vars_expression(&b.1));
-        },
-        parser::Expression::Multiplication(b) => {
-            ref_vars.append(&mut get_ref_vars_expression(&b.0));
-            ref_vars.append(&mut get_ref_vars_expression(&b.1));
-        },
-        parser::Expression::Division(b) => {
-            ref_vars.append(&mut get_ref_vars_expression(&b.0));
-            ref_vars.append(&mut get_ref_vars_expression(&b.1));
-        },
-        parser::Expression::Modulo(b) => {
-            ref_vars.append(&mut get_ref_vars_expression(&b.0));
-            ref_vars.append(&mut get_ref_vars_expression(&b.1));
-        },
-        parser::Expression::Greater(b) => {
-            ref_vars.append(&mut get_ref_vars_expression(&b.0));
-            ref_vars.append(&mut get_ref_vars_expression(&b.1));
-        },
-        parser::Expression::GreaterEquals(b) => {
-            ref_vars.append(&mut get_ref_vars_expression(&b.0));
-            ref_vars.append(&mut get_ref_vars_expression(&b.1));
-        },
-        parser::Expression::Less(b) => {
-            ref_vars.append(&mut get_ref_vars_expression(&b.0));
-            ref_vars.append(&mut get_ref_vars_expression(&b.1));
-        },
-        parser::Expression::LessEquals(b) => {
-            ref_vars.append(&mut get_ref_vars_expression(&b.0));
-            ref_vars.append(&mut get_ref_vars_expression(&b.1));
-        },
-        parser::Expression::Equals(b) => {
-            ref_vars.append(&mut get_ref_vars_expression(&b.0));
-            ref_vars.append(&mut get_ref_vars_expression(&b.1));
-        },
-        parser::Expression::NotEquals(b) => {
-            ref_vars.append(&mut get_ref_vars_expression(&b.0));
-            ref_vars.append(&mut get_ref_vars_expression(&b.1));
-        },
-        parser::Expression::LogicAnd(b) => {
-            ref_vars.append(&mut get_ref_vars_expression(&b.0));
-            ref_vars.append(&mut get_ref_vars_expression(&b.1));
-        },
-        parser::Expression::LogicOr(b) => {
-            ref_vars.append(&mut get_ref_vars_expression(&b.0));
-            ref_vars.append(&mut get_ref_vars_expression(&b.1));
-        },
+fn convert_assignment(assignment: &parser::Assignment, var_tracker: &mut VariableTracker) -> SsaStatement {
+    let new_expression = convert_expression(&assignment.expression, var_tracker);
+    if var_tracker.is_global(&assignment.variable_name) {
+        return SsaStatement::GlobalAssignment(SsaAssignment { variable_name: assignment.variable_name.to_owned(), expression: new_expression });
     }
-    ref_vars.iter().unique().cloned().collect()
+    let new_var = var_tracker.get_new(&assignment.variable_name);
+    SsaStatement::Assignment(SsaAssignment { variable_name: new_var, expression: new_expression })
 }
 
-fn convert_block(block: &parser::Block, var_tracker: &mut VariableTracker) -> SsaBlock {
+//while loops and for loops (the init assignment of a for loop is converted before)
+fn convert_loop(condition: &parser::Expression, block: &parser::Block, step: Option<&parser::Assignment>, var_tracker: &mut VariableTracker) -> SsaStatement {
+    let new_condition = convert_expression(condition, var_tracker);
+    let mut assigned_vars: Vec<VariableName> = get_assigned_variables_in_block(block, var_tracker);
+    if let Some(step) = step {
+        if var_tracker.get_current_optinal(&step.variable_name).is_some() {
+            assigned_vars.push(step.variable_name.to_owned());
+        }
+    }
+    let assigned_vars: Vec<VariableName> = assigned_vars.into_iter().unique().collect();
+    let outer_var_names: Vec<VariableName> = assigned_vars.iter().map(|v| var_tracker.get_current(v)).collect();
+    let inner_loop_vars: LoopVariables = assigned_vars.iter().cloned().zip(outer_var_names.iter().cloned()).collect();
+    let versions_before = var_tracker.get_versions();
+    var_tracker.push_scope();
+    let new_inner_block = convert_block(block, var_tracker, Some(&inner_loop_vars));
+    var_tracker.pop_scope();
+    let inner_var_names: Vec<VariableName> = assigned_vars.iter().map(|v| var_tracker.get_current(v)).collect();
+    let mut phi_nodes = vec![];
+    let mut loop_phi_nodes = vec![];
+    for (var, outer, inner) in itertools::izip!(&assigned_vars, &outer_var_names, inner_var_names) {
+        //the condition and the block read the outer version -> update it for the next iteration
+        loop_phi_nodes.push(LoopPhiNode{condition_var: outer.to_owned(), inner_var: inner.to_owned()});
+        phi_nodes.push(PhiNode{result_var: var_tracker.get_new(var),inner_option: inner, outer_option: outer.to_owned()});
+    }
+
+    //the step is reached after the block or by continue, both update the outer versions first
+    let new_step = step.map(|step| {
+        let versions_after = var_tracker.get_versions();
+        var_tracker.set_versions(versions_before);
+        var_tracker.push_scope();
+        let new_step_block = vec![convert_assignment(step, var_tracker)];
+        var_tracker.pop_scope();
+        let step_phi_nodes = assigned_vars.iter().zip(outer_var_names.iter())
+            .filter(|(var, _)| **var == step.variable_name)
+            .map(|(var, outer)| LoopPhiNode{condition_var: outer.to_owned(), inner_var: var_tracker.get_current(var)})
+            .collect();
+        var_tracker.set_versions(versions_after);
+        SsaLoopStep { block: new_step_block, phi_nodes: step_phi_nodes }
+    });
+
+    SsaStatement::WhileLoop(SsaWhileLoop {condition: new_condition, block: new_inner_block, step: new_step}, phi_nodes, loop_phi_nodes)
+}
+
+fn convert_block(block: &parser::Block, var_tracker: &mut VariableTracker, loop_vars: Option<&LoopVariables>) -> SsaBlock {
     let mut new_block = vec![];
     for statement in block {
         match statement {
             parser::Statement::Assignment(a) => {
-                let new_inner_block = convert_expression(&a.expression, var_tracker);
-                let new_var = var_tracker.get_new(&a.variable_name.to_owned());
-                new_block.push(SsaStatement::Assignment(SsaAssignment { variable_name: new_var, expression: new_inner_block }));
+                new_block.push(convert_assignment(a, var_tracker));
+            },
+            parser::Statement::ArrayAssignment(a) => {
+                new_block.push(SsaStatement::ArrayAssignment(SsaArrayAssignment {
+                    array: convert_expression(&a.array, var_tracker),
+                    index: convert_expression(&a.index, var_tracker),
+                    expression: convert_expression(&a.expression, var_tracker)
+                }));
+            },
+            parser::Statement::FieldAssignment(a) => {
+                new_block.push(SsaStatement::FieldAssignment(SsaFieldAssignment {
+                    object: convert_expression(&a.object, var_tracker),
+                    field: a.field.to_owned(),
+                    expression: convert_expression(&a.expression, var_tracker)
+                }));
+            },
+            parser::Statement::FunctionCall(f) if var_tracker.is_function_variable(&f.name) => {
+                new_block.push(SsaStatement::IndirectCall(convert_indirect_call(f, var_tracker)));
             },
             parser::Statement::FunctionCall(f) => {
                 let mut new_args = vec![];
@@ -215,41 +383,51 @@ fn convert_block(block: &parser::Block, var_tracker: &mut VariableTracker) -> Ss
             },
             parser::Statement::IfStatement(s) => {
                 let new_condition = convert_expression(&s.condition, var_tracker);
-                let assigned_vars: Vec<VariableName> = get_assigned_variables_in_block(&s.block, var_tracker);
-                let outer_var_names: Vec<VariableName> = assigned_vars.iter().map(|v| var_tracker.get_current(v)).collect();
-                let new_inner_block = convert_block(&s.block, var_tracker);
+                let mut assigned_vars: Vec<VariableName> = get_assigned_variables_in_block(&s.block, var_tracker);
+                if let Some(else_block) = &s.else_block {
+                    assigned_vars.append(&mut get_assigned_variables_in_block(else_block, var_tracker));
+                }
+                let assigned_vars: Vec<VariableName> = assigned_vars.into_iter().unique().collect();
+                let versions_before = var_tracker.get_versions();
+                var_tracker.push_scope();
+                let new_inner_block = convert_block(&s.block, var_tracker, loop_vars);
+                var_tracker.pop_scope();
                 let inner_var_names: Vec<VariableName> = assigned_vars.iter().map(|v| var_tracker.get_current(v)).collect();
+                var_tracker.set_versions(versions_before);
+                let new_else_block = s.else_block.as_ref().map(|else_block| {
+                    var_tracker.push_scope();
+                    let new_else_block = convert_block(else_block, var_tracker, loop_vars);
+                    var_tracker.pop_scope();
+                    new_else_block
+                });
+                //without an else block these are the versions from before the if statement
+                let outer_var_names: Vec<VariableName> = assigned_vars.iter().map(|v| var_tracker.get_current(v)).collect();
 
                 let mut phi_nodes = vec![];
                 for (var, outer, inner) in itertools::izip!(assigned_vars, outer_var_names, inner_var_names) {
                     phi_nodes.push(PhiNode {result_var: var_tracker.get_new(&var), inner_option: inner, outer_option: outer})
                 }
 
-                new_block.push(SsaStatement::IfStatement(SsaIfStatement {condition: new_condition, block: new_inner_block}, phi_nodes));
+                new_block.push(SsaStatement::IfStatement(SsaIfStatement {condition: new_condition, block: new_inner_block, else_block: new_else_block}, phi_nodes));
             },
             parser::Statement::WhileLoop(l) => {
-                let new_condition = convert_expression(&l.condition, var_tracker);
-                let assigned_vars: Vec<VariableName> = get_assigned_variables_in_block(&l.block, var_tracker);
-                let outer_var_names: Vec<VariableName> = assigned_vars.iter().map(|v| var_tracker.get_current(v)).collect();
-                let new_inner_block = convert_block(&l.block, var_tracker);
-                let inner_var_names: Vec<VariableName> = assigned_vars.iter().map(|v| var_tracker.get_current(v)).collect();
-                let ref_vars_condition = get_ref_vars_expression(&new_condition);
-                let mut phi_nodes = vec![];
-                let mut loop_phi_nodes = vec![];
-                for (var, outer, inner) in itertools::izip!(assigned_vars, outer_var_names, inner_var_names) {
-                    if ref_vars_condition.contains(&outer) {
-                        loop_phi_nodes.push(LoopPhiNode{condition_var: outer.to_owned(), inner_var: inner.to_owned()});
-                    }
-                    phi_nodes.push(PhiNode{result_var: var_tracker.get_new(&var),inner_option: inner, outer_option: outer});
-
+                new_block.push(convert_loop(&l.condition, &l.block, None, var_tracker));
+            },
+            //variables that are declared by the init assignment are only visible in the loop
+            parser::Statement::ForLoop(l) => {
+                var_tracker.push_scope();
+                if let Some(init) = &l.init {
+                    new_block.push(convert_assignment(init, var_tracker));
                 }
-
-                new_block.push(SsaStatement::WhileLoop(SsaWhileLoop {condition: new_condition, block: new_inner_block}, phi_nodes, loop_phi_nodes));
+                new_block.push(convert_loop(&l.condition, &l.block, l.step.as_ref(), var_tracker));
+                var_tracker.pop_scope();
             },
             parser::Statement::Return(e) => {
                 let expr = convert_expression(e, var_tracker);
                 new_block.push(SsaStatement::Return(expr))
             }
+            parser::Statement::Break => new_block.push(SsaStatement::Break(get_loop_exit_nodes(loop_vars, var_tracker))),
+            parser::Statement::Continue => new_block.push(SsaStatement::Continue(get_loop_exit_nodes(loop_vars, var_tracker)))
         }
     }
     new_block
@@ -259,7 +437,10 @@ fn convert_block(block: &parser::Block, var_tracker: &mut VariableTracker) -> Ss
 #[derive(Debug)]
 pub struct SsaProgram {
     pub functions: Vec<SsaFunction>,
-    pub external_functions: Vec<ExternalFunction>
+    pub external_functions: Vec<ExternalFunction>,
+    pub globals: Vec<VariableName>,
+    pub strings: Vec<String>, //stored in the read-only data section
+    pub structs: Vec<parser::Struct>
 }
 
 
@@ -270,7 +451,7 @@ pub type PhiNodes = Vec<PhiNode>;
 pub struct PhiNode {
     pub result_var: VariableName,
     pub inner_option: VariableName,
-    pub outer_option: VariableName
+    pub outer_option: VariableName //if statements: version after the else block
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -286,16 +467,38 @@ pub struct SsaAssignment {
     pub expression: parser::Expression
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct SsaArrayAssignment {
+    pub array: parser::Expression,
+    pub index: parser::Expression,
+    pub expression: parser::Expression
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SsaFieldAssignment {
+    pub object: parser::Expression,
+    pub field: String,
+    pub expression: parser::Expression
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct SsaIfStatement {
     pub condition: parser::Expression,
-    pub block: SsaBlock
+    pub block: SsaBlock,
+    pub else_block: Option<SsaBlock>
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct SsaWhileLoop {
     pub condition: parser::Expression,
-    pub block: SsaBlock
+    pub block: SsaBlock,
+    pub step: Option<SsaLoopStep> //for loops
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SsaLoopStep {
+    pub block: SsaBlock,
+    pub phi_nodes: LoopPhiNodes //variables that are assigned by the step
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -315,26 +518,42 @@ pub type LoopPhiNodes = Vec<LoopPhiNode>;
 #[derive(Debug, PartialEq, Clone)]
 pub enum SsaStatement {
     Assignment(SsaAssignment),
+    GlobalAssignment(SsaAssignment),
+    ArrayAssignment(SsaArrayAssignment),
+    FieldAssignment(SsaFieldAssignment),
     IfStatement(SsaIfStatement, PhiNodes),
     WhileLoop(SsaWhileLoop, PhiNodes, LoopPhiNodes),
     FunctionCall(SsaFunctionCall),
-    Return(parser::Expression)
+    IndirectCall(parser::IndirectCall),
+    Return(parser::Expression),
+    Break(LoopPhiNodes),
+    Continue(LoopPhiNodes)
 }
 
 pub fn convert(program: &parser::Program) -> SsaProgram {
     let mut new_function = vec![];
     let mut ext_function: Vec<ExternalFunction> = vec![];
-    let mut var_tracker = VariableTracker::new();
+    let constants = semantic::get_constant_values(program).expect("the constants are checked by the semantic analysis");
+    let globals: Vec<VariableName> = program.globals.iter().map(|g| g.variable_name.to_owned()).collect();
+    let mut var_tracker = VariableTracker::new(constants, globals.clone());
     for function in &program.functions {
         match function{
             parser::Function::Internal(f) => {
+                var_tracker.push_scope();
                 let new_parameters = f.parameters.iter().map(|p| var_tracker.get_new(p)).collect();
-                let new_block = convert_block(&f.block, &mut var_tracker);
+                let new_block = convert_block(&f.block, &mut var_tracker, None);
+                var_tracker.pop_scope();
                 new_function.push(SsaFunction{name: f.name.to_owned(), block: new_block, parameters: new_parameters});
             }
             parser::Function::External(f) => ext_function.push(f.clone())
         }
     }
+    if !program.globals.is_empty() {
+        var_tracker.push_scope();
+        let new_block = program.globals.iter().map(|g| convert_assignment(g, &mut var_tracker)).collect();
+        var_tracker.pop_scope();
+        new_function.push(SsaFunction{name: GLOBALS_INIT_FUNCTION.to_owned(), block: new_block, parameters: vec![]});
+    }
 
-    SsaProgram { functions: new_function, external_functions: ext_function }
+    SsaProgram { functions: new_function, external_functions: ext_function, globals, strings: var_tracker.strings, structs: program.structs.clone() }
 }
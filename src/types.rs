@@ -0,0 +1,738 @@
+use std::collections::HashMap;
+use std::mem;
+use crate::parser::{self, Expression, FunctionCall, Statement, Type};
+
+#[derive(Debug, PartialEq)]
+pub enum TypeError {
+    InvalidOperand(String, Type),
+    OperandMismatch(String, Type, Type),
+    InvalidCondition(Type),
+    InvalidIndex(Type),
+    InvalidArgument(String, usize, Type, Type),
+    InvalidAssignment(String, Type, Type),
+    InvalidReturn(String, Type, Type),
+    InvalidCharacter(i64),
+    InvalidCast(Type, Type),
+    InvalidReference(String),
+}
+
+type TypeResult<T> = Result<T, TypeError>;
+
+impl std::fmt::Display for TypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidOperand(operator, found) =>
+            write!(f, "invalid operand for {}: {}", operator, found),
+            Self::OperandMismatch(operator, left, right) =>
+            write!(f, "operands of {} have different types: {} and {}", operator, left, right),
+            Self::InvalidCondition(found) =>
+            write!(f, "condition has to be bool, found {}", found),
+            Self::InvalidIndex(found) =>
+            write!(f, "array index or size has to be int, found {}", found),
+            Self::InvalidArgument(fun, position, expected, found) =>
+            write!(f, "argument {} of {} has to be {}, found {}", position, fun, expected, found),
+            Self::InvalidAssignment(name, expected, found) =>
+            write!(f, "can't assign {} to {} of type {}", found, name, expected),
+            Self::InvalidReturn(fun, expected, found) =>
+            write!(f, "{} has to return {}, found {}", fun, expected, found),
+            Self::InvalidCharacter(value) =>
+            write!(f, "not a character: {}", value),
+            Self::InvalidCast(from, to) =>
+            write!(f, "can't cast {} to {}", from, to),
+            Self::InvalidReference(fun) =>
+            write!(f, "external functions with float parameters or results can't be referenced: {}", fun),
+        }
+    }
+}
+
+// None is the type of untyped values (number literals, strings, arrays, function references and everything
+// that isn't annotated), they can be used as any type. Operators only have a typed result if an operand is typed.
+// Float literals are floats, untyped values that are used as floats are reinterpreted (floats are stored as their bits),
+// only casts convert them.
+type ValueType = Option<Type>;
+
+type FloatOperation = fn(Box<(Expression, Expression)>) -> Expression;
+
+//same result as cvttsd2si: truncated, NaN and values out of range become i64::MIN
+pub fn float_to_int(value: f64) -> i64 {
+    if value.is_nan() || value >= i64::MAX as f64 || value < i64::MIN as f64 {
+        i64::MIN
+    }else{
+        value as i64
+    }
+}
+
+//a char can be used as an int, the other way around needs a cast, floats can only be used as floats
+fn is_assignable(expected: ValueType, found: ValueType) -> bool {
+    match (expected, found) {
+        (None, _) | (_, None) => true,
+        (Some(Type::Int), Some(Type::Char)) => true,
+        (Some(e), Some(f)) => e == f
+    }
+}
+
+fn get_result_type(operands: &[ValueType], result: Type) -> ValueType {
+    if operands.iter().all(|o| o.is_none()) {
+        None
+    }else{
+        Some(result)
+    }
+}
+
+//char literals are numbers, numbers that are used as char have to be valid characters
+//numbers that are used as float are converted (instead of reinterpreting their bits)
+fn check_literal(expected: ValueType, expression: &mut Expression) -> TypeResult<()> {
+    match (expected, &*expression) {
+        (Some(Type::Char), Expression::Number(n)) if u32::try_from(*n).ok().and_then(char::from_u32).is_none() => return Err(TypeError::InvalidCharacter(*n)),
+        (Some(Type::Float), Expression::Number(n)) => *expression = Expression::Float(*n as f64),
+        (Some(Type::Float), Expression::Negation(e)) => if let Expression::Number(n) = **e {
+            *expression = Expression::Float(-(n as f64));
+        },
+        _ => ()
+    }
+    Ok(())
+}
+
+//declarations without annotation that are initialized with a number literal are ints
+fn get_initializer_type(expression: &Expression, found: ValueType) -> ValueType {
+    match expression {
+        Expression::Number(_) => Some(Type::Int),
+        Expression::Negation(e) if matches!(**e, Expression::Number(_)) => Some(Type::Int),
+        _ => found
+    }
+}
+
+fn get_operands(expression: &mut Expression) -> &mut Box<(Expression, Expression)> {
+    match expression {
+        Expression::Addition(b) | Expression::Subtraction(b) | Expression::Multiplication(b) | Expression::Division(b) |
+        Expression::Modulo(b) | Expression::Greater(b) | Expression::GreaterEquals(b) | Expression::Less(b) |
+        Expression::LessEquals(b) | Expression::Equals(b) | Expression::NotEquals(b) | Expression::LogicAnd(b) |
+        Expression::LogicOr(b) | Expression::BitwiseAnd(b) | Expression::BitwiseOr(b) | Expression::BitwiseXor(b) |
+        Expression::ShiftLeft(b) | Expression::ShiftRight(b) | Expression::ShiftRightLogical(b) => b,
+        _ => unreachable!("not a binary operation")
+    }
+}
+
+//replaces a negation or a cast by the float operation with the same operand
+fn lower_unary(expression: &mut Expression, float_operation: fn(Box<Expression>) -> Expression) {
+    match mem::replace(expression, Expression::Number(0)) {
+        Expression::Negation(e) | Expression::Cast(e, _) => *expression = float_operation(e),
+        _ => unreachable!("not a negation or a cast")
+    }
+}
+
+fn check_arithmetic(operator: &str, left: ValueType, right: ValueType) -> TypeResult<ValueType> {
+    for t in [left, right].into_iter().flatten() {
+        if t == Type::Bool || t == Type::Float {
+            return Err(TypeError::InvalidOperand(operator.to_owned(), t));
+        }
+    }
+    //calculations with chars stay chars unless an int is involved
+    if left == Some(Type::Int) || right == Some(Type::Int) {
+        return Ok(Some(Type::Int));
+    }
+    Ok(left.or(right))
+}
+
+fn check_ordering(operator: &str, left: ValueType, right: ValueType) -> TypeResult<ValueType> {
+    if left == Some(Type::Bool) || right == Some(Type::Bool) {
+        return Err(TypeError::InvalidOperand(operator.to_owned(), Type::Bool));
+    }
+    Ok(get_result_type(&[left, right], Type::Bool))
+}
+
+//bools can only be compared with bools
+fn check_equality(operator: &str, left: ValueType, right: ValueType) -> TypeResult<ValueType> {
+    if let (Some(l), Some(r)) = (left, right) {
+        if (l == Type::Bool) != (r == Type::Bool) {
+            return Err(TypeError::OperandMismatch(operator.to_owned(), l, r));
+        }
+    }
+    Ok(get_result_type(&[left, right], Type::Bool))
+}
+
+fn check_logic(operator: &str, left: ValueType, right: ValueType) -> TypeResult<ValueType> {
+    for t in [left, right].into_iter().flatten() {
+        if t != Type::Bool {
+            return Err(TypeError::InvalidOperand(operator.to_owned(), t));
+        }
+    }
+    Ok(get_result_type(&[left, right], Type::Bool))
+}
+
+//bitwise operators work on bools and on numbers but not on a mix of them
+fn check_bitwise(operator: &str, left: ValueType, right: ValueType) -> TypeResult<ValueType> {
+    if let (Some(l), Some(r)) = (left, right) {
+        if (l == Type::Bool) != (r == Type::Bool) {
+            return Err(TypeError::OperandMismatch(operator.to_owned(), l, r));
+        }
+    }
+    if left == Some(Type::Bool) || right == Some(Type::Bool) {
+        return Ok(Some(Type::Bool));
+    }
+    check_arithmetic(operator, left, right)
+}
+
+//true if one of the operands is a float, the other one has to be a float or untyped
+fn check_float_operands(operator: &str, b: &mut (Expression, Expression), left: ValueType, right: ValueType) -> TypeResult<bool> {
+    match (left, right) {
+        (Some(Type::Float), Some(Type::Float)) => Ok(true),
+        (Some(Type::Float), None) => check_literal(left, &mut b.1).map(|_| true),
+        (None, Some(Type::Float)) => check_literal(right, &mut b.0).map(|_| true),
+        (Some(l), Some(r)) if l == Type::Float || r == Type::Float => Err(TypeError::OperandMismatch(operator.to_owned(), l, r)),
+        _ => Ok(false)
+    }
+}
+
+struct TypeChecker {
+    signatures: HashMap<String, (Vec<ValueType>, ValueType)>,
+    float_externals: Vec<String>, //external functions that get floats in the xmm registers
+    constants: HashMap<String, ValueType>,
+    globals: HashMap<String, ValueType>,
+    scopes: Vec<HashMap<String, ValueType>>, //same rules as the semantic check
+    function_name: String,
+    return_type: ValueType
+}
+
+impl TypeChecker {
+    fn get_variable_type(&self, name: &str) -> Option<ValueType> {
+        self.constants.get(name)
+            .or(self.globals.get(name))
+            .or(self.scopes.iter().rev().find_map(|s| s.get(name)))
+            .copied()
+    }
+
+    //calls of variables are indirect calls (same rules as the semantic check), their arguments are not checked
+    fn is_function_variable(&self, name: &str) -> bool {
+        self.globals.contains_key(name) || self.scopes.iter().any(|s| s.contains_key(name))
+    }
+
+    //operators that exist for floats are replaced by the float operator if an operand is a float
+    fn check_binary(&self, expression: &mut Expression, operator: &str, check: fn(&str, ValueType, ValueType) -> TypeResult<ValueType>, float_operation: Option<(FloatOperation, Type)>) -> TypeResult<ValueType> {
+        let b = get_operands(expression);
+        let left = self.check_expression(&mut b.0)?;
+        let right = self.check_expression(&mut b.1)?;
+        if let Some((float_operation, result)) = float_operation {
+            if check_float_operands(operator, b, left, right)? {
+                let operands = mem::replace(b, Box::new((Expression::Number(0), Expression::Number(0))));
+                *expression = float_operation(operands);
+                return Ok(Some(result));
+            }
+        }
+        check(operator, left, right)
+    }
+
+    fn check_unary_arithmetic(&self, operator: &str, e: &mut Expression) -> TypeResult<ValueType> {
+        match self.check_expression(e)? {
+            Some(t) if t == Type::Bool || t == Type::Float => Err(TypeError::InvalidOperand(operator.to_owned(), t)),
+            t => Ok(t)
+        }
+    }
+
+    fn check_index(&self, e: &mut Expression) -> TypeResult<()> {
+        match self.check_expression(e)? {
+            Some(t) if t == Type::Bool || t == Type::Float => Err(TypeError::InvalidIndex(t)),
+            _ => Ok(())
+        }
+    }
+
+    fn check_condition(&self, e: &mut Expression) -> TypeResult<()> {
+        match self.check_expression(e)? {
+            Some(t) if t != Type::Bool => Err(TypeError::InvalidCondition(t)),
+            _ => Ok(())
+        }
+    }
+
+    fn check_function_call(&self, function_call: &mut FunctionCall) -> TypeResult<ValueType> {
+        if self.is_function_variable(&function_call.name) {
+            for arg in &mut function_call.arguments {
+                self.check_expression(arg)?;
+            }
+            return Ok(None);
+        }
+        let (parameter_types, return_type) = self.signatures.get(&function_call.name).expect("functions are checked by the semantic analysis");
+        for (i, (arg, expected)) in function_call.arguments.iter_mut().zip(parameter_types.iter()).enumerate() {
+            let found = self.check_expression(arg)?;
+            check_literal(*expected, arg)?;
+            if !is_assignable(*expected, found) {
+                return Err(TypeError::InvalidArgument(function_call.name.to_owned(), i + 1, expected.unwrap(), found.unwrap()));
+            }
+        }
+        Ok(*return_type)
+    }
+
+    fn check_expression(&self, expression: &mut Expression) -> TypeResult<ValueType> {
+        match expression {
+            Expression::Number(_) | Expression::String(_) => Ok(None),
+            Expression::Float(_) => Ok(Some(Type::Float)),
+            //references are called with the integer registers
+            Expression::FunctionReference(name) => match self.float_externals.contains(name) {
+                true => Err(TypeError::InvalidReference(name.to_owned())),
+                false => Ok(None)
+            },
+            Expression::Variable(v) | Expression::Global(v) => Ok(self.get_variable_type(v).flatten()),
+            Expression::Addition(_) => self.check_binary(expression, "+", check_arithmetic, Some((Expression::FloatAddition, Type::Float))),
+            Expression::Subtraction(_) => self.check_binary(expression, "-", check_arithmetic, Some((Expression::FloatSubtraction, Type::Float))),
+            Expression::Multiplication(_) => self.check_binary(expression, "*", check_arithmetic, Some((Expression::FloatMultiplication, Type::Float))),
+            Expression::Division(_) => self.check_binary(expression, "/", check_arithmetic, Some((Expression::FloatDivision, Type::Float))),
+            Expression::Modulo(_) => self.check_binary(expression, "%", check_arithmetic, None),
+            Expression::ShiftLeft(_) => self.check_binary(expression, "<<", check_arithmetic, None),
+            Expression::ShiftRight(_) => self.check_binary(expression, ">>", check_arithmetic, None),
+            Expression::ShiftRightLogical(_) => self.check_binary(expression, ">>>", check_arithmetic, None),
+            Expression::Greater(_) => self.check_binary(expression, ">", check_ordering, Some((Expression::FloatGreater, Type::Bool))),
+            Expression::GreaterEquals(_) => self.check_binary(expression, ">=", check_ordering, Some((Expression::FloatGreaterEquals, Type::Bool))),
+            Expression::Less(_) => self.check_binary(expression, "<", check_ordering, Some((Expression::FloatLess, Type::Bool))),
+            Expression::LessEquals(_) => self.check_binary(expression, "<=", check_ordering, Some((Expression::FloatLessEquals, Type::Bool))),
+            Expression::Equals(_) => self.check_binary(expression, "==", check_equality, Some((Expression::FloatEquals, Type::Bool))),
+            Expression::NotEquals(_) => self.check_binary(expression, "!=", check_equality, Some((Expression::FloatNotEquals, Type::Bool))),
+            Expression::LogicAnd(_) => self.check_binary(expression, "&&", check_logic, None),
+            Expression::LogicOr(_) => self.check_binary(expression, "||", check_logic, None),
+            Expression::BitwiseAnd(_) => self.check_binary(expression, "&", check_bitwise, None),
+            Expression::BitwiseOr(_) => self.check_binary(expression, "|", check_bitwise, None),
+            Expression::BitwiseXor(_) => self.check_binary(expression, "^", check_bitwise, None),
+            Expression::Negation(e) => match self.check_expression(e)? {
+                Some(Type::Float) => {
+                    lower_unary(expression, Expression::FloatNegation);
+                    Ok(Some(Type::Float))
+                },
+                Some(Type::Bool) => Err(TypeError::InvalidOperand("-".to_owned(), Type::Bool)),
+                t => Ok(t)
+            },
+            Expression::BitwiseNot(e) => self.check_unary_arithmetic("~", e),
+            Expression::LogicNot(e) => {
+                let t = self.check_expression(e)?;
+                check_logic("!", t, None)
+            },
+            Expression::FunctionCall(f) => self.check_function_call(f),
+            Expression::IndirectCall(c) => {
+                self.check_expression(&mut c.function)?;
+                for arg in &mut c.arguments {
+                    self.check_expression(arg)?;
+                }
+                Ok(None)
+            },
+            Expression::NewArray(e) => {
+                self.check_index(e)?;
+                Ok(None)
+            },
+            Expression::ArrayLength(e) => {
+                self.check_expression(e)?;
+                Ok(None)
+            },
+            Expression::ArrayAccess(b) => {
+                self.check_expression(&mut b.0)?;
+                self.check_index(&mut b.1)?;
+                Ok(None)
+            },
+            Expression::Cast(e, t) => {
+                let t = *t;
+                let found = self.check_expression(e)?;
+                match (found, t) {
+                    (Some(Type::Bool), Type::Float) | (Some(Type::Float), Type::Bool) => return Err(TypeError::InvalidCast(found.unwrap(), t)),
+                    (Some(Type::Int | Type::Char) | None, Type::Float) => lower_unary(expression, Expression::IntToFloat),
+                    (Some(Type::Float), Type::Int | Type::Char) => lower_unary(expression, Expression::FloatToInt),
+                    _ => ()
+                }
+                Ok(Some(t))
+            },
+            //already lowered
+            Expression::FloatAddition(b) | Expression::FloatSubtraction(b) | Expression::FloatMultiplication(b) | Expression::FloatDivision(b) => {
+                self.check_expression(&mut b.0)?;
+                self.check_expression(&mut b.1)?;
+                Ok(Some(Type::Float))
+            },
+            Expression::FloatGreater(b) | Expression::FloatGreaterEquals(b) | Expression::FloatLess(b) |
+            Expression::FloatLessEquals(b) | Expression::FloatEquals(b) | Expression::FloatNotEquals(b) => {
+                self.check_expression(&mut b.0)?;
+                self.check_expression(&mut b.1)?;
+                Ok(Some(Type::Bool))
+            },
+            Expression::FloatNegation(e) | Expression::IntToFloat(e) => {
+                self.check_expression(e)?;
+                Ok(Some(Type::Float))
+            },
+            Expression::FloatToInt(e) => {
+                self.check_expression(e)?;
+                Ok(Some(Type::Int))
+            },
+            //fields are untyped like array elements
+            Expression::NewStruct(n) => {
+                for (_, field) in &mut n.fields {
+                    self.check_expression(field)?;
+                }
+                Ok(None)
+            },
+            Expression::FieldAccess(e, _) => {
+                self.check_expression(e)?;
+                Ok(None)
+            },
+        }
+    }
+
+    //constants and globals get the annotated or the inferred type
+    fn check_declaration(&self, declaration: &mut parser::Assignment) -> TypeResult<ValueType> {
+        let found = self.check_expression(&mut declaration.expression)?;
+        check_literal(declaration.variable_type, &mut declaration.expression)?;
+        if !is_assignable(declaration.variable_type, found) {
+            return Err(TypeError::InvalidAssignment(declaration.variable_name.to_owned(), declaration.variable_type.unwrap(), found.unwrap()));
+        }
+        Ok(declaration.variable_type.or(get_initializer_type(&declaration.expression, found)))
+    }
+
+    fn check_assignment(&mut self, assignment: &mut parser::Assignment) -> TypeResult<()> {
+        let name = &assignment.variable_name.to_owned();
+        let found = self.check_expression(&mut assignment.expression)?;
+        let expected = if assignment.declaration {
+            //declared variables get the annotated or the inferred type
+            self.scopes.last_mut().unwrap().insert(name.to_owned(), assignment.variable_type.or(get_initializer_type(&assignment.expression, found)));
+            assignment.variable_type
+        }else{
+            self.get_variable_type(name).flatten()
+        };
+        check_literal(expected, &mut assignment.expression)?;
+        if !is_assignable(expected, found) {
+            return Err(TypeError::InvalidAssignment(name.to_owned(), expected.unwrap(), found.unwrap()));
+        }
+        Ok(())
+    }
+
+    fn check_return(&self, expression: &mut Expression) -> TypeResult<()> {
+        let found = self.check_expression(expression)?;
+        check_literal(self.return_type, expression)?;
+        if !is_assignable(self.return_type, found) {
+            return Err(TypeError::InvalidReturn(self.function_name.to_owned(), self.return_type.unwrap(), found.unwrap()));
+        }
+        Ok(())
+    }
+
+    fn check_scope(&mut self, block: &mut parser::Block) -> TypeResult<()> {
+        self.scopes.push(HashMap::new());
+        let res = self.check_block(block);
+        self.scopes.pop();
+        res
+    }
+
+    fn check_block(&mut self, block: &mut parser::Block) -> TypeResult<()> {
+        for statement in block {
+            match statement {
+                Statement::Assignment(a) => self.check_assignment(a)?,
+                Statement::ArrayAssignment(a) => {
+                    self.check_expression(&mut a.array)?;
+                    self.check_index(&mut a.index)?;
+                    self.check_expression(&mut a.expression)?;
+                },
+                Statement::FieldAssignment(a) => {
+                    self.check_expression(&mut a.object)?;
+                    self.check_expression(&mut a.expression)?;
+                },
+                Statement::IfStatement(s) => {
+                    self.check_condition(&mut s.condition)?;
+                    self.check_scope(&mut s.block)?;
+                    if let Some(else_block) = &mut s.else_block {
+                        self.check_scope(else_block)?;
+                    }
+                },
+                Statement::WhileLoop(l) => {
+                    self.check_condition(&mut l.condition)?;
+                    self.check_scope(&mut l.block)?;
+                },
+                //the init assignment can declare a variable of the loop
+                Statement::ForLoop(l) => {
+                    self.scopes.push(HashMap::new());
+                    if let Some(init) = &mut l.init {
+                        self.check_assignment(init)?;
+                    }
+                    self.check_condition(&mut l.condition)?;
+                    self.check_scope(&mut l.block)?;
+                    if let Some(step) = &mut l.step {
+                        self.check_assignment(step)?;
+                    }
+                    self.scopes.pop();
+                },
+                Statement::FunctionCall(f) => {
+                    self.check_function_call(f)?;
+                },
+                Statement::Return(e) => self.check_return(e)?,
+                Statement::Break | Statement::Continue => ()
+            }
+        }
+        Ok(())
+    }
+
+    fn check_function(&mut self, function: &mut parser::InternalFunction) -> TypeResult<()> {
+        let parameters = function.parameters.iter().cloned().zip(function.parameter_types.iter().copied()).collect();
+        self.scopes = vec![parameters];
+        self.function_name = function.name.to_owned();
+        self.return_type = function.return_type;
+        self.check_block(&mut function.block)
+    }
+}
+
+//has to be called after the semantic check, replaces the operations on floats by the float operations
+pub fn check(program: &mut parser::Program) -> TypeResult<()> {
+    let signatures = program.functions.iter().map(|fun|
+        match fun {
+            parser::Function::Internal(f) => (f.name.to_owned(), (f.parameter_types.to_owned(), f.return_type)),
+            parser::Function::External(f) => (f.name.to_owned(), (f.parameter_types.to_owned(), f.return_type))
+        }
+    ).collect();
+    let float_externals = program.functions.iter().filter_map(|fun|
+        match fun {
+            parser::Function::External(f) if f.parameter_types.contains(&Some(Type::Float)) || f.return_type == Some(Type::Float) => Some(f.name.to_owned()),
+            _ => None
+        }
+    ).collect();
+    let mut checker = TypeChecker {
+        signatures,
+        float_externals,
+        constants: HashMap::new(),
+        //every global can be called, even the ones that are declared later
+        globals: program.globals.iter().map(|g| (g.variable_name.to_owned(), None)).collect(),
+        scopes: vec![],
+        function_name: String::new(),
+        return_type: None
+    };
+    for constant in &mut program.constants {
+        let t = checker.check_declaration(constant)?;
+        checker.constants.insert(constant.variable_name.to_owned(), t);
+    }
+    for global in &mut program.globals {
+        let t = checker.check_declaration(global)?;
+        checker.globals.insert(global.variable_name.to_owned(), t);
+    }
+    for function in &mut program.functions {
+        if let parser::Function::Internal(f) = function {
+            checker.check_function(f)?;
+        }
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer, predefined_functions};
+    use logos::Logos;
+
+    fn check_code(code: &str) -> TypeResult<()> {
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let mut program = parser::parse(&mut lex).unwrap();
+        predefined_functions::add(&mut program);
+        check(&mut program)
+    }
+
+    #[test]
+    fn check_untyped_program() {
+        let code = "
+        fun main(a, b) {
+            if(a + b) {
+                print_char(a * 1000);
+            }
+            return a < b + (a == 1);
+        }
+        ";
+        assert_eq!(check_code(code), Ok(()));
+    }
+
+    #[test]
+    fn check_condition_type() {
+        let code = "
+        fun main(a: int, b: int) {
+            if(a + b) {
+                return 1;
+            }
+        }
+        ";
+        assert_eq!(check_code(code), Err(TypeError::InvalidCondition(Type::Int)));
+    }
+
+    #[test]
+    fn check_argument_type() {
+        let code = "
+        fun main(a: int) {
+            print_char(a);
+        }
+        ";
+        assert_eq!(check_code(code), Err(TypeError::InvalidArgument("print_char".to_owned(), 1, Type::Char, Type::Int)));
+        let code = "
+        fun main() {
+            print_char(2000000);
+        }
+        ";
+        assert_eq!(check_code(code), Err(TypeError::InvalidCharacter(2000000)));
+    }
+
+    #[test]
+    fn check_inferred_local() {
+        let code = "
+        fun is_digit(c: char) -> bool {
+            return c >= '0' && c <= '9';
+        }
+        fun main(c: char) {
+            let digit = is_digit(c);
+            let n = digit + 1;
+        }
+        ";
+        assert_eq!(check_code(code), Err(TypeError::InvalidOperand("+".to_owned(), Type::Bool)));
+    }
+
+    #[test]
+    fn check_literal_initializers() {
+        let code = "
+        fun main() {
+            let n = 3;
+            if(n) {}
+        }
+        ";
+        assert_eq!(check_code(code), Err(TypeError::InvalidCondition(Type::Int)));
+        let code = "
+        fun main() {
+            let n = 3;
+            print_char(n * 100000);
+        }
+        ";
+        assert_eq!(check_code(code), Err(TypeError::InvalidArgument("print_char".to_owned(), 1, Type::Char, Type::Int)));
+        let code = "
+        fun main() {
+            let c = 'a';
+            let x = -1;
+            let f = 2.5;
+            print_char(c + 1);
+            f = x;
+        }
+        ";
+        assert_eq!(check_code(code), Err(TypeError::InvalidAssignment("f".to_owned(), Type::Float, Type::Int)));
+    }
+
+    #[test]
+    fn check_annotated_variable() {
+        let code = "
+        fun main(a: int) {
+            let c: char = 'a';
+            c = c + 1;
+            let n: int = c;
+            c = n;
+        }
+        ";
+        assert_eq!(check_code(code), Err(TypeError::InvalidAssignment("c".to_owned(), Type::Char, Type::Int)));
+    }
+
+    #[test]
+    fn check_cast() {
+        let code = "
+        fun main(a: int) -> bool {
+            print_char(('a' + a % 26) as char);
+            return a as bool;
+        }
+        ";
+        assert_eq!(check_code(code), Ok(()));
+    }
+
+    #[test]
+    fn check_return_type() {
+        let code = "
+        fun main(a: int) -> bool {
+            return a - 1;
+        }
+        ";
+        assert_eq!(check_code(code), Err(TypeError::InvalidReturn("main".to_owned(), Type::Bool, Type::Int)));
+    }
+
+    #[test]
+    fn check_typed_globals() {
+        let code = "
+        const NEWLINE: char = 10;
+        global done = false_value();
+        fun false_value() -> bool {
+            return 0;
+        }
+        fun main() {
+            done = NEWLINE;
+        }
+        ";
+        assert_eq!(check_code(code), Err(TypeError::InvalidAssignment("done".to_owned(), Type::Bool, Type::Char)));
+    }
+
+    #[test]
+    fn check_bitwise_mix() {
+        let code = "
+        fun main(a: int, b: bool) {
+            let c = (a > 0) & b;
+            let d = a & b;
+        }
+        ";
+        assert_eq!(check_code(code), Err(TypeError::OperandMismatch("&".to_owned(), Type::Int, Type::Bool)));
+    }
+
+    #[test]
+    fn check_float_lowering() {
+        let code = "
+        fun main(a: int) -> float {
+            let x: float = 2;
+            return -x * (a as float);
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let mut program = parser::parse(&mut lex).unwrap();
+        assert_eq!(check(&mut program), Ok(()));
+        let function = match &program.functions[0] {
+            parser::Function::Internal(f) => f,
+            _ => unreachable!()
+        };
+        let expected = [
+            Statement::Assignment(parser::Assignment { variable_name: "x".to_owned(), variable_type: Some(Type::Float), declaration: true, expression: Expression::Float(2.0) }),
+            Statement::Return(Expression::FloatMultiplication(Box::new((
+                Expression::FloatNegation(Box::new(Expression::Variable("x".to_owned()))),
+                Expression::IntToFloat(Box::new(Expression::Variable("a".to_owned())))
+            ))))
+        ];
+        assert_eq!(function.block, expected);
+    }
+
+    #[test]
+    fn check_untyped_cast_to_float() {
+        let code = "
+        fun main(a) -> float {
+            return a as float;
+        }
+        ";
+        let mut lex = lexer::Token::lexer(code).peekable();
+        let mut program = parser::parse(&mut lex).unwrap();
+        assert_eq!(check(&mut program), Ok(()));
+        let function = match &program.functions[0] {
+            parser::Function::Internal(f) => f,
+            _ => unreachable!()
+        };
+        assert_eq!(function.block, [Statement::Return(Expression::IntToFloat(Box::new(Expression::Variable("a".to_owned()))))]);
+    }
+
+    #[test]
+    fn check_float_mix() {
+        let code = "
+        fun main(a: int, x: float) {
+            let y = x + 1;
+            let z = x % 2.0;
+        }
+        ";
+        assert_eq!(check_code(code), Err(TypeError::InvalidOperand("%".to_owned(), Type::Float)));
+        let code = "
+        fun main(a: int, x: float) {
+            return x < a;
+        }
+        ";
+        assert_eq!(check_code(code), Err(TypeError::OperandMismatch("<".to_owned(), Type::Float, Type::Int)));
+        let code = "
+        fun main(x: float) {
+            return x as bool;
+        }
+        ";
+        assert_eq!(check_code(code), Err(TypeError::InvalidCast(Type::Float, Type::Bool)));
+        let code = "
+        fun main() {
+            let f = &sqrt;
+        }
+        ";
+        assert_eq!(check_code(code), Err(TypeError::InvalidReference("sqrt".to_owned())));
+    }
+}